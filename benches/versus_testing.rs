@@ -3,14 +3,14 @@ extern crate bencher;
 use bencher::Bencher;
 use blobwar::{
     configuration::*,
-    strategy::{Greedy, MinMax, AlphaBeta, Strategy},
+    strategy::{AlphaBeta, Strategy},
 };
 
-fn bench_MinMax(b: &mut Bencher) {
+fn bench_alpha_beta(b: &mut Bencher) {
     let board = Default::default();
     let mut game = Configuration::new(&board);
-    let mut player_one = AlphaBeta(3); // replace the strategy if you want.
-    let mut player_two = AlphaBeta(3);
+    let mut player_one = AlphaBeta::new(3); // replace the strategy if you want.
+    let mut player_two = AlphaBeta::new(3);
     b.iter(|| {
         while !game.game_over() {
             let play_attempt = if game.current_player {
@@ -28,5 +28,5 @@ fn bench_MinMax(b: &mut Bencher) {
     });
 }
 
-benchmark_group!(benches, bench_MinMax);
+benchmark_group!(benches, bench_alpha_beta);
 benchmark_main!(benches);
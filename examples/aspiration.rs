@@ -0,0 +1,17 @@
+use blobwar::board::Board;
+use blobwar::configuration::Configuration;
+use blobwar::strategy::{iterative_deepening_with_stats, IterativeStrategy};
+
+use std::time::{Duration, Instant};
+
+fn main() {
+    for board_name in &["standard", "cross", "ataxx"] {
+        let board = Board::load(board_name).expect("failed loading board");
+        let configuration = Configuration::new(&board);
+        for strategy in &[IterativeStrategy::AlphaBeta, IterativeStrategy::Pvs] {
+            let deadline = Instant::now() + Duration::from_millis(500);
+            let (_, stats) = iterative_deepening_with_stats(*strategy, &configuration, deadline);
+            println!("{} ({:?}): {}", board_name, strategy, stats);
+        }
+    }
+}
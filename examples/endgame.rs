@@ -0,0 +1,43 @@
+use blobwar::board::Board;
+use blobwar::strategy::endgame::DEFAULT_THRESHOLD;
+use blobwar::strategy::{Endgame, Greedy, SearchControl, Strategy};
+
+use std::time::Instant;
+
+fn main() {
+    for board_name in &["path", "island", "constrained", "cross", "standard"] {
+        let board = Board::load(board_name).expect("failed loading board");
+        let mut state = board.initial_configuration();
+        // play greedily until the solver kicks in
+        while !Endgame::new(DEFAULT_THRESHOLD).applies(&state) && !state.game_over() {
+            match Greedy().compute_next_move(&state) {
+                Some(movement) => state.apply_movement(&movement),
+                None => state = state.skip_play(),
+            }
+        }
+        println!("{}", state);
+        let solvers = (1..4)
+            .map(|jump_limit| {
+                (
+                    format!("evaluated after {} plies without duplication", jump_limit),
+                    Endgame::new(DEFAULT_THRESHOLD).jump_limit(jump_limit),
+                )
+            })
+            .chain(std::iter::once((
+                "proven".to_string(),
+                Endgame::new(DEFAULT_THRESHOLD),
+            )));
+        for (name, solver) in solvers {
+            let start = Instant::now();
+            let solution = solver.solve(&state, &SearchControl::default());
+            println!(
+                "{} {}: {:?} in {:?} ({} nodes)",
+                board_name,
+                name,
+                solution,
+                start.elapsed(),
+                solver.nodes()
+            );
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use glob::glob;
+
+use blobwar::board::Board;
+use blobwar::configuration::Configuration;
+use blobwar::strategy::{AlphaBeta, Greedy, SearchControl, Strategy};
+
+fn main() {
+    let depth = 4;
+    let opening_moves = 8;
+    println!(
+        "nodes visited by alpha - beta at depth {} after {} greedy moves",
+        depth, opening_moves
+    );
+    println!("{:>12} {:>10} {:>10} {:>7}", "board", "unordered", "ordered", "ratio");
+    for board_name in glob("boards/*").expect("missing boards directory") {
+        let path = board_name.expect("failed reading boards directory");
+        let name = path.file_name().unwrap();
+        let board = Board::load(name).expect("failed loading map");
+        let mut configuration = Configuration::new(&board);
+        for _ in 0..opening_moves {
+            match Greedy().compute_next_move(&configuration) {
+                Some(movement) => configuration.apply_movement(&movement),
+                None => configuration = configuration.skip_play(),
+            }
+        }
+        let mut unordered = AlphaBeta::new(depth).ordering(false);
+        let mut ordered = AlphaBeta::new(depth);
+        let control = SearchControl::default();
+        let (unordered_value, _) = unordered.search(&configuration, &control).unwrap();
+        let (ordered_value, _) = ordered.search(&configuration, &control).unwrap();
+        assert_eq!(unordered_value, ordered_value);
+        println!(
+            "{:>12} {:>10} {:>10} {:>6.1}%",
+            name.to_str().unwrap(),
+            unordered.nodes(),
+            ordered.nodes(),
+            100.0 * ordered.nodes() as f64 / unordered.nodes() as f64
+        );
+    }
+}
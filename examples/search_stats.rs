@@ -0,0 +1,30 @@
+use blobwar::board::Board;
+use blobwar::configuration::Configuration;
+use blobwar::strategy::{AlphaBeta, AlphaBetaPar, MinMax, Pvs, Strategy};
+
+fn main() {
+    for board_name in &["standard", "cross", "ataxx"] {
+        let board = Board::load(board_name).expect("failed loading board");
+        let configuration = Configuration::new(&board);
+        let mut strategies: Vec<Box<dyn Strategy>> = vec![
+            Box::new(MinMax::new(4)),
+            Box::new(AlphaBeta::new(4)),
+            Box::new(AlphaBetaPar::new(4)),
+            Box::new(Pvs::new(4)),
+        ];
+        for strategy in &mut strategies {
+            strategy.compute_next_move(&configuration);
+            let stats = strategy
+                .search_stats()
+                .expect("searching strategies have stats");
+            println!(
+                "{} ({}): {} ({:.0} nodes/s), pv {}",
+                board_name,
+                strategy,
+                stats,
+                stats.nodes_per_second(),
+                stats.principal_variation(&board)
+            );
+        }
+    }
+}
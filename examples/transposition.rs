@@ -0,0 +1,28 @@
+use blobwar::board::Board;
+use blobwar::configuration::Configuration;
+use blobwar::strategy::{AlphaBeta, AlphaBetaPar, Strategy};
+
+fn main() {
+    for board_name in &["standard", "cross"] {
+        let board = Board::load(board_name).expect("failed loading board");
+        let configuration = Configuration::new(&board);
+        let mut sequential = AlphaBeta::new(5);
+        let mut parallel = AlphaBetaPar::new(5);
+        sequential.compute_next_move(&configuration);
+        parallel.compute_next_move(&configuration);
+        for (name, stats) in &[
+            ("sequential", sequential.table_stats()),
+            ("parallel", parallel.table_stats()),
+        ] {
+            println!(
+                "{} ({}): {} hits, {} misses ({:.1}%), {} stores",
+                board_name,
+                name,
+                stats.hits,
+                stats.misses,
+                100.0 * stats.hit_rate(),
+                stats.stores
+            );
+        }
+    }
+}
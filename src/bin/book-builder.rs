@@ -0,0 +1,117 @@
+//! Build the opening book of a board with deep alpha - beta searches.
+//!
+//! usage: book-builder [--board standard] [--depth 6] [--plies 4] [--width 3] [--output file]
+//! Every position reached by playing one of the `width` best moves of each side during the
+//! first `plies` plies gets its best move at given search depth. The book is saved in
+//! books/BOARD.json by default.
+use blobwar::board::Board;
+use blobwar::book::OpeningBook;
+use blobwar::configuration::{Configuration, Movement};
+use blobwar::strategy::{AlphaBeta, Score, SearchControl};
+
+use std::env::args;
+use std::fs::create_dir_all;
+use std::process::exit;
+use std::time::Instant;
+
+struct Builder {
+    /// Searches children of book positions, one ply shallower than the book depth.
+    alpha_beta: AlphaBeta,
+    depth: u8,
+    width: usize,
+    book: OpeningBook,
+}
+
+impl Builder {
+    /// Add given configuration and the best continuations to the book.
+    fn expand(&mut self, state: &Configuration, plies: usize) {
+        if plies == 0 || state.game_over() || self.book.probe(state).is_some() {
+            return;
+        }
+        let control = SearchControl::default();
+        // value of each move for the player to move, lower is better
+        let mut ranked: Vec<(Score, Movement)> = state
+            .movements()
+            .filter(|movement| state.check_move(movement))
+            .map(|movement| {
+                let (value, _) = self
+                    .alpha_beta
+                    .search(&state.play(&movement), &control)
+                    .expect("search was stopped");
+                (value.saturating_neg(), movement)
+            })
+            .collect();
+        ranked.sort_by_key(|&(value, _)| value);
+        let (value, best) = match ranked.first() {
+            Some(&best) => best,
+            None => return,
+        };
+        self.book.insert(state, best, value, self.depth);
+        eprintln!("{} positions", self.book.len());
+        for &(_, movement) in ranked.iter().take(self.width) {
+            self.expand(&state.play(&movement), plies - 1);
+        }
+    }
+}
+
+fn usage(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!(
+        "usage: book-builder [--board standard] [--depth 6] [--plies 4] [--width 3] [--output file]"
+    );
+    exit(1)
+}
+
+fn main() {
+    let mut board_name = "standard".to_owned();
+    let mut depth = 6;
+    let mut plies = 4;
+    let mut width = 3;
+    let mut output = None;
+    let mut arguments = args().skip(1);
+    while let Some(argument) = arguments.next() {
+        let mut value = || {
+            arguments
+                .next()
+                .unwrap_or_else(|| usage(&format!("missing value after {}", argument)))
+        };
+        let number = |value: String| -> usize {
+            value
+                .parse()
+                .unwrap_or_else(|_| usage(&format!("invalid number {}", value)))
+        };
+        match argument.as_str() {
+            "--board" => board_name = value(),
+            "--depth" => depth = number(value()).clamp(1, u8::MAX as usize) as u8,
+            "--plies" => plies = number(value()),
+            "--width" => width = number(value()).max(1),
+            "--output" => output = Some(value()),
+            _ => usage(&format!("unknown argument {}", argument)),
+        }
+    }
+    let board = Board::load(&board_name)
+        .unwrap_or_else(|e| usage(&format!("failed loading board {}: {}", board_name, e)));
+    let output = output.map(Into::into).unwrap_or_else(|| {
+        let path = OpeningBook::default_path(&board_name);
+        if let Some(directory) = path.parent() {
+            create_dir_all(directory).expect("failed creating books directory");
+        }
+        path
+    });
+
+    let start = Instant::now();
+    let mut builder = Builder {
+        alpha_beta: AlphaBeta::new(depth - 1),
+        depth,
+        width,
+        book: OpeningBook::new(&board).board_name(&board_name),
+    };
+    builder.expand(&board.initial_configuration(), plies);
+    builder.book.save(&output).expect("failed saving book");
+    println!(
+        "{} positions saved in {} ({:.1}s)",
+        builder.book.len(),
+        output.display(),
+        start.elapsed().as_secs_f64()
+    );
+}
@@ -1,15 +1,29 @@
 use blobwar::board::Board;
 use blobwar::configuration::Configuration;
-use blobwar::strategy::{IterativeDeepening, IterativeStrategy, Strategy};
+use blobwar::strategy::from_spec;
 
+use std::convert::TryFrom;
 use std::env::args;
 use std::io::Write;
 use std::io::{BufRead, BufReader};
 use std::net::TcpStream;
+use std::process::exit;
 
 fn main() {
-    let address = args().nth(1).expect("missing machine name or IP address");
-    let mut strategy = IterativeDeepening::new(IterativeStrategy::MinMax);
+    let mut address = None;
+    let mut spec = "id:minmax".to_owned();
+    let mut arguments = args().skip(1);
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "--strategy" => spec = arguments.next().expect("missing strategy spec"),
+            _ => address = Some(argument),
+        }
+    }
+    let address = address.expect("usage: client [--strategy spec] address");
+    let mut strategy = from_spec(&spec).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        exit(1)
+    });
     let mut sending =
         TcpStream::connect((address.as_str(), 12_345)).expect("failed connecting to server");
     sending.set_nodelay(true).expect("failed setting no delay");
@@ -19,10 +33,22 @@ fn main() {
         .lines()
         .map(|r| r.expect("failed reading configuration from server"))
     {
-        let board = Board::deserialize(&line);
-        let game = Configuration::deserialize(&line, &board);
+        let board: Board = match line.parse() {
+            Ok(board) => board,
+            Err(e) => {
+                eprintln!("invalid board from server: {}", e);
+                return;
+            }
+        };
+        let game = match Configuration::try_from((line.as_str(), &board)) {
+            Ok(game) => game,
+            Err(e) => {
+                eprintln!("invalid configuration from server: {}", e);
+                return;
+            }
+        };
         let next_move = strategy.compute_next_move(&game);
         serde_json::to_writer(&mut sending, &next_move).expect("sending back movement failed");
-        sending.write(b"\n").expect("newline failed");
+        sending.write_all(b"\n").expect("newline failed");
     }
 }
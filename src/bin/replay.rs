@@ -0,0 +1,78 @@
+use blobwar::configuration::Notation;
+use blobwar::record::GameRecord;
+
+use std::env::args;
+use std::io::{stdin, stdout, BufRead, IsTerminal};
+
+fn main() {
+    let path = args().nth(1).expect("missing game record file");
+    let record = GameRecord::load(&path).expect("failed loading game record");
+    let board = record
+        .rebuild_board()
+        .expect("invalid board in game record");
+    let configurations = record
+        .configurations(&board)
+        .expect("invalid movements in game record");
+    println!(
+        "{} (red) against {} (blue) on {}",
+        record.players[0],
+        record.players[1],
+        if record.board.is_empty() {
+            "unnamed board"
+        } else {
+            &record.board
+        }
+    );
+    println!("commands : [n]ext (default), [p]revious, [f]irst, [l]ast, [q]uit");
+
+    // colour blobs on terminals only
+    let colours = stdout().is_terminal();
+    let last = configurations.len() - 1;
+    let mut ply = 0;
+    let mut commands = stdin().lock().lines();
+    loop {
+        if ply == 0 {
+            println!("initial configuration");
+        } else {
+            let recorded = &record.moves[ply - 1];
+            println!(
+                "turn {}/{} : {} played {} in {:.3}s",
+                ply,
+                last,
+                ["red", "blue"][!configurations[ply].current_player as usize],
+                Notation::new(recorded.movement, &board),
+                recorded.think_time.as_secs_f64()
+            );
+        }
+        if colours {
+            println!("{:#}", configurations[ply]);
+        } else {
+            println!("{}", configurations[ply]);
+        }
+        if ply == last {
+            // forfeits decide the game, whatever the blobs
+            match (record.lost_on_time, record.illegal_move, record.result) {
+                (Some(false), _, _) => println!("RED lost on time"),
+                (Some(true), _, _) => println!("BLUE lost on time"),
+                (None, Some(false), _) => println!("RED lost by playing an illegal movement"),
+                (None, Some(true), _) => println!("BLUE lost by playing an illegal movement"),
+                (None, None, Some(margin)) if margin > 0 => println!("RED wins by {}", margin),
+                (None, None, Some(margin)) if margin < 0 => println!("BLUE wins by {}", -margin),
+                (None, None, Some(_)) => println!("DRAW"),
+                (None, None, None) => println!("game was not finished"),
+            }
+        }
+        let command = match commands.next() {
+            Some(line) => line.expect("failed reading command"),
+            None => return,
+        };
+        match command.trim() {
+            "" | "n" => ply = (ply + 1).min(last),
+            "p" => ply = ply.saturating_sub(1),
+            "f" => ply = 0,
+            "l" => ply = last,
+            "q" => return,
+            other => println!("unknown command {:?}", other),
+        }
+    }
+}
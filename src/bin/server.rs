@@ -1,23 +1,65 @@
 use blobwar::board::Board;
-use blobwar::configuration::Configuration;
-use blobwar::strategy::{IterativeDeepening, IterativeStrategy, NetworkPlayer};
+use blobwar::strategy::{from_spec, NetworkPlayer, Strategy};
 
 use std::env::args;
 use std::net::TcpListener;
+use std::process::exit;
 
-fn main() {
-    let map_name = args().nth(1).unwrap_or("standard".to_owned());
-    // we need two players
-    let listener = TcpListener::bind("0.0.0.0:12345").expect("bind failed");
-    let mut players = listener
-        .incoming()
-        .map(|c| NetworkPlayer::new(c.expect("accept failed")));
+/// Spec of players connecting to us.
+const CLIENT: &str = "client";
 
-    // create board and start game
-    let board = Board::load(map_name).expect("failed loading map");
-    let mut game = Configuration::new(&board);
-    game.battle(
-        IterativeDeepening::new(IterativeStrategy::MinMax),
-        players.next().unwrap(),
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!(
+        "usage: server [--board name] [--red spec] [--blue spec] [--record file]\n\
+         (use \"{}\" as spec for players connecting on port 12345)",
+        CLIENT
     );
+    exit(1)
+}
+
+fn main() {
+    let mut map_name = "standard".to_owned();
+    let mut specs = ["id:minmax".to_owned(), CLIENT.to_owned()];
+    let mut record_file = None;
+    let mut arguments = args().skip(1);
+    while let Some(argument) = arguments.next() {
+        if !argument.starts_with("--") {
+            // map name alone, as in older versions
+            map_name = argument;
+            continue;
+        }
+        let value = arguments
+            .next()
+            .unwrap_or_else(|| fail(&format!("missing value after {}", argument)));
+        match argument.as_str() {
+            "--board" => map_name = value,
+            "--red" => specs[0] = value,
+            "--blue" => specs[1] = value,
+            "--record" => record_file = Some(value),
+            _ => fail(&format!("unknown argument {}", argument)),
+        }
+    }
+
+    let board = Board::load(&map_name).unwrap_or_else(|e| fail(&e.to_string()));
+    let listener = if specs.iter().any(|spec| spec == CLIENT) {
+        Some(TcpListener::bind("0.0.0.0:12345").expect("bind failed"))
+    } else {
+        None
+    };
+    let [red, blue] = specs.map(|spec| -> Box<dyn Strategy> {
+        match &listener {
+            Some(listener) if spec == CLIENT => {
+                let (connection, _) = listener.accept().expect("accept failed");
+                Box::new(NetworkPlayer::new(connection).unwrap_or_else(|e| fail(&e.to_string())))
+            }
+            _ => from_spec(&spec).unwrap_or_else(|e| fail(&e.to_string())),
+        }
+    });
+
+    let mut game = board.initial_configuration();
+    let record = game.battle(red, blue).board_name(&map_name);
+    if let Some(record_file) = record_file {
+        record.save(record_file).expect("failed saving game");
+    }
 }
@@ -0,0 +1,196 @@
+//! Round-robin tournament between strategies on several boards.
+//!
+//! usage: tournament [--boards standard,cross|all] [--csv file] [--json file] [--pgn file]
+//! [--time control] spec spec...
+//! with strategy specs such as `alphabeta:5`, `minmax:3`, `greedy` or `id-alphabeta:500ms`.
+//! Games are played at the same time, except under a time control : clocks charge wall time,
+//! which should not depend on the other games.
+use blobwar::board::Board;
+use blobwar::clock::TimeControl;
+use blobwar::game::play_timed_match;
+use blobwar::pgn::PgnGame;
+use blobwar::strategy::from_spec;
+use blobwar::tournament::{play_games, standings, GameResult, Standing};
+use glob::glob;
+use serde_derive::Serialize;
+
+use std::env::args;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::process::exit;
+
+/// Write one line per game.
+fn write_csv(path: &str, games: &[GameResult]) -> std::io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    writeln!(
+        file,
+        "board,red,blue,winner,red_blobs,blue_blobs,plies,passes,red_time_ms,blue_time_ms"
+    )?;
+    for game in games {
+        writeln!(
+            file,
+            "{},{},{},{},{},{},{},{},{},{}",
+            game.board,
+            game.red,
+            game.blue,
+            game.winner,
+            game.red_blobs,
+            game.blue_blobs,
+            game.plies,
+            game.passes,
+            game.red_time_ms,
+            game.blue_time_ms
+        )?;
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct Results<'r> {
+    standings: &'r [Standing],
+    games: &'r [GameResult],
+}
+
+fn usage(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!(
+        "usage: tournament [--boards standard,cross|all] [--csv file] [--json file] [--pgn file] [--time control] spec spec..."
+    );
+    exit(1)
+}
+
+fn main() {
+    let mut boards = vec!["standard".to_owned()];
+    let mut csv = None;
+    let mut json = None;
+    let mut pgn = None;
+    let mut time_control = TimeControl::Unlimited;
+    let mut specs = Vec::new();
+    let mut arguments = args().skip(1);
+    while let Some(argument) = arguments.next() {
+        let mut value = || {
+            arguments
+                .next()
+                .unwrap_or_else(|| usage(&format!("missing value after {}", argument)))
+        };
+        match argument.as_str() {
+            "--boards" => {
+                boards = match value().as_str() {
+                    "all" => glob("boards/*")
+                        .expect("missing boards directory")
+                        .map(|path| {
+                            let path = path.expect("failed reading boards directory");
+                            path.file_name().unwrap().to_string_lossy().into_owned()
+                        })
+                        .collect(),
+                    list => list.split(',').map(str::to_owned).collect(),
+                }
+            }
+            "--csv" => csv = Some(value()),
+            "--json" => json = Some(value()),
+            "--pgn" => pgn = Some(value()),
+            "--time" => time_control = value().parse().unwrap_or_else(|e| usage(&format!("{}", e))),
+            _ => specs.push(argument),
+        }
+    }
+    if specs.len() < 2 {
+        usage("we need at least two strategies");
+    }
+    for spec in &specs {
+        if let Err(e) = from_spec(spec) {
+            usage(&e.to_string());
+        }
+    }
+    let loaded_boards: Vec<(String, Board)> = boards
+        .iter()
+        .map(|name| match Board::load(name) {
+            Ok(board) => (name.clone(), board),
+            Err(e) => usage(&format!("failed loading board {}: {}", name, e)),
+        })
+        .collect();
+
+    let pairings: Vec<(usize, [usize; 2])> = (0..loaded_boards.len())
+        .flat_map(|board| {
+            let players = specs.len();
+            (0..players).flat_map(move |red| {
+                (0..players)
+                    .filter(move |&blue| blue != red)
+                    .map(move |blue| (board, [red, blue]))
+            })
+        })
+        .collect();
+    // as many games at once as the global pool has threads, which searches keep to themselves,
+    // but timed games are played one at a time so that the other games do not slow them down
+    let concurrency = if time_control == TimeControl::Unlimited {
+        rayon::current_num_threads()
+    } else {
+        1
+    };
+    let games: Vec<GameResult> = play_games(pairings, concurrency, |(board, players)| {
+        let (name, board) = &loaded_boards[board];
+        let mut red = from_spec(&specs[players[0]]).unwrap();
+        let mut blue = from_spec(&specs[players[1]]).unwrap();
+        let start = board.initial_configuration();
+        let mut state = start;
+        let outcome = play_timed_match(&mut state, &mut *red, &mut *blue, time_control, None);
+        let mut result = GameResult::new(name, &specs, players, &outcome);
+        if pgn.is_some() {
+            let record = outcome.record(&start, &red, &blue).board_name(name);
+            let game = PgnGame::from_record(&record).expect("recorded games are valid");
+            result.game = Some(game.tag("Event", "blobwar tournament"));
+        }
+        let forfeit = match (outcome.lost_on_time, outcome.illegal_move) {
+            (Some(false), _) => ", red lost on time",
+            (Some(true), _) => ", blue lost on time",
+            (None, Some(false)) => ", red played an illegal movement",
+            (None, Some(true)) => ", blue played an illegal movement",
+            (None, None) => "",
+        };
+        eprintln!(
+            "{}: {} (red) {} - {} {} (blue){}",
+            name, result.red, result.red_blobs, result.blue_blobs, result.blue, forfeit
+        );
+        result
+    });
+
+    let (standings, crosstable) = standings(&specs, &games);
+
+    let name_width = specs.iter().map(|s| s.len()).max().unwrap_or(0);
+    print!("{:1$}", "", name_width + 3);
+    for index in 0..specs.len() {
+        print!(" {:>9}", index + 1);
+    }
+    println!(" {:>6} {:>7}", "margin", "elo");
+    for (index, standing) in standings.iter().enumerate() {
+        print!("{:>2} {:2$}", index + 1, standing.strategy, name_width);
+        for (opponent, [wins, draws, losses]) in crosstable[index].iter().enumerate() {
+            if opponent == index {
+                print!(" {:>9}", "-");
+            } else {
+                print!(" {:>9}", format!("{}/{}/{}", wins, draws, losses));
+            }
+        }
+        println!(" {:>6.1} {:>7.0}", standing.average_margin, standing.elo);
+    }
+
+    if let Some(path) = csv {
+        write_csv(&path, &games).expect("failed writing csv results");
+    }
+    if let Some(path) = json {
+        let file = BufWriter::new(File::create(path).expect("failed creating json results"));
+        serde_json::to_writer_pretty(
+            file,
+            &Results {
+                standings: &standings,
+                games: &games,
+            },
+        )
+        .expect("failed writing json results");
+    }
+    if let Some(path) = pgn {
+        let mut file = BufWriter::new(File::create(path).expect("failed creating pgn games"));
+        for game in games.iter().filter_map(|game| game.game.as_ref()) {
+            writeln!(file, "{}", game).expect("failed writing pgn games");
+        }
+    }
+}
@@ -0,0 +1,273 @@
+//! Engine speaking a UAI-like (Universal Ataxx Interface) line protocol on stdin / stdout, so
+//! that our searches can be driven by tournament managers and graphical interfaces.
+//!
+//! Supported commands :
+//! - `uai` : identification and options, ended by `uaiok`
+//! - `isready` : answered by `readyok`
+//! - `setoption name Algorithm value ALGORITHM` : minmax, alphabeta, alphabetapar or pvs
+//! - `uainewgame`
+//! - `position startpos [moves MOVE...]` or `position fen FEN [moves MOVE...]`
+//! - `go [depth N] [movetime MS] [wtime MS] [btime MS] [winc MS] [binc MS] [movestogo N]
+//!   [infinite]` : search, printing `info depth D score S nodes N time MS nps N pv MOVE...`
+//!   after each iteration and `bestmove MOVE` at the end
+//! - `stop`, `quit`
+//!
+//! As in other Ataxx engines, `btime` and `binc` are the clock of 'x' (red, moving first) and
+//! `wtime` and `winc` the clock of 'o' (blue).
+//! Positions are given in FEN (see the `fen` module). `startpos` is the 7x7 starting position
+//! of Ataxx, as tournament managers expect.
+//! Moves are in algebraic notation (see `Movement::notation`).
+use blobwar::board::Board;
+use blobwar::clock::Clock;
+use blobwar::configuration::{Configuration, Notation};
+use blobwar::strategy::{
+    deepening, AspirationStats, Iteration, IterativeStrategy, Outcome, SearchControl, MAX_DEPTH,
+};
+
+use std::io::{stdin, BufRead};
+use std::sync::Arc;
+use std::thread::{self, sleep, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Starting position of Ataxx games.
+const STARTPOS: &str = "x5o/7/7/7/7/7/o5x x 0 1";
+
+/// Score of an iteration as seen by interfaces : centi-blobs or moves until the end of the
+/// game (negative when losing).
+fn score(value: i32) -> String {
+    match Outcome::of(value) {
+        Outcome::Win(plies) => format!("mate {}", plies.div_ceil(2)),
+        Outcome::Loss(plies) => format!("mate -{}", plies.div_ceil(2)),
+        Outcome::Evaluation(value) => format!("cp {}", -value * 100),
+    }
+}
+
+/// Info line of given completed iteration.
+fn info(iteration: &Iteration, board: &Board, elapsed: Duration) -> String {
+    let nodes = iteration.stats.counters.nodes;
+    let milliseconds = elapsed.as_millis() as u64;
+    let mut line = format!(
+        "info depth {} score {} nodes {} time {} nps {} pv",
+        iteration.depth,
+        score(iteration.value),
+        nodes,
+        milliseconds,
+        nodes * 1000 / milliseconds.max(1)
+    );
+    line.push(' ');
+    if iteration.stats.principal_variation.is_empty() {
+        line.push_str(&Notation::new(iteration.movement, board).to_string());
+    } else {
+        line.push_str(&iteration.stats.principal_variation(board));
+    }
+    line
+}
+
+/// Limits given to a `go` command.
+#[derive(Default)]
+struct Limits {
+    depth: Option<u8>,
+    move_time: Option<u64>,
+    /// Remaining time and increment of each player, 'x' (red) first.
+    time: [Option<u64>; 2],
+    increment: [u64; 2],
+    moves_to_go: Option<u32>,
+    infinite: bool,
+}
+
+impl Limits {
+    fn parse(arguments: &[&str]) -> Result<Self, String> {
+        let mut limits = Limits::default();
+        let mut arguments = arguments.iter();
+        while let Some(&name) = arguments.next() {
+            if name == "infinite" {
+                limits.infinite = true;
+                continue;
+            }
+            let value = arguments
+                .next()
+                .ok_or_else(|| format!("missing value after {}", name))?;
+            let invalid = |_| format!("invalid value {} for {}", value, name);
+            match name {
+                "depth" => limits.depth = Some(value.parse().map_err(invalid)?),
+                "movetime" => limits.move_time = Some(value.parse().map_err(invalid)?),
+                "btime" => limits.time[0] = Some(value.parse().map_err(invalid)?),
+                "wtime" => limits.time[1] = Some(value.parse().map_err(invalid)?),
+                "binc" => limits.increment[0] = value.parse().map_err(invalid)?,
+                "winc" => limits.increment[1] = value.parse().map_err(invalid)?,
+                "movestogo" => limits.moves_to_go = Some(value.parse().map_err(invalid)?),
+                _ => return Err(format!("unknown go parameter {}", name)),
+            }
+        }
+        Ok(limits)
+    }
+
+    /// Thinking time of given player (None to think until stopped or the depth is reached).
+    fn thinking_time(&self, player: bool) -> Option<Duration> {
+        if self.infinite {
+            return None;
+        }
+        let clock = match (self.move_time, self.time[player as usize]) {
+            (Some(move_time), _) => Clock {
+                remaining: Some(Duration::from_millis(move_time)),
+                increment: Duration::default(),
+                moves_to_go: Some(1),
+            },
+            (None, Some(time)) => Clock {
+                remaining: Some(Duration::from_millis(time)),
+                increment: Duration::from_millis(self.increment[player as usize]),
+                moves_to_go: self.moves_to_go,
+            },
+            (None, None) => return None,
+        };
+        clock.budget()
+    }
+}
+
+/// Running search.
+struct Search {
+    control: Arc<SearchControl>,
+    worker: JoinHandle<()>,
+}
+
+impl Search {
+    /// Stop the search and wait for its best move to be printed.
+    fn stop(self) {
+        self.control.stop();
+        self.worker.join().expect("search thread panicked");
+    }
+}
+
+/// Search given FEN on a worker thread, printing info lines and the best
+/// move.
+fn start_search(position: String, algorithm: IterativeStrategy, limits: &Limits) -> Search {
+    let control = Arc::new(SearchControl::default());
+    let board = Board::from_fen(&position).expect("positions are checked when set");
+    let thinking_time = limits.thinking_time(
+        Configuration::from_fen(&position, &board)
+            .expect("positions are checked when set")
+            .current_player,
+    );
+    if let Some(thinking_time) = thinking_time {
+        let control = control.clone();
+        thread::spawn(move || {
+            sleep(thinking_time);
+            control.stop();
+        });
+    }
+    let max_depth = limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+    let search_control = control.clone();
+    let worker = thread::spawn(move || {
+        let state =
+            Configuration::from_fen(&position, &board).expect("positions are checked when set");
+        let start = Instant::now();
+        let mut best = None;
+        deepening(
+            algorithm,
+            &state,
+            &search_control,
+            max_depth,
+            &mut AspirationStats::default(),
+            |iteration| {
+                println!("{}", info(&iteration, &board, start.elapsed()));
+                best = Some(iteration.movement);
+                true
+            },
+        );
+        // if not even the first iteration completed, play anything
+        let movement = best.unwrap_or_else(|| {
+            state
+                .movements()
+                .find(|movement| state.check_move(movement))
+        });
+        println!("bestmove {}", Notation::new(movement, &board));
+    });
+    Search { control, worker }
+}
+
+/// FEN of given `position` arguments.
+fn parse_position(arguments: &[&str]) -> Result<String, String> {
+    let moves_index = arguments
+        .iter()
+        .position(|&argument| argument == "moves")
+        .unwrap_or(arguments.len());
+    let (position, moves) = arguments.split_at(moves_index);
+    let fen = match position {
+        ["startpos"] => STARTPOS.to_owned(),
+        ["fen", fields @ ..] if !fields.is_empty() => fields.join(" "),
+        _ => return Err("position is startpos or fen FEN".to_owned()),
+    };
+    let board = Board::from_fen(&fen).map_err(|e| format!("{}", e))?;
+    let mut state = Configuration::from_fen(&fen, &board).map_err(|e| format!("{}", e))?;
+    for text in moves.iter().skip(1) {
+        match state.parse_move(text).map_err(|e| format!("{}", e))? {
+            Some(movement) => state.apply_movement(&movement),
+            None => state = state.skip_play(),
+        }
+    }
+    Ok(state.to_fen())
+}
+
+fn main() {
+    let mut algorithm = IterativeStrategy::Pvs;
+    let mut position = STARTPOS.to_owned();
+    let mut search: Option<Search> = None;
+    for line in stdin().lock().lines() {
+        let line = line.expect("failed reading standard input");
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (command, arguments) = match words.split_first() {
+            Some((command, arguments)) => (*command, arguments),
+            None => continue,
+        };
+        match command {
+            "uai" => {
+                println!("id name blobwar {}", env!("CARGO_PKG_VERSION"));
+                println!("id author {}", env!("CARGO_PKG_AUTHORS"));
+                println!(
+                    "option name Algorithm type combo default pvs var minmax var alphabeta var alphabetapar var pvs"
+                );
+                println!("uaiok");
+            }
+            "isready" => println!("readyok"),
+            "setoption" => match arguments {
+                ["name", "Algorithm", "value", value] => {
+                    algorithm = match *value {
+                        "minmax" => IterativeStrategy::MinMax,
+                        "alphabeta" => IterativeStrategy::AlphaBeta,
+                        "alphabetapar" => IterativeStrategy::AlphaBetaPar,
+                        "pvs" => IterativeStrategy::Pvs,
+                        _ => {
+                            println!("info string unknown algorithm {}", value);
+                            continue;
+                        }
+                    }
+                }
+                _ => println!("info string unknown option"),
+            },
+            "uainewgame" => position = STARTPOS.to_owned(),
+            "position" => match parse_position(arguments) {
+                Ok(fen) => position = fen,
+                Err(e) => println!("info string invalid position : {}", e),
+            },
+            "go" => {
+                if let Some(running) = search.take() {
+                    running.stop();
+                }
+                match Limits::parse(arguments) {
+                    Ok(limits) => search = Some(start_search(position.clone(), algorithm, &limits)),
+                    Err(e) => println!("info string {}", e),
+                }
+            }
+            "stop" => {
+                if let Some(running) = search.take() {
+                    running.stop();
+                }
+            }
+            "quit" => break,
+            _ => println!("info string unknown command {}", command),
+        }
+    }
+    if let Some(running) = search.take() {
+        running.stop();
+    }
+}
@@ -1,14 +1,25 @@
 //! Board related features. Provides the `Board` structure storing holes.
 use std::cmp::{max, min};
 use std::fs::File;
-use std::io;
 use std::io::prelude::*;
 use std::path::Path;
+use std::str::FromStr;
 
-use super::positions::{BoardPosition, Position, Positions};
+use super::configuration::Configuration;
+use super::error::{BlobwarError, Location};
+use super::fen::{is_fen, Fen};
+use super::positions::{Position, Positions, MAX_CELLS};
+use super::symmetry::Transform;
+
+/// Default board width and height.
+const DEFAULT_SIZE: u8 = 8;
 
 /// Board representation.
 pub struct Board {
+    /// Number of columns.
+    pub width: u8,
+    /// Number of rows.
+    pub height: u8,
     /// `Positions` of the holes.
     pub holes: Positions,
     /// Associate to each position an uncompressed set of neighbouring positions (at distance 1 and at distance 2)
@@ -18,57 +29,265 @@ pub struct Board {
     pub individual_neighbours: [Vec<Vec<Position>>; 2],
     /// Associate to each `Position` the `Positions` of all its neighbours.
     pub neighbours: Vec<Positions>,
+    /// `Positions` of red and blue blobs at the start of the game.
+    pub starting_blobs: [Positions; 2],
+    /// Transforms mapping the board (its holes) onto itself, identity first.
+    pub symmetries: Vec<Transform>,
+}
+
+/// Letters of given column in algebraic notation : "a" to "z", then "aa", "ab"...
+pub(crate) fn file(x: u8) -> String {
+    let mut letters = Vec::new();
+    let mut x = x as usize + 1;
+    while x > 0 {
+        letters.push(b'a' + ((x - 1) % 26) as u8);
+        x = (x - 1) / 26;
+    }
+    letters.reverse();
+    String::from_utf8_lossy(&letters).into_owned()
 }
 
 impl Default for Board {
     fn default() -> Self {
-        Board::new(Default::default())
+        Board::new(DEFAULT_SIZE, DEFAULT_SIZE, Default::default()).expect("default board is valid")
     }
 }
 
 impl Board {
-    /// Compute new `Board` structure from given holes.
-    pub fn new(holes: Positions) -> Self {
+    /// Compute new `Board` structure of given dimensions from given holes.
+    /// Red starts in the top left and bottom right corners, blue in the two others.
+    /// Boards of less than 2x2 cells (their corners overlap) or more than `MAX_CELLS` cells are
+    /// rejected.
+    pub fn new(width: u8, height: u8, holes: Positions) -> Result<Self, BlobwarError> {
+        if width < 2 || height < 2 {
+            return Err(BlobwarError::BoardTooSmall { width, height });
+        }
+        let cells = width as usize * height as usize;
+        if cells > MAX_CELLS {
+            return Err(BlobwarError::BoardTooLarge { cells });
+        }
         let mut board = Board {
-            holes,
+            width,
+            height,
+            holes: holes.intersection_with(Positions::first(width as usize * height as usize)),
             individual_neighbours: [Vec::new(), Vec::new()],
             neighbours: Vec::new(),
+            starting_blobs: Default::default(),
+            symmetries: Vec::new(),
         };
+        board.starting_blobs = board.corners();
         board.fill_individual_neighbours();
         board.fill_neighbours();
-        board
+        board.symmetries = Transform::ALL
+            .iter()
+            .copied()
+            .filter(|&transform| board.is_symmetric(transform))
+            .collect();
+        Ok(board)
+    }
+
+    /// Load a `Board` from given file of the "boards" directory (see `parse_map`).
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, BlobwarError> {
+        let mut content = String::new();
+        File::open(Path::new("boards").join(path))?.read_to_string(&mut content)?;
+        Board::parse_map(&content)
     }
 
-    /// Load a `Board` from given file.
-    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
-        let file = File::open(Path::new("boards").join(path))?;
-        let mut bit = 1u64;
+    /// Parse a `Board` from the content of a map file.
+    /// Each line is a row, 'x' characters are holes and 'O', '.' or ' ' empty cells. Blank lines
+    /// are skipped and dimensions are inferred from the text.
+    /// 'r' and 'b' characters place red and blue starting blobs ; if there are none, players
+    /// start in the corners.
+    /// Boards smaller than 2x2, with other characters or where a starting blob would be on a
+    /// hole are rejected. Errors give line numbers in the file.
+    pub fn parse_map(content: &str) -> Result<Self, BlobwarError> {
+        let mut bit = 1u128;
         let mut positions_code = 0;
-        for line in io::BufReader::new(file).lines() {
-            for character in line?.chars() {
-                if character == 'x' {
-                    positions_code |= bit;
+        let mut blobs = [0; 2];
+        let mut width = None;
+        let mut height = 0;
+        // file line of each row
+        let mut row_lines = Vec::new();
+        for (line_number, line) in content.lines().enumerate() {
+            // spaces are cells : only blank lines and carriage returns are ignored
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() {
+                continue;
+            }
+            let line_width = line.chars().count();
+            if *width.get_or_insert(line_width) != line_width {
+                return Err(BlobwarError::UnevenRows {
+                    line: line_number + 1,
+                });
+            }
+            height += 1;
+            row_lines.push(line_number + 1);
+            if line_width * height > MAX_CELLS {
+                return Err(BlobwarError::BoardTooLarge {
+                    cells: line_width * height,
+                });
+            }
+            for (column, character) in line.chars().enumerate() {
+                match character {
+                    'x' => positions_code |= bit,
+                    'r' => blobs[0] |= bit,
+                    'b' => blobs[1] |= bit,
+                    'O' | '.' | ' ' => {}
+                    _ => {
+                        return Err(BlobwarError::InvalidCell {
+                            character,
+                            location: Location {
+                                line: line_number + 1,
+                                column: column + 1,
+                            },
+                        })
+                    }
                 }
                 bit <<= 1;
             }
         }
-        Ok(Board::new(Positions(positions_code)))
+        let width = width.ok_or(BlobwarError::EmptyBoard)?;
+        let mut board = Board::new(width as u8, height as u8, Positions(positions_code))?;
+        match blobs {
+            [0, 0] => {}
+            [0, _] | [_, 0] => return Err(BlobwarError::MissingStartingBlobs),
+            [red, blue] => board.starting_blobs = [Positions(red), Positions(blue)],
+        }
+        let on_hole = board.starting_blobs[0]
+            .union_with(board.starting_blobs[1])
+            .intersection_with(board.holes);
+        if let Some(position) = on_hole.positions().next() {
+            let (x, y) = board.coordinates(position);
+            return Err(BlobwarError::OverlappingCells {
+                location: Location {
+                    line: row_lines[y as usize],
+                    column: x as usize + 1,
+                },
+            });
+        }
+        Ok(board)
+    }
+
+    /// Initial game configuration on this board.
+    pub fn initial_configuration(&self) -> Configuration<'_> {
+        Configuration::new(self)
+    }
+
+    /// Default starting `Positions` : red in top left and bottom right corners and blue in
+    /// the two others.
+    fn corners(&self) -> [Positions; 2] {
+        let (right, bottom) = (self.width - 1, self.height - 1);
+        let corners = |(x1, y1), (x2, y2)| {
+            Positions::single(self.position(x1, y1))
+                .union_with(Positions::single(self.position(x2, y2)))
+        };
+        [
+            corners((0, 0), (right, bottom)),
+            corners((right, 0), (0, bottom)),
+        ]
+    }
+
+    /// Number of cells.
+    pub fn size(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    /// `Positions` of all cells of the board (holes included).
+    pub fn cells(&self) -> Positions {
+        Positions::first(self.size())
+    }
+
+    /// Convert 2D coordinates to board coordinates.
+    pub fn position(&self, x: u8, y: u8) -> Position {
+        y * self.width + x
+    }
+
+    /// Convert board coordinates to 2d coordinates.
+    pub fn coordinates(&self, position: Position) -> (u8, u8) {
+        (position % self.width, position / self.width)
+    }
+
+    /// Is given 2D coordinate on the board ?
+    pub fn contains(&self, x: u8, y: u8) -> bool {
+        x < self.width && y < self.height
+    }
+
+    /// Algebraic name of given position, such as "c3" : files are letters from the left
+    /// ("aa" follows "z" on wide boards) and ranks numbers from the bottom.
+    pub fn square(&self, position: Position) -> String {
+        let (x, y) = self.coordinates(position);
+        format!("{}{}", file(x), self.height - y)
+    }
+
+    /// Position of given algebraic square name (see `square`).
+    pub fn parse_square(&self, square: &str) -> Result<Position, BlobwarError> {
+        let invalid = || BlobwarError::InvalidNotation(square.to_owned());
+        let digits = square
+            .find(|c: char| !c.is_ascii_lowercase())
+            .ok_or_else(invalid)?;
+        let (file, rank) = square.split_at(digits);
+        let x = file
+            .bytes()
+            .try_fold(0usize, |x, letter| {
+                let x = x * 26 + (letter - b'a') as usize + 1;
+                if x <= self.width as usize {
+                    Some(x)
+                } else {
+                    None
+                }
+            })
+            .filter(|&x| x > 0)
+            .ok_or_else(invalid)?;
+        // no signs nor leading zeros
+        if !rank.starts_with(|c: char| ('1'..='9').contains(&c)) {
+            return Err(invalid());
+        }
+        let rank: u8 = rank.parse().map_err(|_| invalid())?;
+        if rank > self.height {
+            return Err(invalid());
+        }
+        Ok(self.position(x as u8 - 1, self.height - rank))
+    }
+
+    /// Does given transform map the board onto itself ?
+    pub fn is_symmetric(&self, transform: Transform) -> bool {
+        (!transform.swaps_axes() || self.width == self.height)
+            && self.holes.transform(transform, self.width, self.height) == self.holes
+    }
+
+    /// Image of given position by given transform.
+    pub fn transform_position(&self, position: Position, transform: Transform) -> Position {
+        let (x, y) = self.coordinates(position);
+        let (x, y) = transform.apply(x, y, self.width, self.height);
+        self.position(x, y)
+    }
+
+    /// Image of given positions by given transform.
+    pub fn transform_positions(&self, positions: Positions, transform: Transform) -> Positions {
+        positions.transform(transform, self.width, self.height)
+    }
+
+    /// Compute distance between two board coordinates.
+    pub fn distance(&self, position: Position, other: Position) -> u8 {
+        let (x1, y1) = self.coordinates(position);
+        let (x2, y2) = self.coordinates(other);
+        max((x2 as i16 - x1 as i16).abs(), (y2 as i16 - y1 as i16).abs()) as u8
     }
 
     /// Pre-compute valid neighbours for each position.
     fn fill_individual_neighbours(&mut self) {
-        for position in 0i8..64i8 {
+        let (width, height) = (self.width as i16, self.height as i16);
+        for position in 0..self.size() as i16 {
             self.individual_neighbours[0].push(Vec::new());
             self.individual_neighbours[1].push(Vec::new());
-            let x = position % 8;
-            let y = position / 8;
-            for neighbouring_x in max(0, x - 2)..=min(7, x + 2) {
-                for neighbouring_y in max(0, y - 2)..=min(7, y + 2) {
+            let x = position % width;
+            let y = position / width;
+            for neighbouring_x in max(0, x - 2)..=min(width - 1, x + 2) {
+                for neighbouring_y in max(0, y - 2)..=min(height - 1, y + 2) {
                     let distance =
                         max((neighbouring_x - x).abs(), (neighbouring_y - y).abs()) as usize;
                     if distance != 0 {
-                        let coordinate =
-                            Position::from_2d(neighbouring_x as u8, neighbouring_y as u8);
+                        let coordinate = self.position(neighbouring_x as u8, neighbouring_y as u8);
                         if !self.holes.contains(coordinate) {
                             self.individual_neighbours[distance - 1][position as usize]
                                 .push(coordinate);
@@ -81,7 +300,7 @@ impl Board {
 
     /// Compute neighbours `Positions`.
     fn fill_neighbours(&mut self) {
-        for position in 0..64 {
+        for position in 0..self.size() {
             self.neighbours.push(
                 self.individual_neighbours[0][position]
                     .iter()
@@ -91,18 +310,119 @@ impl Board {
         }
     }
 
-    /// Deserialize serialized `Configuration` into `Board`.
+    /// Parse the `Board` of a FEN (see the `fen` module) : its dimensions and holes.
+    pub fn from_fen(fen: &str) -> Result<Self, BlobwarError> {
+        let fen = Fen::parse(fen)?;
+        Board::new(fen.width, fen.height, fen.holes)
+    }
+
+    /// Deserialize serialized `Configuration` (or FEN) into `Board`.
+    /// Boards which are not 8x8 are prefixed by their dimensions, as in "7x7:".
+    /// Panics on invalid input, use `str::parse` to handle errors.
     pub fn deserialize(string: &str) -> Self {
+        string.parse().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Prefix of serialized configurations on this board (empty for 8x8 boards).
+    pub(crate) fn serialized_dimensions(&self) -> String {
+        if self.width == DEFAULT_SIZE && self.height == DEFAULT_SIZE {
+            String::new()
+        } else {
+            format!("{}x{}:", self.width, self.height)
+        }
+    }
+}
+
+impl FromStr for Board {
+    type Err = BlobwarError;
+    /// Parse the `Board` of a serialized `Configuration`, or of a FEN.
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        if is_fen(string) {
+            return Board::from_fen(string);
+        }
+        let serialized = Serialized::split(string)?;
         let mut holes = 0;
-        let mut bit = 1u64;
-        for code in string.chars().skip(1) {
+        let mut bit = 1u128;
+        for (column, code) in serialized.cells() {
             match code {
                 'h' => holes |= bit,
                 ' ' | 'r' | 'b' => {}
-                _ => panic!("invalid cell content"),
+                _ => {
+                    return Err(BlobwarError::InvalidCell {
+                        character: code,
+                        location: Location { line: 1, column },
+                    })
+                }
             }
             bit <<= 1;
         }
-        Board::new(Positions(holes))
+        Board::new(serialized.width, serialized.height, Positions(holes))
+    }
+}
+
+/// Serialized configuration split into board dimensions and content.
+pub(crate) struct Serialized<'s> {
+    pub(crate) width: u8,
+    pub(crate) height: u8,
+    /// Number of characters before the player code.
+    offset: usize,
+    /// Player code followed by cells.
+    content: &'s str,
+}
+
+impl<'s> Serialized<'s> {
+    /// Split given serialized configuration, checking its length.
+    pub(crate) fn split(string: &'s str) -> Result<Self, BlobwarError> {
+        let (width, height, offset, content) = match string.find(':') {
+            Some(index) => {
+                let invalid = || BlobwarError::InvalidDimensions(string[..index].to_owned());
+                let mut dimensions = string[..index].split('x').map(|d| d.parse::<u8>());
+                let width = dimensions
+                    .next()
+                    .ok_or_else(invalid)?
+                    .map_err(|_| invalid())?;
+                let height = dimensions
+                    .next()
+                    .ok_or_else(invalid)?
+                    .map_err(|_| invalid())?;
+                if dimensions.next().is_some() || width == 0 || height == 0 {
+                    return Err(invalid());
+                }
+                (width, height, index + 1, &string[index + 1..])
+            }
+            None => (DEFAULT_SIZE, DEFAULT_SIZE, 0, string),
+        };
+        let cells = width as usize * height as usize;
+        if cells > MAX_CELLS {
+            return Err(BlobwarError::BoardTooLarge { cells });
+        }
+        let found = content.chars().count();
+        if found != cells + 1 {
+            return Err(BlobwarError::InvalidLength {
+                expected: cells + 1,
+                found,
+            });
+        }
+        Ok(Serialized {
+            width,
+            height,
+            offset,
+            content,
+        })
+    }
+
+    /// Player code and its column.
+    pub(crate) fn player(&self) -> (usize, char) {
+        (self.offset + 1, self.content.chars().next().unwrap())
+    }
+
+    /// Iterate on cell codes with their columns.
+    pub(crate) fn cells(&self) -> impl Iterator<Item = (usize, char)> + 's {
+        let offset = self.offset;
+        self.content
+            .chars()
+            .enumerate()
+            .skip(1)
+            .map(move |(index, code)| (offset + index + 1, code))
     }
 }
@@ -0,0 +1,156 @@
+//! Opening books : best moves of the first positions of a board, computed offline (see the
+//! `book-builder` binary) so that games do not start with the same long searches again and
+//! again.
+//! Positions are stored in their canonical form (see `Configuration::canonical`) so that
+//! symmetric positions share their entry.
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::board::Board;
+use crate::configuration::{Configuration, Movement};
+use crate::error::BlobwarError;
+use crate::positions::Position;
+use crate::strategy::Score;
+
+/// Directory where books are stored by default.
+pub const BOOKS_DIRECTORY: &str = "books";
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+/// Book move of one position.
+pub struct BookEntry {
+    /// Key of the position (see `OpeningBook::key`).
+    pub key: u64,
+    /// Move to play, in the canonical form of the position.
+    pub movement: Movement,
+    /// Value of the move, seen from the player to move (lower is better, as for searches).
+    pub value: Score,
+    /// Depth of the search which found the move.
+    pub depth: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+/// Opening book of one board, stored as JSON.
+pub struct OpeningBook {
+    /// Name of the board in the "boards" directory (might be empty).
+    pub board: String,
+    /// Number of columns.
+    pub width: u8,
+    /// Number of rows.
+    pub height: u8,
+    /// Positions of the holes.
+    pub holes: Vec<Position>,
+    /// Entries sorted by key.
+    entries: Vec<BookEntry>,
+}
+
+impl OpeningBook {
+    /// Empty book for given board.
+    pub fn new(board: &Board) -> Self {
+        OpeningBook {
+            board: String::new(),
+            width: board.width,
+            height: board.height,
+            holes: board.holes.positions().collect(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Name the board of the book.
+    pub fn board_name(self, name: &str) -> Self {
+        OpeningBook {
+            board: name.to_owned(),
+            ..self
+        }
+    }
+
+    /// Default path of the book of given board.
+    pub fn default_path(board_name: &str) -> PathBuf {
+        Path::new(BOOKS_DIRECTORY).join(format!("{}.json", board_name))
+    }
+
+    /// Key under which given configuration (and all its symmetric ones) is stored.
+    pub fn key(state: &Configuration) -> u64 {
+        state.canonical_hash()
+    }
+
+    /// Was the book built for given board ?
+    pub fn matches(&self, board: &Board) -> bool {
+        self.width == board.width
+            && self.height == board.height
+            && board.holes.positions().eq(self.holes.iter().copied())
+    }
+
+    /// Number of positions in the book.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Is the book empty ?
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// All entries, sorted by key.
+    pub fn entries(&self) -> &[BookEntry] {
+        &self.entries
+    }
+
+    /// Store given move for given configuration, replacing any previous one.
+    pub fn insert(&mut self, state: &Configuration, movement: Movement, value: Score, depth: u8) {
+        let (canonical, transform) = state.canonical();
+        let entry = BookEntry {
+            key: canonical.zobrist_hash(),
+            movement: movement.transform(transform, state.board()),
+            value,
+            depth,
+        };
+        match self.entries.binary_search_by_key(&entry.key, |e| e.key) {
+            Ok(index) => self.entries[index] = entry,
+            Err(index) => self.entries.insert(index, entry),
+        }
+    }
+
+    /// Book entry of given configuration, if the book has a legal move for it.
+    /// The movement of the returned entry is mapped back from the canonical form to `state`.
+    pub fn probe(&self, state: &Configuration) -> Option<BookEntry> {
+        if !self.matches(state.board()) {
+            return None;
+        }
+        let (canonical, transform) = state.canonical();
+        let key = canonical.zobrist_hash();
+        let index = self
+            .entries
+            .binary_search_by_key(&key, |entry| entry.key)
+            .ok()?;
+        let mut entry = self.entries[index];
+        // guard against key collisions
+        if entry.movement.check_range(state.board()).is_err() {
+            return None;
+        }
+        entry.movement = entry.movement.transform(transform.inverse(), state.board());
+        if state.check_move(&entry.movement) {
+            Some(entry)
+        } else {
+            None
+        }
+    }
+
+    /// Save as JSON in given file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), BlobwarError> {
+        let file = File::create(path)?;
+        serde_json::to_writer(io::BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    /// Load from given JSON file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, BlobwarError> {
+        let file = File::open(path)?;
+        let mut book: OpeningBook = serde_json::from_reader(io::BufReader::new(file))?;
+        // hand edited files might not be sorted
+        book.entries.sort_by_key(|entry| entry.key);
+        Ok(book)
+    }
+}
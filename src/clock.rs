@@ -0,0 +1,166 @@
+//! Time controls and the clocks of players.
+//! Time controls are written as in the PGN TimeControl tag, in seconds : "-" for unlimited
+//! time, "300" for sudden death (the whole game in 5 minutes), "300+2" for a Fischer increment
+//! (2 seconds added after each move) and "10/move" for a fixed time per move.
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::error::BlobwarError;
+
+/// Longest accepted duration, in milliseconds (one day).
+pub const MAX_DURATION: u64 = 86_400_000;
+
+/// Time kept aside on each move for communications and bookkeeping.
+pub const MOVE_OVERHEAD: Duration = Duration::from_millis(20);
+
+/// Moves left in the game when the clock does not tell.
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+/// How much time players get.
+pub enum TimeControl {
+    /// No limit.
+    #[default]
+    Unlimited,
+    /// Given time for the whole game.
+    SuddenDeath(Duration),
+    /// Base time for the whole game, plus an increment after each move.
+    Fischer {
+        /// Time at the start of the game.
+        base: Duration,
+        /// Time added after each move.
+        increment: Duration,
+    },
+    /// Given time for each move, unused time is lost.
+    PerMove(Duration),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+/// Time left to a player, as given to strategies before they think.
+pub struct Clock {
+    /// Time left before losing (None when unlimited).
+    pub remaining: Option<Duration>,
+    /// Time added after each move.
+    pub increment: Duration,
+    /// Number of moves to play within the remaining time (None for the rest of the game).
+    pub moves_to_go: Option<u32>,
+}
+
+impl Clock {
+    /// Thinking time to spend on the next move (None when unlimited) : a share of the
+    /// remaining time plus the increment, never more than half of the remaining time unless
+    /// it is the last move to play with it.
+    pub fn budget(&self) -> Option<Duration> {
+        let remaining = self.remaining?;
+        let moves_to_go = self.moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+        let share = (remaining / moves_to_go).saturating_add(self.increment);
+        let share = if moves_to_go == 1 {
+            share.min(remaining)
+        } else {
+            share.min(remaining / 2)
+        };
+        Some(
+            share
+                .saturating_sub(MOVE_OVERHEAD)
+                .max(Duration::from_millis(1)),
+        )
+    }
+}
+
+impl TimeControl {
+    /// Clock of a player at the start of the game.
+    pub fn clock(self) -> Clock {
+        match self {
+            TimeControl::Unlimited => Clock::default(),
+            TimeControl::SuddenDeath(time) => Clock {
+                remaining: Some(time),
+                ..Default::default()
+            },
+            TimeControl::Fischer { base, increment } => Clock {
+                remaining: Some(base),
+                increment,
+                moves_to_go: None,
+            },
+            TimeControl::PerMove(time) => Clock {
+                remaining: Some(time),
+                increment: Duration::default(),
+                moves_to_go: Some(1),
+            },
+        }
+    }
+
+    /// Update given clock after a move which took given time.
+    /// Return false if the player ran out of time.
+    pub fn charge(self, clock: &mut Clock, think_time: Duration) -> bool {
+        let remaining = match clock.remaining {
+            Some(remaining) => remaining,
+            None => return true,
+        };
+        if think_time > remaining {
+            return false;
+        }
+        *clock = match self {
+            TimeControl::PerMove(_) => self.clock(),
+            _ => Clock {
+                remaining: Some((remaining - think_time).saturating_add(clock.increment)),
+                ..*clock
+            },
+        };
+        true
+    }
+}
+
+/// Given duration in seconds, as in "2" or "0.5".
+fn seconds(duration: Duration) -> String {
+    if duration.subsec_millis() == 0 {
+        duration.as_secs().to_string()
+    } else {
+        let seconds = format!("{:.3}", duration.as_secs_f64());
+        seconds.trim_end_matches('0').to_owned()
+    }
+}
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TimeControl::Unlimited => write!(f, "-"),
+            TimeControl::SuddenDeath(time) => write!(f, "{}", seconds(time)),
+            TimeControl::Fischer { base, increment } => {
+                write!(f, "{}+{}", seconds(base), seconds(increment))
+            }
+            TimeControl::PerMove(time) => write!(f, "{}/move", seconds(time)),
+        }
+    }
+}
+
+impl FromStr for TimeControl {
+    type Err = BlobwarError;
+    /// Parse time control from its textual form (see module documentation).
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let string = string.trim();
+        let duration = |seconds: &str| {
+            // also rejects negative, infinite and too long durations (over `MAX_DURATION`)
+            seconds
+                .parse::<f64>()
+                .ok()
+                .filter(|&seconds| seconds <= (MAX_DURATION / 1000) as f64)
+                .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+                .ok_or_else(|| BlobwarError::InvalidTimeControl(string.to_owned()))
+        };
+        if string == "-" {
+            Ok(TimeControl::Unlimited)
+        } else if let Some(time) = string.strip_suffix("/move") {
+            Ok(TimeControl::PerMove(duration(time)?))
+        } else if let Some((base, increment)) = string.split_once('+') {
+            Ok(TimeControl::Fischer {
+                base: duration(base)?,
+                increment: duration(increment)?,
+            })
+        } else {
+            Ok(TimeControl::SuddenDeath(duration(string)?))
+        }
+    }
+}
@@ -1,13 +1,22 @@
 //! Provide a `Configuration` for storing game state ; a `Movement` for storing moves to play.
+use super::board::file;
 use super::board::Board;
-use super::positions::{BoardPosition, Position, Positions};
+use super::board::Serialized;
+use super::clock::TimeControl;
+use super::error::{BlobwarError, Location};
+use super::fen::{cell_code, is_fen, player_code, Fen};
+use super::game::{play_timed_match, verbose};
+use super::positions::{Position, Positions};
+use super::record::GameRecord;
 use super::strategy::Strategy;
+use super::symmetry::Transform;
+use super::zobrist;
 use rayon::prelude::{ParallelBridge, ParallelIterator};
 use serde_derive::{Deserialize, Serialize};
+use std::convert::TryFrom;
 use std::fmt;
-use std::hash::Hash;
 use std::iter::once;
-use term;
+use std::str::FromStr;
 
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
 /// Movements : clone some blob or jump
@@ -18,25 +27,170 @@ pub enum Movement {
     Jump(u8, u8),
 }
 
+impl Movement {
+    /// Pack a possible movement into the 18 lowest bits of an integer (0 for no movement).
+    /// Used to store movements in atomics.
+    pub(crate) fn pack(movement: Option<Movement>) -> u32 {
+        match movement {
+            None => 0,
+            Some(Movement::Duplicate(destination)) => 1 << 16 | destination as u32,
+            Some(Movement::Jump(source, destination)) => {
+                2 << 16 | (source as u32) << 8 | destination as u32
+            }
+        }
+    }
+
+    /// Unpack movement packed with `pack`.
+    pub(crate) fn unpack(data: u32) -> Option<Movement> {
+        let destination = data as u8;
+        let source = (data >> 8) as u8;
+        match data >> 16 {
+            1 => Some(Movement::Duplicate(destination)),
+            2 => Some(Movement::Jump(source, destination)),
+            _ => None,
+        }
+    }
+
+    /// Check that the movement stays on given board.
+    pub fn check_range(self, board: &Board) -> Result<Self, BlobwarError> {
+        let positions = match self {
+            Movement::Duplicate(destination) => [destination, destination],
+            Movement::Jump(source, destination) => [source, destination],
+        };
+        match positions
+            .iter()
+            .copied()
+            .find(|&position| position as usize >= board.size())
+        {
+            Some(position) => Err(BlobwarError::OutOfRange {
+                position,
+                size: board.size(),
+            }),
+            None => Ok(self),
+        }
+    }
+
+    /// Cell the movement ends on.
+    pub fn destination(&self) -> Position {
+        match *self {
+            Movement::Duplicate(destination) | Movement::Jump(_, destination) => destination,
+        }
+    }
+
+    /// Image of the movement by given symmetry of given board.
+    pub fn transform(self, transform: Transform, board: &Board) -> Self {
+        match self {
+            Movement::Duplicate(destination) => {
+                Movement::Duplicate(board.transform_position(destination, transform))
+            }
+            Movement::Jump(source, destination) => Movement::Jump(
+                board.transform_position(source, transform),
+                board.transform_position(destination, transform),
+            ),
+        }
+    }
+
+    /// Algebraic notation of the movement on given board : "c3" for a duplication, "a1c3" for a
+    /// jump (see `Board::square`).
+    pub fn notation(self, board: &Board) -> Notation<'_> {
+        Notation::new(Some(self), board)
+    }
+}
+
+/// Algebraic notation of a possible movement on a board, displayed as "c3", "a1c3" or "0000"
+/// for a pass.
+pub struct Notation<'b> {
+    movement: Option<Movement>,
+    board: &'b Board,
+}
+
+impl<'b> Notation<'b> {
+    /// Notation of given movement (None for a pass) on given board.
+    pub fn new(movement: Option<Movement>, board: &'b Board) -> Self {
+        Notation { movement, board }
+    }
+}
+
+impl fmt::Display for Notation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.movement {
+            None => write!(f, "0000"),
+            Some(Movement::Duplicate(destination)) => {
+                write!(f, "{}", self.board.square(destination))
+            }
+            Some(Movement::Jump(source, destination)) => write!(
+                f,
+                "{}{}",
+                self.board.square(source),
+                self.board.square(destination)
+            ),
+        }
+    }
+}
+
+impl FromStr for Movement {
+    type Err = BlobwarError;
+    /// Parse movement from its JSON form (as sent on the network).
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        Ok(serde_json::from_str(string)?)
+    }
+}
+
+impl TryFrom<(&str, &Board)> for Movement {
+    type Error = BlobwarError;
+    /// Parse movement from its algebraic notation on given board (see `Movement::notation`).
+    /// Two adjacent squares, as in "b2c3", are a duplication.
+    /// Use `Configuration::parse_move` to also check that the movement can be played.
+    fn try_from((string, board): (&str, &Board)) -> Result<Self, Self::Error> {
+        let string = string.trim();
+        // the second square starts with the first letter following a digit
+        let split = string
+            .char_indices()
+            .skip_while(|(_, c)| !c.is_ascii_digit())
+            .find(|(_, c)| c.is_ascii_lowercase())
+            .map(|(index, _)| index);
+        let invalid = |_| BlobwarError::InvalidNotation(string.to_owned());
+        match split {
+            None => Ok(Movement::Duplicate(
+                board.parse_square(string).map_err(invalid)?,
+            )),
+            Some(index) => {
+                let source = board.parse_square(&string[..index]).map_err(invalid)?;
+                let destination = board.parse_square(&string[index..]).map_err(invalid)?;
+                if board.distance(source, destination) == 1 {
+                    Ok(Movement::Duplicate(destination))
+                } else {
+                    Ok(Movement::Jump(source, destination))
+                }
+            }
+        }
+    }
+}
+
 #[derive(Copy, Clone)]
 /// Game state. We know, who should play, what is the board and where every blob is located.
 pub struct Configuration<'a> {
     blobs: [Positions; 2],
+    /// Zobrist hash of the blobs, updated by movements (see `zobrist_hash`).
+    blobs_hash: u64,
     board: &'a Board,
     /// Who should play now.
     pub current_player: bool,
 }
 
 impl<'a> Configuration<'a> {
-    /// Create an initial game configuration out of given `Board`.
+    /// Create an initial game configuration out of given `Board`, using its starting blobs.
     pub fn new(board: &'a Board) -> Self {
+        Configuration::with_blobs(board, board.starting_blobs, false)
+    }
+
+    /// Configuration on given board with given blobs and player to move.
+    fn with_blobs(board: &'a Board, blobs: [Positions; 2], current_player: bool) -> Self {
         Configuration {
-            blobs: [
-                Positions::single(0).union_with(Positions::single(63)),
-                Positions::single(7).union_with(Positions::single(56)),
-            ],
+            blobs,
+            blobs_hash: zobrist::hash(&blobs, false),
             board,
-            current_player: false,
+            current_player,
         }
     }
 
@@ -47,6 +201,7 @@ impl<'a> Configuration<'a> {
         let destination = match *movement {
             Movement::Jump(source, destination) => {
                 self.blobs[me].remove(Positions::single(source));
+                self.blobs_hash ^= zobrist::blobs_key(me, Positions::single(source));
                 destination
             }
             Movement::Duplicate(destination) => destination,
@@ -57,6 +212,10 @@ impl<'a> Configuration<'a> {
         self.blobs[me].add(changing_blobs);
         self.blobs[me].add(Positions::single(destination));
         self.blobs[him].remove(changing_blobs);
+        self.blobs_hash ^= zobrist::blobs_key(
+            me,
+            changing_blobs.union_with(Positions::single(destination)),
+        ) ^ zobrist::blobs_key(him, changing_blobs);
         self.current_player = !self.current_player;
     }
 
@@ -74,44 +233,104 @@ impl<'a> Configuration<'a> {
         new_configuration
     }
 
+    /// The `Board` we are played on.
+    pub fn board(&self) -> &'a Board {
+        self.board
+    }
+
+    /// `Positions` of given player's blobs (false for red, true for blue).
+    pub fn blobs(&self, player: bool) -> Positions {
+        self.blobs[player as usize]
+    }
+
     /// Does target player has blob at target position set ?
     pub fn free_position_at(&self, position: Position) -> bool {
         !self.blobs[0].union_with(self.blobs[1]).contains(position)
     }
 
-    /// Iterate on all `Position`(s) of empty cells.
-    pub fn empty_cells(&self) -> impl Iterator<Item = Position> {
+    /// `Positions` of all empty cells.
+    fn empty_positions(&self) -> Positions {
         self.blobs[0]
             .union_with(self.blobs[1])
             .union_with(self.board.holes)
             .invert()
-            .positions()
+            .intersection_with(self.board.cells())
+    }
+
+    /// Iterate on all `Position`(s) of empty cells.
+    pub fn empty_cells(&self) -> impl Iterator<Item = Position> {
+        self.empty_positions().positions()
     }
 
     /// Same as empty_cells but parallel
     pub fn par_empty_cells(&self) -> impl ParallelIterator<Item = Position> {
-        self.blobs[0]
-            .union_with(self.blobs[1])
-            .union_with(self.board.holes)
-            .invert()
-            .par_positions()
+        self.empty_positions().par_positions()
+    }
+
+    /// Red blobs count minus blue blobs count.
+    pub fn red_margin(&self) -> i16 {
+        self.blobs[0].len() as i16 - self.blobs[1].len() as i16
     }
 
     /// Return the configuration value (#other_player - #current_player)
-    pub fn value(&self) -> i8 {
+    pub fn value(&self) -> i16 {
         if self.current_player {
-            (self.blobs[0].len() - self.blobs[1].len()) as i8
+            self.red_margin()
         } else {
-            -(self.blobs[0].len() - self.blobs[1].len()) as i8
+            -self.red_margin()
+        }
+    }
+
+    /// Number of adversary blobs given movement would convert.
+    pub fn captures(&self, movement: &Movement) -> u8 {
+        self.blobs[!self.current_player as usize]
+            .intersection_with(self.board.neighbours[movement.destination() as usize])
+            .len()
+    }
+
+    /// Parse given movement in algebraic notation ("0000" for a pass, returned as None) and
+    /// check it can be played.
+    pub fn parse_move(&self, notation: &str) -> Result<Option<Movement>, BlobwarError> {
+        let notation = notation.trim();
+        if notation == "0000" {
+            return if self.movements().next().is_some() {
+                Err(BlobwarError::UnplayableMove {
+                    movement: notation.to_owned(),
+                    reason: "cannot pass when moves are available",
+                })
+            } else {
+                Ok(None)
+            };
+        }
+        let movement = Movement::try_from((notation, self.board))?;
+        if self.check_move(&movement) {
+            return Ok(Some(movement));
         }
+        let player = self.blobs[self.current_player as usize];
+        let reason = match movement {
+            _ if self.board.holes.contains(movement.destination()) => "destination is a hole",
+            _ if !self.free_position_at(movement.destination()) => "destination is not empty",
+            Movement::Jump(source, _) if !player.contains(source) => {
+                "source is not a blob of the player to move"
+            }
+            Movement::Jump(..) => "jumps go two cells away",
+            Movement::Duplicate(_) => "no blob of the player to move next to the destination",
+        };
+        Err(BlobwarError::UnplayableMove {
+            movement: movement.notation(self.board).to_string(),
+            reason,
+        })
     }
 
     /// Return if given movement is correct for current configuration.
     pub fn check_move(&self, movement: &Movement) -> bool {
+        if movement.check_range(self.board).is_err() {
+            return false;
+        }
         let destination = match *movement {
             Movement::Jump(source, destination) => {
                 if !(self.blobs[self.current_player as usize].contains(source))
-                    || source.distance_to(destination) != 2
+                    || self.board.distance(source, destination) != 2
                 {
                     return false;
                 }
@@ -132,46 +351,36 @@ impl<'a> Configuration<'a> {
             && !self.blobs[1].contains(destination)
     }
 
-    /// Play a match between the given players starting from current `Configuration`.
-    pub fn battle<T: Strategy, U: Strategy>(&mut self, mut player_one: T, mut player_two: U) {
-        while !self.game_over() {
-            println!(
-                "{} player's turn (he is losing by {} before playing)",
-                ["red", "blue"][self.current_player as usize],
-                self.value()
-            );
-            println!("{}", self);
-            let play_attempt = if self.current_player {
-                player_two.compute_next_move(self)
-            } else {
-                player_one.compute_next_move(self)
-            };
-            if let Some(ref next_move) = play_attempt {
-                assert!(self.check_move(next_move));
-                self.apply_movement(next_move);
-            } else {
-                self.current_player = !self.current_player;
-            }
-        }
+    /// Play a match between the given players starting from current `Configuration`,
+    /// printing every turn.
+    /// Return the record of the game. Use `game::play_match` for silent matches.
+    /// Players have unlimited time.
+    pub fn battle<T: Strategy, U: Strategy>(&mut self, player_one: T, player_two: U) -> GameRecord {
+        self.timed_battle(player_one, player_two, TimeControl::Unlimited)
+    }
 
-        let value = self.blobs[0].len() - self.blobs[1].len();
-        match value {
-            x if x > 0 => println!("RED ({}) wins over BLUE ({})!", player_one, player_two),
-            x if x < 0 => println!("BLUE ({}) wins over RED ({})!", player_two, player_one),
-            _ => println!("DRAW!"),
-        }
-        println!("{}", self);
-        println!("GAME OVER (red value of {})", value);
+    /// Play a match as `battle` does, under given time control (see `game::play_timed_match`).
+    pub fn timed_battle<T: Strategy, U: Strategy>(
+        &mut self,
+        mut player_one: T,
+        mut player_two: U,
+        time_control: TimeControl,
+    ) -> GameRecord {
+        let start = *self;
+        let mut observer = verbose([player_one.to_string(), player_two.to_string()]);
+        let outcome = play_timed_match(
+            self,
+            &mut player_one,
+            &mut player_two,
+            time_control,
+            Some(&mut observer),
+        );
+        outcome.record(&start, &player_one, &player_two)
     }
 
     /// Return true if no empty space remains or someone died.
     pub fn game_over(&self) -> bool {
-        self.blobs[0].is_empty()
-            || self.blobs[1].is_empty()
-            || self.blobs[0]
-                .union_with(self.blobs[1])
-                .union_with(self.board.holes)
-                .is_all()
+        self.blobs[0].is_empty() || self.blobs[1].is_empty() || self.empty_positions().is_empty()
     }
 
     /// Iterate on all possible jumps for given player.
@@ -187,6 +396,7 @@ impl<'a> Configuration<'a> {
             })
     }
 
+    #[allow(dead_code)]
     fn par_jumps<'b>(&'b self) -> impl 'b + ParallelIterator<Item = Movement> {
         self.blobs[self.current_player as usize]
             .par_positions()
@@ -211,6 +421,7 @@ impl<'a> Configuration<'a> {
             .map(Movement::Duplicate)
     }
 
+    #[allow(dead_code)]
     fn par_duplicates<'b>(&'b self) -> impl 'b + ParallelIterator<Item = Movement> {
         self.par_empty_cells()
             .filter(move |&p| {
@@ -232,15 +443,54 @@ impl<'a> Configuration<'a> {
         self.movements().par_bridge()
     }
 
+    /// Zobrist hash of blobs positions and player to move.
+    /// Holes are not taken into account since they never change during a game.
+    pub fn zobrist_hash(&self) -> u64 {
+        self.blobs_hash ^ zobrist::player_key(self.current_player)
+    }
+
+    /// Image of the configuration by given symmetry of its board.
+    pub fn transformed(&self, transform: Transform) -> Self {
+        let blobs = [
+            self.board.transform_positions(self.blobs[0], transform),
+            self.board.transform_positions(self.blobs[1], transform),
+        ];
+        Configuration::with_blobs(self.board, blobs, self.current_player)
+    }
+
+    /// Canonical representative amongst the images of the configuration by the symmetries of
+    /// its board, with the transform leading to it.
+    /// Symmetric configurations share the same canonical form.
+    pub fn canonical(&self) -> (Self, Transform) {
+        self.board
+            .symmetries
+            .iter()
+            .map(|&transform| (self.transformed(transform), transform))
+            .min_by_key(|(image, _)| (image.blobs[0].0, image.blobs[1].0))
+            .unwrap_or((*self, Transform::Identity))
+    }
+
+    /// Zobrist hash of the canonical form : the same for all symmetric configurations.
+    pub fn canonical_hash(&self) -> u64 {
+        self.canonical().0.zobrist_hash()
+    }
+
     /// Serialize `Configuration` into a `String`.
     /// Use in communications with sub-processes.
+    /// Boards which are not 8x8 get their dimensions as prefix (see `Board::deserialize`).
     pub fn serialize(&self) -> String {
-        once(if self.current_player { '1' } else { '0' })
-            .chain(
+        let size = self.board.size();
+        let mut serialized = self.board.serialized_dimensions();
+        serialized.extend(
+            once(if self.current_player { '1' } else { '0' }).chain(
                 self.board
                     .holes
-                    .full_bits()
-                    .zip(self.blobs[0].full_bits().zip(self.blobs[1].full_bits()))
+                    .full_bits(size)
+                    .zip(
+                        self.blobs[0]
+                            .full_bits(size)
+                            .zip(self.blobs[1].full_bits(size)),
+                    )
                     .map(|(h, (r, b))| match (h, r, b) {
                         (true, false, false) => 'h',
                         (false, true, false) => 'r',
@@ -248,89 +498,209 @@ impl<'a> Configuration<'a> {
                         (false, false, false) => ' ',
                         _ => panic!("invalid configuration"),
                     }),
+            ),
+        );
+        serialized
+    }
+
+    /// FEN of the configuration (see the `fen` module), without move counters.
+    pub fn to_fen(&self) -> String {
+        let size = self.board.size();
+        let width = self.board.width as usize;
+        let cells: Vec<char> = self
+            .board
+            .holes
+            .full_bits(size)
+            .zip(
+                self.blobs[0]
+                    .full_bits(size)
+                    .zip(self.blobs[1].full_bits(size)),
             )
-            .collect()
+            .map(|(hole, (red, blue))| cell_code(hole, red, blue))
+            .collect();
+        let mut fen = String::new();
+        for (index, rank) in cells.chunks(width).enumerate() {
+            if index > 0 {
+                fen.push('/');
+            }
+            let mut empty = 0;
+            for &code in rank {
+                if code == ' ' {
+                    empty += 1;
+                    continue;
+                }
+                if empty > 0 {
+                    fen.push_str(&empty.to_string());
+                    empty = 0;
+                }
+                fen.push(code);
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+        }
+        fen.push(' ');
+        fen.push(player_code(self.current_player));
+        fen
+    }
+
+    /// FEN of the configuration with given halfmove clock and fullmove number.
+    pub fn to_fen_with_counters(&self, halfmove: u32, fullmove: u32) -> String {
+        format!("{} {} {}", self.to_fen(), halfmove, fullmove)
+    }
+
+    /// Parse given FEN (see the `fen` module) on given board.
+    /// Dimensions and holes must match the board's. Move counters are ignored.
+    pub fn from_fen(fen: &str, board: &'a Board) -> Result<Self, BlobwarError> {
+        let fen = Fen::parse(fen)?;
+        if (fen.width, fen.height) != (board.width, board.height) {
+            return Err(BlobwarError::InvalidDimensions(format!(
+                "{}x{}",
+                fen.width, fen.height
+            )));
+        }
+        let mismatch = fen.holes.0 ^ board.holes.0;
+        if mismatch != 0 {
+            return Err(BlobwarError::OverlappingCells {
+                location: Location {
+                    line: 1,
+                    column: fen.columns[mismatch.trailing_zeros() as usize],
+                },
+            });
+        }
+        Ok(Configuration::with_blobs(
+            board,
+            fen.blobs,
+            fen.current_player,
+        ))
     }
 
     /// Deserialize given `String` into a `Configuration`. You need to deserialize the `Board`
     /// first.
+    /// Panics on invalid input, use `Configuration::try_from` to handle errors.
     pub fn deserialize(string: &str, board: &'a Board) -> Self {
-        let mut chars = string.chars();
-        let current_player;
-        if let Some(player_char) = chars.next() {
-            current_player = match player_char {
-                '1' => true,
-                '0' => false,
-                _ => panic!("invalid player code"),
-            }
-        } else {
-            panic!("missing player code");
+        Configuration::try_from((string, board)).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl<'a> TryFrom<(&str, &'a Board)> for Configuration<'a> {
+    type Error = BlobwarError;
+    /// Parse serialized configuration (or FEN) on given board.
+    /// Holes must match the board's and blobs may not be placed on them.
+    fn try_from((string, board): (&str, &'a Board)) -> Result<Self, Self::Error> {
+        if is_fen(string) {
+            return Configuration::from_fen(string, board);
+        }
+        let serialized = Serialized::split(string)?;
+        if (serialized.width, serialized.height) != (board.width, board.height) {
+            return Err(BlobwarError::InvalidDimensions(format!(
+                "{}x{}",
+                serialized.width, serialized.height
+            )));
         }
+        let (column, player_char) = serialized.player();
+        let current_player = match player_char {
+            '1' => true,
+            '0' => false,
+            _ => {
+                return Err(BlobwarError::InvalidPlayer {
+                    character: Some(player_char),
+                    location: Location { line: 1, column },
+                })
+            }
+        };
         let mut blobs = [0; 2];
-        let mut bit = 1u64;
-        for code in chars {
+        for (position, (column, code)) in serialized.cells().enumerate() {
+            let bit = 1u128 << position;
+            let location = Location { line: 1, column };
+            let hole = board.holes.contains(position as Position);
             match code {
                 'r' => blobs[0] |= bit,
                 'b' => blobs[1] |= bit,
                 ' ' | 'h' => {}
-                _ => panic!("invalid cell content"),
+                character => {
+                    return Err(BlobwarError::InvalidCell {
+                        character,
+                        location,
+                    })
+                }
+            }
+            if hole != (code == 'h') {
+                return Err(BlobwarError::OverlappingCells { location });
             }
-            bit <<= 1;
         }
-        Configuration {
+        Ok(Configuration::with_blobs(
             board,
-            blobs: [Positions(blobs[0]), Positions(blobs[1])],
+            [Positions(blobs[0]), Positions(blobs[1])],
             current_player,
-        }
+        ))
     }
 }
 
 impl<'a> fmt::Display for Configuration<'a> {
+    /// Board with red blobs as 'x', blue ones as 'o' and holes as '-'. The alternate form
+    /// (`{:#}`) also colours blobs with ANSI codes, for terminals.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "\n  01234567 \n")?;
-        write!(f, " +--------+\n")?;
+        let width = self.board.width as usize;
+        let size = self.board.size();
+        // rows and columns are labelled as in algebraic notation (see `Board::square`)
+        let label_width = self.board.height.to_string().len();
+        let padding = " ".repeat(label_width);
+        let border = format!("{}+{}+", padding, "-".repeat(width));
+        let colours = f.alternate();
+        // files of wide boards take several letters, written top to bottom
+        let files: Vec<String> = (0..self.board.width).map(file).collect();
+        let letters = files.last().map_or(0, String::len);
+        writeln!(f)?;
+        for line in 0..letters {
+            write!(f, "{} ", padding)?;
+            for name in &files {
+                let letter = (line + name.len())
+                    .checked_sub(letters)
+                    .and_then(|index| name.chars().nth(index));
+                write!(f, "{}", letter.unwrap_or(' '))?;
+            }
+            writeln!(f, " ")?;
+        }
+        writeln!(f, "{}", border)?;
         for (index, (hole, (red, blue))) in self
             .board
             .holes
-            .full_bits()
-            .zip(self.blobs[0].full_bits().zip(self.blobs[1].full_bits()))
+            .full_bits(size)
+            .zip(
+                self.blobs[0]
+                    .full_bits(size)
+                    .zip(self.blobs[1].full_bits(size)),
+            )
             .enumerate()
         {
-            if index % 8 == 0 {
-                write!(f, "{}|", index / 8)?;
+            if index % width == 0 {
+                let rank = self.board.height as usize - index / width;
+                write!(f, "{:>1$}|", rank, label_width)?;
             }
-            let mut t = term::stdout().unwrap();
             match (hole, red, blue) {
-                (true, false, false) => write!(f, "x")?,
-                (false, true, false) => {
-                    t.fg(term::color::RED).unwrap();
-                    write!(f, "x")?;
-                    t.reset().unwrap();
-                }
-                (false, false, true) => {
-                    t.fg(term::color::CYAN).unwrap();
-                    write!(f, "o")?;
-                    t.reset().unwrap();
-                }
+                (true, false, false) => write!(f, "-")?,
+                (false, true, false) if colours => write!(f, "\x1b[31mx\x1b[0m")?,
+                (false, false, true) if colours => write!(f, "\x1b[36mo\x1b[0m")?,
+                (false, true, false) => write!(f, "x")?,
+                (false, false, true) => write!(f, "o")?,
                 (false, false, false) => write!(f, " ")?,
                 _ => panic!("invalid board: {} {} {}", hole, red, blue),
             }
-            if index % 8 == 7 {
-                write!(f, "|\n")?;
+            if index % width == width - 1 {
+                writeln!(f, "|")?;
             }
         }
-        write!(f, " +--------+")?;
+        write!(f, "{}", border)?;
         Ok(())
     }
 }
 
 impl PartialEq for Configuration<'_> {
     fn eq(&self, other: &Self) -> bool {
-        self.blobs
-            .get(0)
-            .unwrap()
-            .intersection_with(*other.blobs.get(0).unwrap())
-            .is_empty()
+        self.blobs == other.blobs
+            && self.current_player == other.current_player
+            && self.board.holes == other.board.holes
     }
 }
 
@@ -338,6 +708,6 @@ impl Eq for Configuration<'_> {}
 
 impl std::hash::Hash for Configuration<'_> {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.board.holes.0.hash(state);
+        self.zobrist_hash().hash(state);
     }
 }
@@ -0,0 +1,223 @@
+//! Errors raised when loading or parsing boards, configurations and moves.
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Location of a character in parsed text (both start at 1).
+pub struct Location {
+    /// Line number.
+    pub line: usize,
+    /// Column number.
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+#[derive(Debug)]
+/// Everything which can go wrong when reading game data.
+pub enum BlobwarError {
+    /// Reading failed.
+    Io(io::Error),
+    /// Invalid JSON.
+    Json(serde_json::Error),
+    /// Text has not the expected number of characters.
+    InvalidLength {
+        /// Expected number of characters.
+        expected: usize,
+        /// Number of characters found.
+        found: usize,
+    },
+    /// Unknown character.
+    InvalidCell {
+        /// Faulty character.
+        character: char,
+        /// Where it was found.
+        location: Location,
+    },
+    /// Player code is neither '0' nor '1'.
+    InvalidPlayer {
+        /// Faulty character (None if missing).
+        character: Option<char>,
+        /// Where it was found.
+        location: Location,
+    },
+    /// Dimensions prefix cannot be parsed.
+    InvalidDimensions(String),
+    /// FEN move counters are not two numbers.
+    InvalidCounters(String),
+    /// Board rows do not all have the same width.
+    UnevenRows {
+        /// First row with a different width.
+        line: usize,
+    },
+    /// Board has no cells.
+    EmptyBoard,
+    /// Board has more than `MAX_CELLS` cells.
+    BoardTooLarge {
+        /// Number of cells of the board.
+        cells: usize,
+    },
+    /// Board has less than two columns or two rows : starting corners would overlap.
+    BoardTooSmall {
+        /// Number of columns.
+        width: u8,
+        /// Number of rows.
+        height: u8,
+    },
+    /// Only one player has starting blobs.
+    MissingStartingBlobs,
+    /// A cell holds several things (blob on a hole, or hole missing from the board).
+    OverlappingCells {
+        /// Where the conflict was found.
+        location: Location,
+    },
+    /// A movement goes outside of the board.
+    OutOfRange {
+        /// Faulty position.
+        position: u8,
+        /// Number of cells of the board.
+        size: usize,
+    },
+    /// A recorded movement cannot be played.
+    IllegalMove {
+        /// Turn of the movement (starting at 1).
+        ply: usize,
+    },
+    /// Movement is not in algebraic notation (or leaves the board).
+    InvalidNotation(String),
+    /// A movement cannot be played on the current configuration.
+    UnplayableMove {
+        /// Movement in algebraic notation.
+        movement: String,
+        /// Why it cannot be played.
+        reason: &'static str,
+    },
+    /// Game text cannot be read or replayed.
+    InvalidGame(String),
+    /// Time control cannot be parsed.
+    InvalidTimeControl(String),
+    /// Evaluator weights are so large that evaluations could reach values of won games.
+    InvalidWeights(String),
+    /// No strategy has this name.
+    UnknownStrategy {
+        /// Faulty name.
+        name: String,
+        /// Specs of available strategies with their description.
+        available: &'static [(&'static str, &'static str)],
+    },
+    /// Strategy spec cannot be parsed.
+    InvalidSpec {
+        /// Faulty spec.
+        spec: String,
+        /// What is wrong with it.
+        reason: String,
+    },
+}
+
+impl fmt::Display for BlobwarError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BlobwarError::Io(e) => write!(f, "i/o error: {}", e),
+            BlobwarError::Json(e) => write!(f, "invalid json: {}", e),
+            BlobwarError::InvalidLength { expected, found } => write!(
+                f,
+                "invalid length: expected {} characters, found {}",
+                expected, found
+            ),
+            BlobwarError::InvalidCell {
+                character,
+                location,
+            } => write!(f, "invalid cell content {:?} at {}", character, location),
+            BlobwarError::InvalidPlayer {
+                character: Some(character),
+                location,
+            } => write!(f, "invalid player code {:?} at {}", character, location),
+            BlobwarError::InvalidPlayer {
+                character: None,
+                location,
+            } => write!(f, "missing player code at {}", location),
+            BlobwarError::InvalidDimensions(dimensions) => {
+                write!(f, "invalid board dimensions {:?}", dimensions)
+            }
+            BlobwarError::InvalidCounters(counters) => {
+                write!(f, "invalid move counters {:?}", counters)
+            }
+            BlobwarError::UnevenRows { line } => {
+                write!(f, "row at line {} has a different width", line)
+            }
+            BlobwarError::EmptyBoard => write!(f, "empty board"),
+            BlobwarError::BoardTooLarge { cells } => {
+                write!(f, "board is too large ({} cells)", cells)
+            }
+            BlobwarError::BoardTooSmall { width, height } => {
+                write!(f, "board is too small ({}x{}, at least 2x2)", width, height)
+            }
+            BlobwarError::MissingStartingBlobs => write!(f, "both players need starting blobs"),
+            BlobwarError::OverlappingCells { location } => {
+                write!(f, "overlapping cell contents at {}", location)
+            }
+            BlobwarError::OutOfRange { position, size } => write!(
+                f,
+                "position {} is outside of the board ({} cells)",
+                position, size
+            ),
+            BlobwarError::IllegalMove { ply } => write!(f, "illegal movement at turn {}", ply),
+            BlobwarError::InvalidNotation(movement) => write!(
+                f,
+                "invalid movement {:?} (expected c3, a1c3 or 0000 for a pass)",
+                movement
+            ),
+            BlobwarError::UnplayableMove { movement, reason } => {
+                write!(f, "illegal movement {}: {}", movement, reason)
+            }
+            BlobwarError::InvalidGame(reason) => write!(f, "invalid game: {}", reason),
+            BlobwarError::InvalidTimeControl(time_control) => write!(
+                f,
+                "invalid time control {:?} (expected -, 300, 300+2 or 10/move)",
+                time_control
+            ),
+            BlobwarError::InvalidWeights(weights) => write!(
+                f,
+                "invalid weights {}: evaluations could reach values of won games",
+                weights
+            ),
+            BlobwarError::UnknownStrategy { name, available } => {
+                write!(f, "unknown strategy {:?}, available strategies:", name)?;
+                for (spec, description) in available.iter() {
+                    write!(f, "\n  {:<22} {}", spec, description)?;
+                }
+                Ok(())
+            }
+            BlobwarError::InvalidSpec { spec, reason } => {
+                write!(f, "invalid spec {:?}: {}", spec, reason)
+            }
+        }
+    }
+}
+
+impl Error for BlobwarError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BlobwarError::Io(e) => Some(e),
+            BlobwarError::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for BlobwarError {
+    fn from(error: io::Error) -> Self {
+        BlobwarError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for BlobwarError {
+    fn from(error: serde_json::Error) -> Self {
+        BlobwarError::Json(error)
+    }
+}
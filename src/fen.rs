@@ -0,0 +1,147 @@
+//! FEN-like notation of configurations, as used by Ataxx programs : "x5o/7/7/7/7/7/o5x x 0 1".
+//! Ranks are given from top to bottom, separated by '/'. In each rank 'x' is a red blob, 'o' a
+//! blue blob, '-' a hole and numbers count consecutive empty cells. Then come the player to
+//! move ('x' or 'o') and optionally the halfmove clock and the fullmove number, which are
+//! checked but not used.
+//! See `Board::from_fen`, `Configuration::from_fen` and `Configuration::to_fen`.
+use crate::error::{BlobwarError, Location};
+use crate::positions::{Positions, MAX_CELLS};
+
+/// Does given string look like a FEN rather than a serialized configuration ?
+/// FENs have several ranks or their player to move as second word.
+pub fn is_fen(string: &str) -> bool {
+    string.contains('/') || matches!(string.split_whitespace().nth(1), Some("x") | Some("o"))
+}
+
+/// Character of given cell contents.
+pub(crate) fn cell_code(hole: bool, red: bool, blue: bool) -> char {
+    match (hole, red, blue) {
+        (true, _, _) => '-',
+        (_, true, _) => 'x',
+        (_, _, true) => 'o',
+        _ => ' ',
+    }
+}
+
+/// Character of given player.
+pub(crate) fn player_code(player: bool) -> char {
+    if player {
+        'o'
+    } else {
+        'x'
+    }
+}
+
+/// Configuration described by a FEN.
+pub(crate) struct Fen {
+    pub(crate) width: u8,
+    pub(crate) height: u8,
+    pub(crate) holes: Positions,
+    pub(crate) blobs: [Positions; 2],
+    pub(crate) current_player: bool,
+    /// Column of each cell, for error messages.
+    pub(crate) columns: Vec<usize>,
+}
+
+impl Fen {
+    /// Parse given FEN.
+    pub(crate) fn parse(fen: &str) -> Result<Self, BlobwarError> {
+        let location = |column| Location { line: 1, column };
+        let ranks = fen
+            .split_whitespace()
+            .next()
+            .ok_or(BlobwarError::EmptyBoard)?;
+        // columns start after leading blanks
+        let start = fen.len() - fen.trim_start().len();
+        let rest = &fen[start + ranks.len()..];
+        let mut fields = rest.split_whitespace();
+
+        let mut codes = [0u128; 3];
+        let mut columns = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+        let mut rank_width = 0;
+        let mut empty = 0;
+        let mut end_rank = |rank_width: &mut usize, height: &mut usize| {
+            if *width.get_or_insert(*rank_width) != *rank_width {
+                return Err(BlobwarError::UnevenRows { line: *height + 1 });
+            }
+            *height += 1;
+            *rank_width = 0;
+            Ok(())
+        };
+        for (index, character) in ranks.chars().enumerate() {
+            let column = start + index + 1;
+            if let Some(digit) = character.to_digit(10) {
+                empty = empty * 10 + digit as usize;
+                if columns.len() + empty > MAX_CELLS {
+                    return Err(BlobwarError::BoardTooLarge {
+                        cells: columns.len() + empty,
+                    });
+                }
+                continue;
+            }
+            columns.extend(std::iter::repeat_n(column - 1, empty));
+            rank_width += empty;
+            empty = 0;
+            let code = match character {
+                '/' => {
+                    end_rank(&mut rank_width, &mut height)?;
+                    continue;
+                }
+                '-' => 0,
+                'x' => 1,
+                'o' => 2,
+                _ => {
+                    return Err(BlobwarError::InvalidCell {
+                        character,
+                        location: location(column),
+                    })
+                }
+            };
+            if columns.len() >= MAX_CELLS {
+                return Err(BlobwarError::BoardTooLarge {
+                    cells: columns.len() + 1,
+                });
+            }
+            codes[code] |= 1 << columns.len();
+            columns.push(column);
+            rank_width += 1;
+        }
+        columns.extend(std::iter::repeat_n(start + ranks.len(), empty));
+        rank_width += empty;
+        end_rank(&mut rank_width, &mut height)?;
+        let width = width.unwrap_or_default();
+        if width == 0 {
+            return Err(BlobwarError::EmptyBoard);
+        }
+
+        let player_column = fen.len() - rest.trim_start().len() + 1;
+        let current_player = match fields.next() {
+            Some("x") => false,
+            Some("o") => true,
+            player => {
+                return Err(BlobwarError::InvalidPlayer {
+                    character: player.and_then(|player| player.chars().next()),
+                    location: location(player_column),
+                })
+            }
+        };
+        let counters: Vec<&str> = fields.collect();
+        if counters.len() > 2
+            || counters
+                .iter()
+                .any(|counter| counter.parse::<u32>().is_err())
+        {
+            return Err(BlobwarError::InvalidCounters(counters.join(" ")));
+        }
+        Ok(Fen {
+            width: width as u8,
+            height: height as u8,
+            holes: Positions(codes[0]),
+            blobs: [Positions(codes[1]), Positions(codes[2])],
+            current_player,
+            columns,
+        })
+    }
+}
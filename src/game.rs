@@ -0,0 +1,261 @@
+//! Headless matches between two strategies.
+//! `play_match` plays a whole game and returns a `MatchOutcome` ; an optional observer gets
+//! notified of every turn (see `verbose` for the console output of `Configuration::battle`).
+//! `play_timed_match` also runs the clocks of both players : a player overrunning its time
+//! loses the game. Players returning an illegal movement lose it too, as do players passing
+//! while they could move.
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::time::{Duration, Instant};
+
+use crate::clock::TimeControl;
+use crate::configuration::{Configuration, Movement, Notation};
+use crate::record::{GameRecord, RecordedMove};
+use crate::strategy::Strategy;
+
+/// Everything observers get told about.
+pub enum MatchEvent<'e, 'a> {
+    /// Current player of given configuration is about to think.
+    ToPlay(&'e Configuration<'a>),
+    /// Current player of given configuration chose a movement (None for a pass).
+    Played {
+        /// Configuration before the movement.
+        state: &'e Configuration<'a>,
+        /// Chosen movement.
+        movement: Option<Movement>,
+        /// Time taken to choose it.
+        think_time: Duration,
+    },
+    /// Game is over.
+    Over {
+        /// Final configuration.
+        state: &'e Configuration<'a>,
+        /// Result of the match.
+        outcome: &'e MatchOutcome,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Result of a match.
+pub struct MatchOutcome {
+    /// Winner : false for red, true for blue, None for a draw. Running out of time or playing
+    /// an illegal movement loses whatever the number of blobs.
+    pub winner: Option<bool>,
+    /// Final number of red blobs.
+    pub red: u8,
+    /// Final number of blue blobs.
+    pub blue: u8,
+    /// Number of turns played (passes included).
+    pub plies: usize,
+    /// Number of passes.
+    pub passes: usize,
+    /// Total thinking time of red and blue.
+    pub time: [Duration; 2],
+    /// All turns, in order.
+    pub moves: Vec<RecordedMove>,
+    /// Time control of the match.
+    pub time_control: TimeControl,
+    /// Player who ran out of time (false for red, true for blue), if any.
+    pub lost_on_time: Option<bool>,
+    /// Player who returned an illegal movement or passed while it could move (false for red,
+    /// true for blue), if any.
+    pub illegal_move: Option<bool>,
+}
+
+impl MatchOutcome {
+    /// Final red margin (positive if red won).
+    pub fn red_margin(&self) -> i16 {
+        self.red as i16 - self.blue as i16
+    }
+
+    /// Record of the game started from given configuration, played between given players.
+    pub fn record<R: fmt::Display, B: fmt::Display>(
+        &self,
+        start: &Configuration,
+        red: &R,
+        blue: &B,
+    ) -> GameRecord {
+        let mut record = GameRecord::new(start.board(), red, blue).starting_configuration(start);
+        record.moves = self.moves.clone();
+        record.result = Some(self.red_margin());
+        record.time_control = self.time_control;
+        record.lost_on_time = self.lost_on_time;
+        record.illegal_move = self.illegal_move;
+        record
+    }
+}
+
+/// Play a match between red and blue players from given configuration until the game is over
+/// or both players pass in a row.
+/// The configuration ends up in its final state.
+/// A player returning an illegal movement, or passing while it has legal ones, forfeits (the
+/// movement is not played).
+pub fn play_match<T, U>(
+    state: &mut Configuration,
+    red: &mut T,
+    blue: &mut U,
+    observer: Option<&mut dyn FnMut(&MatchEvent)>,
+) -> MatchOutcome
+where
+    T: Strategy + ?Sized,
+    U: Strategy + ?Sized,
+{
+    play_timed_match(state, red, blue, TimeControl::Unlimited, observer)
+}
+
+/// Play a match as `play_match` does, under given time control : players get their clock
+/// before each move and lose as soon as they overrun their time (their last move is then not
+/// played).
+pub fn play_timed_match<T, U>(
+    state: &mut Configuration,
+    red: &mut T,
+    blue: &mut U,
+    time_control: TimeControl,
+    mut observer: Option<&mut dyn FnMut(&MatchEvent)>,
+) -> MatchOutcome
+where
+    T: Strategy + ?Sized,
+    U: Strategy + ?Sized,
+{
+    let mut notify = |event: MatchEvent| {
+        if let Some(observer) = observer.as_mut() {
+            observer(&event)
+        }
+    };
+    let mut time = [Duration::default(); 2];
+    let mut clocks = [time_control.clock(); 2];
+    let mut lost_on_time = None;
+    let mut illegal_move = None;
+    let mut moves = Vec::new();
+    while !state.game_over() {
+        notify(MatchEvent::ToPlay(state));
+        let player = state.current_player as usize;
+        let start = Instant::now();
+        let movement = if state.current_player {
+            blue.set_clock(clocks[player]);
+            blue.compute_next_move(state)
+        } else {
+            red.set_clock(clocks[player]);
+            red.compute_next_move(state)
+        };
+        let think_time = start.elapsed();
+        // searches value the configuration for its player, which the movement is worth
+        let stats = if state.current_player {
+            blue.search_stats()
+        } else {
+            red.search_stats()
+        };
+        let score = stats.and_then(|stats| stats.value).map(|value| -value);
+        time[player] += think_time;
+        if !time_control.charge(&mut clocks[player], think_time) {
+            lost_on_time = Some(state.current_player);
+            break;
+        }
+        let legal = match movement {
+            Some(movement) => state.check_move(&movement),
+            // passing twice in a row ends the game : only players who cannot move may pass
+            None => !state
+                .movements()
+                .any(|movement| state.check_move(&movement)),
+        };
+        if !legal {
+            illegal_move = Some(state.current_player);
+            break;
+        }
+        notify(MatchEvent::Played {
+            state,
+            movement,
+            think_time,
+        });
+        moves.push(RecordedMove {
+            movement,
+            think_time,
+            score,
+        });
+        if let Some(ref movement) = movement {
+            state.apply_movement(movement);
+        } else {
+            state.current_player = !state.current_player;
+            // no one can move anymore
+            if moves.len() > 1 && moves[moves.len() - 2].movement.is_none() {
+                break;
+            }
+        }
+    }
+    let (red, blue) = (state.blobs(false).len(), state.blobs(true).len());
+    let outcome = MatchOutcome {
+        winner: match (lost_on_time.or(illegal_move), red.cmp(&blue)) {
+            (Some(loser), _) => Some(!loser),
+            (None, std::cmp::Ordering::Greater) => Some(false),
+            (None, std::cmp::Ordering::Less) => Some(true),
+            (None, std::cmp::Ordering::Equal) => None,
+        },
+        red,
+        blue,
+        plies: moves.len(),
+        passes: moves.iter().filter(|m| m.movement.is_none()).count(),
+        time,
+        moves,
+        time_control,
+        lost_on_time,
+        illegal_move,
+    };
+    notify(MatchEvent::Over {
+        state,
+        outcome: &outcome,
+    });
+    outcome
+}
+
+/// Print given configuration, in colours if asked to.
+fn print_configuration(state: &Configuration, colours: bool) {
+    if colours {
+        println!("{:#}", state);
+    } else {
+        println!("{}", state);
+    }
+}
+
+/// Observer printing the game on the console, given the names of red and blue players.
+/// Blobs are coloured when the console is a terminal.
+pub fn verbose(names: [String; 2]) -> impl FnMut(&MatchEvent) {
+    let colours = io::stdout().is_terminal();
+    move |event| match event {
+        MatchEvent::ToPlay(state) => {
+            println!(
+                "{} player's turn (he is losing by {} before playing)",
+                ["red", "blue"][state.current_player as usize],
+                state.value()
+            );
+            print_configuration(state, colours);
+        }
+        MatchEvent::Played {
+            state,
+            movement,
+            think_time,
+        } => println!(
+            "{} plays {} ({:.3}s)",
+            ["red", "blue"][state.current_player as usize],
+            Notation::new(*movement, state.board()),
+            think_time.as_secs_f64()
+        ),
+        MatchEvent::Over { state, outcome } => {
+            if let Some(loser) = outcome.lost_on_time {
+                println!("{} ran out of time", ["RED", "BLUE"][loser as usize]);
+            }
+            if let Some(loser) = outcome.illegal_move {
+                println!(
+                    "{} played an illegal movement",
+                    ["RED", "BLUE"][loser as usize]
+                );
+            }
+            match outcome.winner {
+                Some(false) => println!("RED ({}) wins over BLUE ({})!", names[0], names[1]),
+                Some(true) => println!("BLUE ({}) wins over RED ({})!", names[1], names[0]),
+                None => println!("DRAW!"),
+            }
+            print_configuration(state, colours);
+            println!("GAME OVER (red value of {})", outcome.red_margin());
+        }
+    }
+}
@@ -3,7 +3,17 @@
 #![warn(clippy::all)]
 
 pub mod board;
+pub mod book;
+pub mod clock;
 pub mod configuration;
+pub mod error;
+pub mod fen;
+pub mod game;
+pub mod pgn;
 pub(crate) mod positions;
+pub mod record;
 pub(crate) mod shmem;
 pub mod strategy;
+pub mod symmetry;
+pub mod tournament;
+pub(crate) mod zobrist;
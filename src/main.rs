@@ -1,11 +1,56 @@
 extern crate blobwar;
-//use blobwar::board::Board;
-use blobwar::configuration::Configuration;
-use blobwar::strategy::{Greedy, Human, MinMax, AlphaBeta};
+use blobwar::board::Board;
+use blobwar::clock::TimeControl;
+use blobwar::pgn::PgnGame;
+use blobwar::strategy::from_spec;
+
+use std::env::args;
+use std::process::exit;
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!(
+        "usage: blobwar [--board name] [--red spec] [--blue spec] [--time control] [--record file] [--pgn file]"
+    );
+    exit(1)
+}
 
 fn main() {
-    //let board = Board::load("x").expect("failed loading board");
-    let board = Default::default();
-    let mut game = Configuration::new(&board);
-    game.battle(AlphaBeta(5), MinMax(4));
+    let mut board_name = None;
+    let mut specs = ["alphabeta:5".to_owned(), "minmax:4".to_owned()];
+    let mut record_file = None;
+    let mut pgn_file = None;
+    let mut time_control = TimeControl::Unlimited;
+    let mut arguments = args().skip(1);
+    while let Some(argument) = arguments.next() {
+        let value = arguments
+            .next()
+            .unwrap_or_else(|| fail(&format!("missing value after {}", argument)));
+        match argument.as_str() {
+            "--board" => board_name = Some(value),
+            "--red" => specs[0] = value,
+            "--blue" => specs[1] = value,
+            "--record" => record_file = Some(value),
+            "--pgn" => pgn_file = Some(value),
+            "--time" => time_control = value.parse().unwrap_or_else(|e| fail(&format!("{}", e))),
+            _ => fail(&format!("unknown argument {}", argument)),
+        }
+    }
+    let board = match &board_name {
+        Some(name) => Board::load(name).unwrap_or_else(|e| fail(&e.to_string())),
+        None => Default::default(),
+    };
+    let [red, blue] = specs.map(|spec| from_spec(&spec).unwrap_or_else(|e| fail(&e.to_string())));
+    let mut game = board.initial_configuration();
+    let record = game
+        .timed_battle(red, blue, time_control)
+        .board_name(board_name.as_deref().unwrap_or_default());
+    if let Some(path) = pgn_file {
+        PgnGame::from_record(&record)
+            .and_then(|game| game.append(path))
+            .expect("failed saving game");
+    }
+    if let Some(path) = record_file {
+        record.save(path).expect("failed saving game");
+    }
 }
@@ -0,0 +1,482 @@
+//! PGN-like text format, to archive games and share them with other programs :
+//!
+//! ```text
+//! [Event "blobwar match"]
+//! [Red "AlphaBeta (depth 5)"]
+//! [Blue "Greedy"]
+//! [Board "standard"]
+//! [Date "2024.03.14"]
+//! [Result "1-0"]
+//! [TimeControl "-"]
+//! [FEN "x6o/8/8/8/8/8/8/o6x x"]
+//!
+//! 1. b7 {[%emt 0.012]} g8 2. a8c6 {[%eval -2] opening} g1 ... 1-0
+//! ```
+//!
+//! Tag pairs come first, then the moves in algebraic notation (see `Movement::notation`) with
+//! their numbers and optional annotations in braces : `[%eval N]` is the value of the move for
+//! its player found by its search (higher is better, in blobs with the default evaluation and
+//! near `MATE` for won games), `[%emt S]` the time spent on it in seconds and anything else a
+//! free comment. Text after ';' on a line is ignored. The result ends the game :
+//! "1-0" when red wins, "0-1" when blue wins, "1/2-1/2" for draws and "*" for unfinished games.
+//! The FEN tag holds the initial configuration ; without it the board is loaded from the
+//! "boards" directory and red moves first. The TimeControl tag follows `TimeControl` notation,
+//! games lost on time have a "time forfeit" Termination tag and games lost by an illegal
+//! movement a "rules infraction" one.
+//! Files may hold several games.
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::board::Board;
+use crate::configuration::{Configuration, Notation};
+use crate::error::BlobwarError;
+use crate::record::GameRecord;
+use crate::strategy::Score;
+
+/// Tags written first, in this order (missing ones get a default value).
+pub const REQUIRED_TAGS: [&str; 7] = [
+    "Event",
+    "Red",
+    "Blue",
+    "Board",
+    "Date",
+    "Result",
+    "TimeControl",
+];
+
+/// Movetext lines are wrapped at this width.
+const LINE_WIDTH: usize = 80;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// One turn of a game and its annotations.
+pub struct AnnotatedMove {
+    /// Movement in algebraic notation ("0000" for a pass).
+    pub movement: String,
+    /// Value of the movement for the player who played it.
+    pub score: Option<Score>,
+    /// Time the player took to decide.
+    pub think_time: Option<Duration>,
+    /// Free comment.
+    pub comment: Option<String>,
+}
+
+impl AnnotatedMove {
+    /// Movement without annotations.
+    pub fn new(movement: &str) -> Self {
+        AnnotatedMove {
+            movement: movement.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// Annotations in braces, if any.
+    fn annotation(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(score) = self.score {
+            parts.push(format!("[%eval {}]", score));
+        }
+        if let Some(think_time) = self.think_time {
+            parts.push(format!("[%emt {:.3}]", think_time.as_secs_f64()));
+        }
+        if let Some(comment) = &self.comment {
+            // braces cannot be nested
+            parts.push(comment.replace(['{', '}'], ""));
+        }
+        if parts.is_empty() {
+            None
+        } else {
+            Some(format!("{{{}}}", parts.join(" ")))
+        }
+    }
+
+    /// Fill annotations from the content of a comment (braces excluded).
+    fn annotate(&mut self, content: &str) -> Result<(), String> {
+        let mut comment = content;
+        let mut text = String::new();
+        while let Some(start) = comment.find("[%") {
+            text.push_str(&comment[..start]);
+            let end = comment[start..]
+                .find(']')
+                .ok_or("unterminated annotation")?
+                + start;
+            let mut words = comment[start + 2..end].split_whitespace();
+            let invalid = || format!("invalid annotation {}", &comment[start..=end]);
+            match (words.next(), words.next(), words.next()) {
+                (Some("eval"), Some(score), None) => {
+                    self.score = Some(score.parse().map_err(|_| invalid())?)
+                }
+                (Some("emt"), Some(seconds), None) => {
+                    let seconds: f64 = seconds.parse().map_err(|_| invalid())?;
+                    // also rejects negative, infinite and too long times
+                    let think_time = Duration::try_from_secs_f64(seconds).map_err(|_| invalid())?;
+                    self.think_time = Some(think_time)
+                }
+                _ => return Err(invalid()),
+            }
+            comment = &comment[end + 1..];
+        }
+        text.push_str(comment);
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !text.is_empty() {
+            self.comment = Some(match self.comment.take() {
+                Some(previous) => previous + " " + &text,
+                None => text,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Game in the PGN-like format.
+pub struct PgnGame {
+    /// Tag pairs, in order.
+    pub tags: Vec<(String, String)>,
+    /// All turns, in order.
+    pub moves: Vec<AnnotatedMove>,
+}
+
+/// Termination tag of games lost on time.
+const TIME_FORFEIT: &str = "time forfeit";
+
+/// Termination tag of games lost by an illegal movement.
+const RULES_INFRACTION: &str = "rules infraction";
+
+/// Result tag of given record.
+fn result_tag(record: &GameRecord) -> &'static str {
+    match record.forfeit() {
+        Some(false) => return "0-1",
+        Some(true) => return "1-0",
+        None => {}
+    }
+    match record.result {
+        Some(margin) if margin > 0 => "1-0",
+        Some(margin) if margin < 0 => "0-1",
+        Some(_) => "1/2-1/2",
+        None => "*",
+    }
+}
+
+/// Today's date, as in "2024.03.14".
+fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() / 86_400)
+        .unwrap_or_default() as i64;
+    // civil calendar from days since 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
+
+/// Quote given tag value.
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Value of a quoted tag value.
+fn unquote(quoted: &str) -> Option<String> {
+    let mut characters = quoted.strip_prefix('"')?.strip_suffix('"')?.chars();
+    let mut value = String::new();
+    while let Some(character) = characters.next() {
+        match character {
+            '\\' => value.push(characters.next()?),
+            '"' => return None,
+            _ => value.push(character),
+        }
+    }
+    Some(value)
+}
+
+impl PgnGame {
+    /// Game of given record, with all required tags (the event is "blobwar match", the date
+    /// today's). Moves are annotated with their thinking times and the values their searches
+    /// found, if any.
+    pub fn from_record(record: &GameRecord) -> Result<Self, BlobwarError> {
+        let board = record.rebuild_board()?;
+        let configurations = record.configurations(&board)?;
+        let board_name = if record.board.is_empty() {
+            "?"
+        } else {
+            &record.board
+        };
+        let game = PgnGame::default()
+            .tag("Event", "blobwar match")
+            .tag("Red", &record.players[0])
+            .tag("Blue", &record.players[1])
+            .tag("Board", board_name)
+            .tag("Date", &today())
+            .tag("Result", result_tag(record))
+            .tag("TimeControl", &record.time_control.to_string())
+            .tag("FEN", &configurations[0].to_fen());
+        let game = match (record.lost_on_time, record.illegal_move) {
+            (Some(_), _) => game.tag("Termination", TIME_FORFEIT),
+            (None, Some(_)) => game.tag("Termination", RULES_INFRACTION),
+            (None, None) => game,
+        };
+        let moves = record
+            .moves
+            .iter()
+            .map(|recorded| AnnotatedMove {
+                score: recorded.score,
+                think_time: Some(recorded.think_time),
+                ..AnnotatedMove::new(&Notation::new(recorded.movement, &board).to_string())
+            })
+            .collect();
+        Ok(PgnGame { moves, ..game })
+    }
+
+    /// Set given tag, replacing any previous value.
+    pub fn tag(mut self, name: &str, value: &str) -> Self {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, previous)) => *previous = value.to_owned(),
+            None => self.tags.push((name.to_owned(), value.to_owned())),
+        }
+        self
+    }
+
+    /// Value of given tag.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Board and initial blobs of the game, from the FEN tag or the board name.
+    pub fn board(&self) -> Result<Board, BlobwarError> {
+        match (self.get("FEN"), self.get("Board")) {
+            (Some(fen), _) => {
+                let mut board = Board::from_fen(fen)?;
+                let start = Configuration::from_fen(fen, &board)?;
+                board.starting_blobs = [start.blobs(false), start.blobs(true)];
+                Ok(board)
+            }
+            (None, Some(name)) if name != "?" => Board::load(name),
+            _ => Err(BlobwarError::InvalidGame(
+                "unknown board : no FEN nor Board tag".to_owned(),
+            )),
+        }
+    }
+
+    /// Record of the game, checking all moves can be played. Comments are lost.
+    pub fn to_record(&self) -> Result<GameRecord, BlobwarError> {
+        let board = self.board()?;
+        let red = self.get("Red").unwrap_or("?");
+        let blue = self.get("Blue").unwrap_or("?");
+        let mut state = board.initial_configuration();
+        if let Some(fen) = self.get("FEN") {
+            state.current_player = Configuration::from_fen(fen, &board)?.current_player;
+        }
+        let mut record = GameRecord::new(&board, &red, &blue)
+            .board_name(self.get("Board").filter(|&name| name != "?").unwrap_or(""))
+            .starting_configuration(&state);
+        for (ply, annotated) in self.moves.iter().enumerate() {
+            let movement = state
+                .parse_move(&annotated.movement)
+                .map_err(|e| BlobwarError::InvalidGame(format!("move {}: {}", ply / 2 + 1, e)))?;
+            match movement {
+                Some(movement) => state.apply_movement(&movement),
+                None => state = state.skip_play(),
+            }
+            record.push(movement, annotated.think_time.unwrap_or_default());
+            record.moves[ply].score = annotated.score;
+        }
+        if let Some(time_control) = self.get("TimeControl") {
+            record.time_control = time_control.parse()?;
+        }
+        if self.get("Result").is_some_and(|result| result != "*") {
+            record.finish(&state);
+        }
+        let termination = self.get("Termination");
+        if termination == Some(TIME_FORFEIT) || termination == Some(RULES_INFRACTION) {
+            let loser = match self.get("Result") {
+                Some("1-0") => Some(true),
+                Some("0-1") => Some(false),
+                _ => {
+                    return Err(BlobwarError::InvalidGame(
+                        "forfeits need a winner".to_owned(),
+                    ))
+                }
+            };
+            if termination == Some(TIME_FORFEIT) {
+                record.lost_on_time = loser;
+            } else {
+                record.illegal_move = loser;
+            }
+        }
+        Ok(record)
+    }
+
+    /// Parse all games of given text.
+    pub fn parse_all(text: &str) -> Result<Vec<Self>, BlobwarError> {
+        let mut games = Vec::new();
+        let mut game = PgnGame::default();
+        // a game is over once its result is read (or when tags follow its moves)
+        let mut in_moves = false;
+        let mut comment: Option<String> = None;
+        for (index, line) in text.lines().enumerate() {
+            let invalid = |reason: String| {
+                BlobwarError::InvalidGame(format!("line {}: {}", index + 1, reason))
+            };
+            let trimmed = line.trim();
+            if comment.is_none() && trimmed.starts_with('[') {
+                if in_moves {
+                    games.push(std::mem::take(&mut game));
+                    in_moves = false;
+                }
+                let (name, value) = trimmed
+                    .strip_prefix('[')
+                    .and_then(|tag| tag.strip_suffix(']'))
+                    .and_then(|tag| tag.split_once(char::is_whitespace))
+                    .and_then(|(name, value)| Some((name, unquote(value.trim())?)))
+                    .ok_or_else(|| invalid("tag pairs look like [Name \"value\"]".to_owned()))?;
+                game = game.tag(name, &value);
+                continue;
+            }
+            let mut rest = line;
+            loop {
+                if let Some(content) = comment.as_mut() {
+                    match rest.find('}') {
+                        Some(end) => {
+                            content.push_str(&rest[..end]);
+                            rest = &rest[end + 1..];
+                            let content = comment.take().unwrap_or_default();
+                            game.moves
+                                .last_mut()
+                                .ok_or_else(|| invalid("comment before the first move".to_owned()))?
+                                .annotate(&content)
+                                .map_err(invalid)?;
+                        }
+                        None => {
+                            content.push_str(rest);
+                            content.push(' ');
+                            break;
+                        }
+                    }
+                }
+                rest = rest.trim_start();
+                if rest.is_empty() || rest.starts_with(';') {
+                    break;
+                }
+                if let Some(after) = rest.strip_prefix('{') {
+                    comment = Some(String::new());
+                    rest = after;
+                    continue;
+                }
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || c == '{' || c == ';')
+                    .unwrap_or(rest.len());
+                let token = &rest[..end];
+                rest = &rest[end..];
+                // move numbers, as in "12." or "12...", might be glued to the move
+                let digits = token
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(token.len());
+                let token = if token[digits..].starts_with('.') {
+                    token[digits..].trim_start_matches('.')
+                } else {
+                    token
+                };
+                match token {
+                    "" => {}
+                    "1-0" | "0-1" | "1/2-1/2" | "*" => {
+                        game = game.tag("Result", token);
+                        games.push(std::mem::take(&mut game));
+                        in_moves = false;
+                    }
+                    _ => {
+                        in_moves = true;
+                        game.moves.push(AnnotatedMove::new(token));
+                    }
+                }
+            }
+        }
+        if comment.is_some() {
+            return Err(BlobwarError::InvalidGame("unterminated comment".to_owned()));
+        }
+        if in_moves || !game.tags.is_empty() {
+            games.push(game);
+        }
+        Ok(games)
+    }
+
+    /// Load all games of given file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<Self>, BlobwarError> {
+        PgnGame::parse_all(&fs::read_to_string(path)?)
+    }
+
+    /// Append the game to given file, creating it if needed.
+    pub fn append<P: AsRef<Path>>(&self, path: P) -> Result<(), BlobwarError> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", self)?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for PgnGame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for name in REQUIRED_TAGS.iter() {
+            let default = match *name {
+                "Date" => "????.??.??",
+                "Result" => "*",
+                "TimeControl" => "-",
+                _ => "?",
+            };
+            writeln!(f, "[{} {}]", name, quote(self.get(name).unwrap_or(default)))?;
+        }
+        for (name, value) in &self.tags {
+            if !REQUIRED_TAGS.contains(&name.as_str()) {
+                writeln!(f, "[{} {}]", name, quote(value))?;
+            }
+        }
+        writeln!(f)?;
+        let mut line = String::new();
+        let mut push = |f: &mut fmt::Formatter, word: &str| {
+            if !line.is_empty() && line.len() + 1 + word.len() > LINE_WIDTH {
+                writeln!(f, "{}", line)?;
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+            Ok(())
+        };
+        // games started by blue begin with "1..."
+        let blue_first = self
+            .get("FEN")
+            .is_some_and(|fen| fen.split_whitespace().nth(1) == Some("o"));
+        if blue_first && !self.moves.is_empty() {
+            push(f, "1...")?;
+        }
+        for (ply, annotated) in self.moves.iter().enumerate() {
+            let ply = ply + blue_first as usize;
+            if ply.is_multiple_of(2) {
+                push(f, &format!("{}. {}", ply / 2 + 1, annotated.movement))?;
+            } else {
+                push(f, &annotated.movement)?;
+            }
+            if let Some(annotation) = annotated.annotation() {
+                push(f, &annotation)?;
+            }
+        }
+        push(f, self.get("Result").unwrap_or("*"))?;
+        writeln!(f, "{}", line)
+    }
+}
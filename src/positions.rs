@@ -1,6 +1,6 @@
-//! a `Positions` is a set of 64 bits locating something on the board.
+//! a `Positions` is a set of 128 bits locating something on the board.
 //! we use it to keep track of blue blobs, red blobs and holes.
-use std;
+//! Boards of any dimensions fit as long as they have at most `MAX_CELLS` cells.
 use std::fmt;
 use std::iter::repeat;
 use std::ops::Deref;
@@ -8,41 +8,24 @@ use std::ops::Deref;
 use rayon::iter::plumbing::{bridge, Consumer, Producer, ProducerCallback, UnindexedConsumer};
 use rayon::prelude::{IndexedParallelIterator, ParallelIterator};
 
-/// Coordinate of a board cell (between 0 and 64).
-pub type Position = u8;
+use crate::symmetry::Transform;
 
-pub trait BoardPosition {
-    /// Convert 2D coordinates to board coordinates.
-    fn from_2d(x: u8, y: u8) -> Self;
-    /// Convert board coordinates to 2d coordinates.
-    fn to_2d(self) -> (u8, u8);
-    /// Compute distance between two board coordinates.
-    fn distance_to(self, other: Self) -> u8;
-}
+/// Coordinate of a board cell (between 0 and `MAX_CELLS`), row by row.
+/// Converting to 2D coordinates requires the board width, see `Board`.
+pub type Position = u8;
 
-impl BoardPosition for Position {
-    fn from_2d(x: u8, y: u8) -> Self {
-        y * 8 + x
-    }
-    fn to_2d(self) -> (u8, u8) {
-        (self % 8, self / 8)
-    }
-    fn distance_to(self, other: Self) -> u8 {
-        let (x1, y1) = self.to_2d();
-        let (x2, y2) = other.to_2d();
-        std::cmp::max((x2 as i8 - x1 as i8).abs(), (y2 as i8 - y1 as i8).abs()) as u8
-    }
-}
+/// Maximal number of cells on a board.
+pub const MAX_CELLS: usize = 128;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
 /// Set of `Position` as a bitfield (position 0 is bit of lowest weight)
 /// This allows to store any combination of board cells in a very compact manner.
 /// Moreover we can then use bit masking operations to compute intersections and unions...
-pub struct Positions(pub u64);
+pub struct Positions(pub u128);
 
 impl Deref for Positions {
-    type Target = u64;
-    fn deref(&self) -> &u64 {
+    type Target = u128;
+    fn deref(&self) -> &u128 {
         &self.0
     }
 }
@@ -62,13 +45,13 @@ impl Positions {
         ParallelBitIterator {
             board: self.0,
             start: 0,
-            end: 64,
+            end: MAX_CELLS as u8,
         }
     }
 
-    /// Iterate on all our 64 bits.
-    pub fn full_bits(&self) -> impl Iterator<Item = bool> {
-        self.bits().chain(repeat(false)).take(64)
+    /// Iterate on our first `size` bits.
+    pub fn full_bits(&self, size: usize) -> impl Iterator<Item = bool> {
+        self.bits().chain(repeat(false)).take(size)
     }
     /// Do we have something on given `Position` ?
     pub fn contains(&self, position: Position) -> bool {
@@ -92,11 +75,11 @@ impl Positions {
     }
     /// Do we contain nothing ?
     pub fn is_empty(&self) -> bool {
-        self.eq(&0)
+        self.0 == 0
     }
     /// How many bits are set ?
-    pub fn len(&self) -> i8 {
-        self.count_ones() as i8
+    pub fn len(&self) -> u8 {
+        self.count_ones() as u8
     }
     /// Return positions obtained when intersecting with given ones.
     pub fn intersection_with(&self, other: Positions) -> Positions {
@@ -116,31 +99,88 @@ impl Positions {
     }
     /// `Positions` initialized with only given `Position` inside.
     pub fn single(position: Position) -> Self {
-        Positions(1u64 << position)
+        Positions(1u128 << position)
     }
-    /// Do we contain every possible `Position` ?
-    pub fn is_all(&self) -> bool {
-        self.0 == std::u64::MAX
+    /// `Positions` containing all positions below `size`.
+    pub fn first(size: usize) -> Self {
+        if size >= MAX_CELLS {
+            Positions(u128::MAX)
+        } else {
+            Positions((1u128 << size) - 1)
+        }
     }
-}
-
-impl Default for Positions {
-    fn default() -> Self {
-        Positions(0)
+    /// Mirror top and bottom rows of a `width` x `height` board : move each row at once.
+    pub fn flip_vertical(&self, width: u8, height: u8) -> Self {
+        let (width, height) = (width as u32, height as u32);
+        let row = Positions::first(width as usize).0;
+        Positions((0..height).fold(0, |flipped, y| {
+            flipped | ((self.0 >> (y * width)) & row) << ((height - 1 - y) * width)
+        }))
+    }
+    /// Mirror left and right columns of a `width` x `height` board : move each column at once.
+    pub fn flip_horizontal(&self, width: u8, height: u8) -> Self {
+        let (width, height) = (width as u32, height as u32);
+        let column = (0..height).fold(0u128, |column, y| column | 1 << (y * width));
+        Positions((0..width).fold(0, |flipped, x| {
+            flipped | ((self.0 >> x) & column) << (width - 1 - x)
+        }))
+    }
+    /// Mirror a `size` x `size` board along its main diagonal : cells of each diagonal parallel
+    /// to it move by the same number of bits.
+    pub fn transpose(&self, size: u8) -> Self {
+        let size = size as i32;
+        Positions((1 - size..size).fold(0, |transposed, offset| {
+            // cells (x, y) with x - y == offset move to (y, x)
+            let diagonal = (0..size)
+                .filter(|y| (0..size).contains(&(y + offset)))
+                .fold(0u128, |diagonal, y| diagonal | 1 << (y * size + y + offset));
+            let shift = offset * (size - 1);
+            let cells = self.0 & diagonal;
+            transposed
+                | if shift >= 0 {
+                    cells << shift
+                } else {
+                    cells >> -shift
+                }
+        }))
+    }
+    /// Image by given transform on a `width` x `height` board.
+    /// Panics if the transform swaps axes of a board which is not square.
+    pub fn transform(&self, transform: Transform, width: u8, height: u8) -> Self {
+        assert!(
+            !transform.swaps_axes() || width == height,
+            "{} needs a square board",
+            transform
+        );
+        match transform {
+            Transform::Identity => *self,
+            Transform::Rotate90 => self.transpose(width).flip_horizontal(width, height),
+            Transform::Rotate180 => self
+                .flip_vertical(width, height)
+                .flip_horizontal(width, height),
+            Transform::Rotate270 => self.transpose(width).flip_vertical(width, height),
+            Transform::FlipHorizontal => self.flip_horizontal(width, height),
+            Transform::FlipVertical => self.flip_vertical(width, height),
+            Transform::FlipDiagonal => self.transpose(width),
+            Transform::FlipAntiDiagonal => self
+                .transpose(width)
+                .flip_vertical(width, height)
+                .flip_horizontal(width, height),
+        }
     }
 }
 
 pub struct BitIterator {
-    remaining: u64,
+    remaining: u128,
     last_index: u8,
     size: Option<u8>,
 }
 
 impl BitIterator {
-    fn new(remaining: u64) -> BitIterator {
+    fn new(remaining: u128) -> BitIterator {
         BitIterator {
-            remaining: remaining,
-            last_index: 64,
+            remaining,
+            last_index: MAX_CELLS as u8,
             size: None,
         }
     }
@@ -171,7 +211,7 @@ impl Iterator for BitIterator {
             (l as usize, Some(l as usize))
         } else {
             let mut l = 0;
-            for i in 0..64 {
+            for i in 0..MAX_CELLS {
                 if (self.remaining >> i) & 1 != 0 {
                     l += 1
                 }
@@ -213,7 +253,7 @@ impl fmt::Display for Positions {
 //Made mostly with the help of https://geo-ant.github.io/blog/2022/implementing-parallel-iterators-rayon/
 
 pub struct ParallelBitIterator {
-    board: u64,
+    board: u128,
     start: u8,
     end: u8,
 }
@@ -253,7 +293,7 @@ impl IndexedParallelIterator for ParallelBitIterator {
 }
 
 struct BitProducer {
-    board: u64,
+    board: u128,
     start: u8,
     end: u8,
 }
@@ -0,0 +1,324 @@
+//! Game records : everything needed to save, reload and replay a game.
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::board::Board;
+use crate::clock::TimeControl;
+use crate::configuration::{Configuration, Movement, Notation};
+use crate::error::{BlobwarError, Location};
+use crate::positions::{Position, Positions, MAX_CELLS};
+use crate::strategy::Score;
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+/// One turn of a game.
+pub struct RecordedMove {
+    /// Movement played (None for a pass).
+    pub movement: Option<Movement>,
+    /// Time the player took to decide.
+    pub think_time: Duration,
+    /// Value of the movement for the player, higher is better (see `SearchStats::value`), if
+    /// it was searched.
+    #[serde(default)]
+    pub score: Option<Score>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(into = "SavedRecord", try_from = "SavedRecord")]
+/// Saved game, stored as JSON. Movements are stored in algebraic notation (see `Notation`).
+pub struct GameRecord {
+    /// Name of the board in the "boards" directory (might be empty).
+    pub board: String,
+    /// Number of columns.
+    pub width: u8,
+    /// Number of rows.
+    pub height: u8,
+    /// Positions of the holes.
+    pub holes: Vec<Position>,
+    /// Positions of red and blue blobs at the start of the game.
+    pub starting_blobs: [Vec<Position>; 2],
+    /// Player moving first (false for red, true for blue).
+    pub first_player: bool,
+    /// Names of red and blue players.
+    pub players: [String; 2],
+    /// All turns, in order.
+    pub moves: Vec<RecordedMove>,
+    /// Final red margin (positive if red won), None if the game is not over.
+    pub result: Option<i16>,
+    /// Time control of the game.
+    pub time_control: TimeControl,
+    /// Player who ran out of time and lost (false for red, true for blue), if any.
+    pub lost_on_time: Option<bool>,
+    /// Player who played an illegal movement and lost (false for red, true for blue), if any.
+    pub illegal_move: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+/// Movement as stored in files.
+enum SavedMovement {
+    /// Algebraic notation, "0000" for a pass.
+    Notation(String),
+    /// JSON form of older records (null for a pass).
+    Json(Option<Movement>),
+}
+
+#[derive(Serialize, Deserialize)]
+/// `RecordedMove` as stored in files.
+struct SavedMove {
+    movement: SavedMovement,
+    think_time: Duration,
+    #[serde(default)]
+    score: Option<Score>,
+}
+
+#[derive(Serialize, Deserialize)]
+/// `GameRecord` as stored in files : movements need the dimensions of the board to be named.
+struct SavedRecord {
+    board: String,
+    width: u8,
+    height: u8,
+    holes: Vec<Position>,
+    starting_blobs: [Vec<Position>; 2],
+    #[serde(default)]
+    first_player: bool,
+    players: [String; 2],
+    moves: Vec<SavedMove>,
+    result: Option<i16>,
+    #[serde(default)]
+    time_control: TimeControl,
+    #[serde(default)]
+    lost_on_time: Option<bool>,
+    #[serde(default)]
+    illegal_move: Option<bool>,
+}
+
+/// Board naming the movements of a record of given dimensions : holes do not matter.
+fn notation_board(width: u8, height: u8) -> Result<Board, BlobwarError> {
+    Board::new(width, height, Positions::default())
+}
+
+impl From<GameRecord> for SavedRecord {
+    fn from(record: GameRecord) -> Self {
+        // movements of records without a valid board cannot be named
+        let board = notation_board(record.width, record.height).ok();
+        let moves = record
+            .moves
+            .iter()
+            .map(|recorded| SavedMove {
+                movement: match &board {
+                    Some(board) => {
+                        SavedMovement::Notation(Notation::new(recorded.movement, board).to_string())
+                    }
+                    None => SavedMovement::Json(recorded.movement),
+                },
+                think_time: recorded.think_time,
+                score: recorded.score,
+            })
+            .collect();
+        SavedRecord {
+            board: record.board,
+            width: record.width,
+            height: record.height,
+            holes: record.holes,
+            starting_blobs: record.starting_blobs,
+            first_player: record.first_player,
+            players: record.players,
+            moves,
+            result: record.result,
+            time_control: record.time_control,
+            lost_on_time: record.lost_on_time,
+            illegal_move: record.illegal_move,
+        }
+    }
+}
+
+impl TryFrom<SavedRecord> for GameRecord {
+    type Error = BlobwarError;
+    /// Name movements on the record's board, built on the first movement in notation.
+    fn try_from(saved: SavedRecord) -> Result<Self, Self::Error> {
+        let mut board = None;
+        let mut moves = Vec::with_capacity(saved.moves.len());
+        for saved_move in saved.moves {
+            let movement = match saved_move.movement {
+                SavedMovement::Json(movement) => movement,
+                SavedMovement::Notation(notation) if notation == "0000" => None,
+                SavedMovement::Notation(notation) => {
+                    let board = match board {
+                        Some(ref board) => board,
+                        None => board.insert(notation_board(saved.width, saved.height)?),
+                    };
+                    Some(Movement::try_from((notation.as_str(), board))?)
+                }
+            };
+            moves.push(RecordedMove {
+                movement,
+                think_time: saved_move.think_time,
+                score: saved_move.score,
+            });
+        }
+        Ok(GameRecord {
+            board: saved.board,
+            width: saved.width,
+            height: saved.height,
+            holes: saved.holes,
+            starting_blobs: saved.starting_blobs,
+            first_player: saved.first_player,
+            players: saved.players,
+            moves,
+            result: saved.result,
+            time_control: saved.time_control,
+            lost_on_time: saved.lost_on_time,
+            illegal_move: saved.illegal_move,
+        })
+    }
+}
+
+impl GameRecord {
+    /// Start recording a game on given board between given players.
+    pub fn new<R: fmt::Display, B: fmt::Display>(board: &Board, red: &R, blue: &B) -> Self {
+        GameRecord {
+            board: String::new(),
+            width: board.width,
+            height: board.height,
+            holes: board.holes.positions().collect(),
+            starting_blobs: [
+                board.starting_blobs[0].positions().collect(),
+                board.starting_blobs[1].positions().collect(),
+            ],
+            first_player: false,
+            players: [red.to_string(), blue.to_string()],
+            moves: Vec::new(),
+            result: None,
+            time_control: TimeControl::Unlimited,
+            lost_on_time: None,
+            illegal_move: None,
+        }
+    }
+
+    /// Start the game from given configuration (on the same board) instead of the initial one.
+    pub fn starting_configuration(self, start: &Configuration) -> Self {
+        GameRecord {
+            starting_blobs: [
+                start.blobs(false).positions().collect(),
+                start.blobs(true).positions().collect(),
+            ],
+            first_player: start.current_player,
+            ..self
+        }
+    }
+
+    /// Player who lost by forfeit, on time or by playing an illegal movement, if any.
+    pub fn forfeit(&self) -> Option<bool> {
+        self.lost_on_time.or(self.illegal_move)
+    }
+
+    /// Name the board the game is played on.
+    pub fn board_name(self, name: &str) -> Self {
+        GameRecord {
+            board: name.to_owned(),
+            ..self
+        }
+    }
+
+    /// Record next turn, whose movement was not searched.
+    pub fn push(&mut self, movement: Option<Movement>, think_time: Duration) {
+        self.moves.push(RecordedMove {
+            movement,
+            think_time,
+            score: None,
+        })
+    }
+
+    /// Record the result of the finished game.
+    pub fn finish(&mut self, state: &Configuration) {
+        self.result = Some(state.red_margin());
+    }
+
+    /// Rebuild the board the game was played on.
+    /// Empty boards and starting blobs on holes (or on each other) are rejected.
+    pub fn rebuild_board(&self) -> Result<Board, BlobwarError> {
+        let size = self.width as usize * self.height as usize;
+        if size == 0 {
+            return Err(BlobwarError::EmptyBoard);
+        }
+        if size > MAX_CELLS {
+            return Err(BlobwarError::BoardTooLarge { cells: size });
+        }
+        let positions = |positions: &[Position]| {
+            positions
+                .iter()
+                .try_fold(Positions::default(), |all, &position| {
+                    if (position as usize) < size {
+                        Ok(all.union_with(Positions::single(position)))
+                    } else {
+                        Err(BlobwarError::OutOfRange { position, size })
+                    }
+                })
+        };
+        let holes = positions(&self.holes)?;
+        let starting_blobs = [
+            positions(&self.starting_blobs[0])?,
+            positions(&self.starting_blobs[1])?,
+        ];
+        let overlapping = starting_blobs[0]
+            .intersection_with(starting_blobs[1])
+            .union_with(starting_blobs[0].intersection_with(holes))
+            .union_with(starting_blobs[1].intersection_with(holes));
+        if let Some(position) = overlapping.positions().next() {
+            return Err(BlobwarError::OverlappingCells {
+                location: Location {
+                    line: (position / self.width) as usize + 1,
+                    column: (position % self.width) as usize + 1,
+                },
+            });
+        }
+        let mut board = Board::new(self.width, self.height, holes)?;
+        board.starting_blobs = starting_blobs;
+        Ok(board)
+    }
+
+    /// All configurations of the game on given (rebuilt) board, from the initial one to the
+    /// final one.
+    pub fn configurations<'b>(
+        &self,
+        board: &'b Board,
+    ) -> Result<Vec<Configuration<'b>>, BlobwarError> {
+        let mut start = board.initial_configuration();
+        start.current_player = self.first_player;
+        let mut configurations = vec![start];
+        for (ply, recorded) in self.moves.iter().enumerate() {
+            let state = configurations[ply];
+            let next = match recorded.movement {
+                Some(movement) => {
+                    let movement = movement.check_range(board)?;
+                    if !state.check_move(&movement) {
+                        return Err(BlobwarError::IllegalMove { ply: ply + 1 });
+                    }
+                    state.play(&movement)
+                }
+                None => state.skip_play(),
+            };
+            configurations.push(next);
+        }
+        Ok(configurations)
+    }
+
+    /// Save as JSON in given file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), BlobwarError> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(io::BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    /// Load from given JSON file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, BlobwarError> {
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(io::BufReader::new(file))?)
+    }
+}
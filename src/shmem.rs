@@ -1,9 +1,10 @@
 //! Provide methods sharing a `Movement` in shared memory between processes.
 //! Slave anytime process writes new moves atomically and master process only reads them.
+//! Statistics of the search leading to each move are shared alongside.
 use super::configuration::Movement;
+use super::strategy::{Counters, Score, SearchStats};
 use libc::off_t;
 use libc::{c_void, size_t};
-use nix;
 use nix::fcntl::{O_CREAT, O_RDWR};
 use nix::sys::mman::MAP_SHARED;
 use nix::sys::mman::{mmap, munmap};
@@ -16,9 +17,68 @@ use std::ptr;
 
 use std::mem;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Iteration times kept in shared statistics.
+const MAX_ITERATIONS: usize = 100;
+
+/// Length of principal variations kept in shared statistics.
+const MAX_LINE: usize = 64;
+
+/// `SearchStats` in fixed size memory.
+#[derive(Copy, Clone)]
+struct RawStats {
+    counters: Counters,
+    depth: u8,
+    iterations: usize,
+    /// Microseconds taken by each iteration.
+    times: [u64; MAX_ITERATIONS],
+    line: usize,
+    /// Packed movements (see `Movement::pack`).
+    moves: [u32; MAX_LINE],
+    value: Option<Score>,
+}
+
+impl RawStats {
+    fn new(stats: &SearchStats) -> Self {
+        let mut raw = RawStats {
+            counters: stats.counters,
+            depth: stats.depth,
+            iterations: stats.iterations.len().min(MAX_ITERATIONS),
+            times: [0; MAX_ITERATIONS],
+            line: stats.principal_variation.len().min(MAX_LINE),
+            moves: [0; MAX_LINE],
+            value: stats.value,
+        };
+        for (time, iteration) in raw.times.iter_mut().zip(&stats.iterations) {
+            *time = iteration.as_micros() as u64;
+        }
+        for (packed, movement) in raw.moves.iter_mut().zip(&stats.principal_variation) {
+            *packed = Movement::pack(Some(*movement));
+        }
+        raw
+    }
+
+    fn stats(&self) -> SearchStats {
+        SearchStats {
+            counters: self.counters,
+            depth: self.depth,
+            iterations: self.times[..self.iterations.min(MAX_ITERATIONS)]
+                .iter()
+                .map(|&time| Duration::from_micros(time))
+                .collect(),
+            principal_variation: self.moves[..self.line.min(MAX_LINE)]
+                .iter()
+                .filter_map(|&packed| Movement::unpack(packed))
+                .collect(),
+            value: self.value,
+        }
+    }
+}
 
 struct InnerAtomicMove {
     movements: [Option<Movement>; 2],
+    stats: [RawStats; 2],
     selected: AtomicUsize,
 }
 
@@ -59,6 +119,8 @@ impl AtomicMove {
             unsafe { (address as *mut InnerAtomicMove).as_mut().unwrap() };
         atomic.movements[0] = None;
         atomic.movements[1] = None;
+        atomic.stats[0] = RawStats::new(&Default::default());
+        atomic.stats[1] = RawStats::new(&Default::default());
         atomic.selected = Default::default();
 
         Ok(AtomicMove {
@@ -86,11 +148,12 @@ impl AtomicMove {
         })
     }
 
-    pub fn store(&mut self, movement: Option<Movement>) {
+    pub fn store(&mut self, movement: Option<Movement>, stats: &SearchStats) {
         let atomic: &mut InnerAtomicMove =
             unsafe { (self.address as *mut InnerAtomicMove).as_mut().unwrap() };
         let index = atomic.selected.load(Ordering::SeqCst);
         atomic.movements[(index + 1) % 2] = movement;
+        atomic.stats[(index + 1) % 2] = RawStats::new(stats);
         atomic.selected.fetch_add(1, Ordering::SeqCst);
     }
 
@@ -99,4 +162,10 @@ impl AtomicMove {
             unsafe { (self.address as *const InnerAtomicMove).as_ref().unwrap() };
         atomic.movements[atomic.selected.load(Ordering::SeqCst) % 2]
     }
+
+    pub fn load_stats(&self) -> SearchStats {
+        let atomic: &InnerAtomicMove =
+            unsafe { (self.address as *const InnerAtomicMove).as_ref().unwrap() };
+        atomic.stats[atomic.selected.load(Ordering::SeqCst) % 2].stats()
+    }
 }
@@ -1,97 +1,209 @@
 //! Alpha - Beta algorithm.
 use std::fmt;
+use std::time::Instant;
 
-use super::Strategy;
+use super::endgame::Endgame;
+use super::evaluator::{Evaluator, Material, Score};
+use super::iterative::{aspiration, AspirationStats};
+use super::ordering::MoveOrdering;
+use super::search::{
+    blocked, table_line, terminal_value, tie_rank, value_from_table, value_to_table, Counters,
+    SearchStats,
+};
+use super::transposition::{Entry, TableStats, TranspositionTable};
+use super::{SearchControl, Strategy};
 use crate::configuration::{Configuration, Movement};
 use crate::shmem::AtomicMove;
 
-fn alpha_beta_rec(
+/// Everything a search needs besides the current configuration.
+struct Search<'s, E> {
+    /// Player we search a move for. Values are seen from him : lower is better.
     player: bool,
-    state: &Configuration,
-    depth: u8,
-    alpha: i8,
-    beta: i8,
-) -> Option<(i8, Option<Movement>)> {
-    if depth == 0 {
-        return Some((
-            state.value()
-                * if state.current_player == player {
-                    1
-                } else {
-                    -1
-                },
-            None,
-        ));
-    }
-
-    let ok_moves = state.movements().filter(|mov| state.check_move(mov));
-
-    let mut check_moves_size = ok_moves.peekable();
-
-    // If no move is doable, return the value
-    if check_moves_size.peek() == None {
-        return Some((
-            state.value()
-                * if state.current_player == player {
-                    1
-                } else {
-                    -1
-                },
-            None,
-        ));
-    }
-    let (_, _, val, mov) = if state.current_player == player {
-        check_moves_size
-            .try_fold((alpha, beta, i8::MAX, None), |(alpha, beta, v, old_mov), new_mov| {
-                //We use try_fold to be able to break from the fold, and we simply return the result from the last Ok or first Err with the identity in unwrap_or_else
-                if let Some((resval, _)) =
-                    alpha_beta_rec(player, &state.play(&new_mov), depth - 1, alpha, beta)
-                {
-                    let new_v = v.min(resval);
-                    if new_v < alpha {
-                        return Err((alpha, beta, new_v, Some(new_mov)));
-                    };
-
-                    let (new_beta, best_mov) =
-                    if new_v < beta {
-                        (new_v, Some(new_mov))
-                    } else {
-                        (beta, old_mov)
-                    };
-                    
-                    Ok((alpha, new_beta, new_v, best_mov))
-                } else {
-                    Ok((alpha, beta, v, None))
-                }
-            })
-            .unwrap_or_else(|a| a)
-    } else {
-        check_moves_size
-            .try_fold((alpha, beta, i8::MIN, None), |(alpha, beta, v, old_mov), new_mov| {
-                //We use try_fold to be able to break from the fold, and we simply return the result from the last Ok or first Err with the identity in unwrap_or_else
-                if let Some((resval, _)) =
-                    alpha_beta_rec(player, &state.play(&new_mov), depth - 1, alpha, beta)
-                {
-                    let new_v = v.max(resval);
-                    if new_v > beta {
-                        return Err((alpha, beta, new_v, Some(new_mov)));
-                    }
-
-                    let (new_alpha, best_mov) =
-                    if new_v > alpha {
-                        (new_v, Some(new_mov))
-                    } else {
-                        (alpha, old_mov)
-                    };
-
-                    Ok((new_alpha, beta, new_v, best_mov))
-                } else {
-                    Ok((alpha, beta, v, None))
-                }
-            })
-            .unwrap_or_else(|a| a)
-    };
-    Some((val, mov))
+    /// Depth of the root, to know at which ply we are.
+    root_depth: u8,
+    table: &'s mut TranspositionTable,
+    ordering: &'s MoveOrdering,
+    control: &'s SearchControl,
+    evaluator: &'s E,
+    endgame: &'s Endgame,
+    counters: Counters,
+}
+
+impl<E: Evaluator> Search<'_, E> {
+    /// Evaluate leaf configuration.
+    fn evaluate(&self, state: &Configuration) -> Score {
+        self.evaluator.evaluate(state)
+            * if state.current_player == self.player {
+                1
+            } else {
+                -1
+            }
+    }
+
+    /// Value of a finished game.
+    fn terminal_value(&self, state: &Configuration, ply: usize) -> Score {
+        terminal_value(state, ply)
+            * if state.current_player == self.player {
+                1
+            } else {
+                -1
+            }
+    }
+
+    /// Solve configuration with few empty cells until the end of the game.
+    /// Return Some(None) if the solver could not prove its value.
+    #[allow(clippy::type_complexity)]
+    fn solve(
+        &self,
+        state: &Configuration,
+        ply: usize,
+        alpha: Score,
+        beta: Score,
+    ) -> Option<Option<(Score, Option<Movement>)>> {
+        // the solver's values are seen from the player to move, higher is better
+        if state.current_player == self.player {
+            let solved = self.endgame.search(
+                state,
+                self.control,
+                self.evaluator,
+                beta.saturating_neg(),
+                alpha.saturating_neg(),
+                ply,
+            )?;
+            Some(solved.map(|(value, movement)| (-value, movement)))
+        } else {
+            self.endgame
+                .search(state, self.control, self.evaluator, alpha, beta, ply)
+        }
+    }
+
+    fn alpha_beta_rec(
+        &mut self,
+        state: &Configuration,
+        depth: u8,
+        alpha: Score,
+        beta: Score,
+    ) -> Option<(Score, Option<Movement>)> {
+        if self.control.stopped() {
+            return None;
+        }
+        self.counters.nodes += 1;
+        let ply = (self.root_depth - depth) as usize;
+        if state.game_over() {
+            return Some((self.terminal_value(state, ply), None));
+        }
+        if self.endgame.applies(state) {
+            if let Some(solved) = self.solve(state, ply, alpha, beta)? {
+                return Some(solved);
+            }
+        }
+        if depth == 0 {
+            self.control.reach_horizon();
+            self.counters.evaluations += 1;
+            return Some((self.evaluate(state), None));
+        }
+
+        // entries are stored from red's point of view
+        let player = self.player;
+        let key = state.zobrist_hash();
+        let mut hash_move = None;
+        if let Some(entry) = self.table.probe(key) {
+            self.counters.table_hits += 1;
+            let mut entry = if player { entry.flipped() } else { entry };
+            entry.value = value_from_table(entry.value, ply);
+            if let Some(value) = entry.cutoff(depth, alpha, beta, false) {
+                // we cannot know if the stored search was cut by its depth limit
+                self.control.reach_horizon();
+                return Some((value, entry.best_move));
+            }
+            hash_move = entry.best_move;
+        }
+
+        let moves = self.ordering.order(state, hash_move, ply);
+
+        // If no move is doable, pass (the game ends if no one can move)
+        if moves.is_empty() {
+            if blocked(state) {
+                return Some((self.terminal_value(state, ply), None));
+            }
+            let (value, _) = self.alpha_beta_rec(&state.skip_play(), depth - 1, alpha, beta)?;
+            return Some((value, None));
+        }
+        let (_, _, val, mov) = if state.current_player == player {
+            moves
+                .into_iter()
+                .try_fold(
+                    (alpha, beta, Score::MAX, None),
+                    |(alpha, beta, v, old_mov), new_mov| {
+                        //We use try_fold to be able to break from the fold, and we simply return the result from the last Ok or first Err with the identity in unwrap_or_else
+                        // at the root, a move of lower rank equal to the best one replaces it
+                        // (see `tie_rank`) : its search window lets ties through
+                        let ties = ply == 0
+                            && old_mov.is_some_and(|old| tie_rank(&new_mov) < tie_rank(&old));
+                        let child_beta = if ties { beta.saturating_add(1) } else { beta };
+                        if let Some((resval, _)) =
+                            self.alpha_beta_rec(&state.play(&new_mov), depth - 1, alpha, child_beta)
+                        {
+                            let new_v = v.min(resval);
+                            if new_v < alpha {
+                                self.ordering.record_cutoff(&new_mov, ply, depth);
+                                self.counters.cutoffs += 1;
+                                return Err((alpha, beta, new_v, Some(new_mov)));
+                            };
+
+                            let (new_beta, best_mov) = if new_v < beta || (ties && resval == beta) {
+                                (new_v, Some(new_mov))
+                            } else {
+                                (beta, old_mov)
+                            };
+
+                            Ok((alpha, new_beta, new_v, best_mov))
+                        } else {
+                            Ok((alpha, beta, v, None))
+                        }
+                    },
+                )
+                .unwrap_or_else(|a| a)
+        } else {
+            moves
+                .into_iter()
+                .try_fold(
+                    (alpha, beta, Score::MIN, None),
+                    |(alpha, beta, v, old_mov), new_mov| {
+                        //We use try_fold to be able to break from the fold, and we simply return the result from the last Ok or first Err with the identity in unwrap_or_else
+                        if let Some((resval, _)) =
+                            self.alpha_beta_rec(&state.play(&new_mov), depth - 1, alpha, beta)
+                        {
+                            let new_v = v.max(resval);
+                            if new_v > beta {
+                                self.ordering.record_cutoff(&new_mov, ply, depth);
+                                self.counters.cutoffs += 1;
+                                return Err((alpha, beta, new_v, Some(new_mov)));
+                            }
+
+                            let (new_alpha, best_mov) = if new_v > alpha {
+                                (new_v, Some(new_mov))
+                            } else {
+                                (alpha, old_mov)
+                            };
+
+                            Ok((new_alpha, beta, new_v, best_mov))
+                        } else {
+                            Ok((alpha, beta, v, None))
+                        }
+                    },
+                )
+                .unwrap_or_else(|a| a)
+        };
+        if self.control.stopped() {
+            return None;
+        }
+        let mut entry = Entry::new(depth, val, alpha, beta, mov);
+        entry.value = value_to_table(val, ply);
+        self.table
+            .store(key, if player { entry.flipped() } else { entry });
+        Some((val, mov))
+    }
 }
 
 /// Anytime alpha beta algorithm.
@@ -100,24 +212,183 @@ fn alpha_beta_rec(
 /// This function is intended to be called from blobwar_iterative_deepening.
 pub fn alpha_beta_anytime(state: &Configuration) {
     let mut movement = AtomicMove::connect().expect("failed connecting to shmem");
+    let mut alpha_beta = AlphaBeta::new(2);
+    // each iteration is searched in a window around the value of the previous one
+    let control = SearchControl::default();
+    let mut stats = AspirationStats::default();
+    let mut previous = None;
+    let mut iterations = Vec::new();
     for depth in 2..100 {
-        let chosen_movement = AlphaBeta(depth).compute_next_move(state);
-        movement.store(chosen_movement);
+        let start = Instant::now();
+        alpha_beta.depth = depth;
+        let result = aspiration(previous, &mut stats, |alpha, beta| {
+            alpha_beta.search_window(state, &control, alpha, beta)
+        });
+        previous = result.map(|(value, _)| value);
+        iterations.push(start.elapsed());
+        movement.store(
+            result.and_then(|(_, chosen_movement)| chosen_movement),
+            &control.stats(depth, iterations.clone()),
+        );
     }
 }
 
 /// Alpha - Beta algorithm with given maximum number of recursions.
-pub struct AlphaBeta(pub u8);
+/// Search results are kept in a transposition table between calls.
+/// Leaves are evaluated with `E` (blob count by default). Positions with few empty cells are
+/// solved until the end of the game instead, when the solver can prove their value (see
+/// `endgame`).
+pub struct AlphaBeta<E = Material> {
+    /// Maximum number of recursions.
+    pub depth: u8,
+    table: TranspositionTable,
+    ordering: MoveOrdering,
+    endgame: Endgame,
+    evaluator: E,
+    nodes: u64,
+    /// Root key and depth of the last search : re-searches of the same depth (in other windows)
+    /// keep the move ordering.
+    last_root: Option<(u64, u8)>,
+    stats: SearchStats,
+}
+
+impl AlphaBeta {
+    /// Alpha - Beta with given depth and a default sized transposition table.
+    pub fn new(depth: u8) -> Self {
+        AlphaBeta {
+            depth,
+            table: Default::default(),
+            ordering: Default::default(),
+            endgame: Default::default(),
+            evaluator: Material,
+            nodes: 0,
+            last_root: None,
+            stats: Default::default(),
+        }
+    }
+}
 
-impl fmt::Display for AlphaBeta {
+impl<E: Evaluator> AlphaBeta<E> {
+    /// Use a transposition table of 2^bits entries.
+    pub fn table_bits(self, bits: u8) -> Self {
+        AlphaBeta {
+            table: TranspositionTable::new(bits),
+            ..self
+        }
+    }
+
+    /// Enable or disable move ordering.
+    pub fn ordering(self, enabled: bool) -> Self {
+        AlphaBeta {
+            ordering: if enabled {
+                Default::default()
+            } else {
+                MoveOrdering::disabled()
+            },
+            ..self
+        }
+    }
+
+    /// Solve positions with at most given number of empty cells until the end of the game with
+    /// the endgame solver, instead of searching them (`DEFAULT_THRESHOLD` by default, 0 disables
+    /// it). Positions whose values the solver cannot prove are searched.
+    pub fn endgame(self, threshold: usize) -> Self {
+        AlphaBeta {
+            endgame: Endgame::new(threshold),
+            ..self
+        }
+    }
+
+    /// Evaluate leaves with given evaluator.
+    pub fn evaluator<F: Evaluator>(self, evaluator: F) -> AlphaBeta<F> {
+        AlphaBeta {
+            depth: self.depth,
+            table: self.table,
+            ordering: self.ordering,
+            endgame: self.endgame,
+            evaluator,
+            nodes: self.nodes,
+            last_root: self.last_root,
+            stats: self.stats,
+        }
+    }
+
+    /// Transposition table usage counters.
+    pub fn table_stats(&self) -> TableStats {
+        self.table.stats()
+    }
+
+    /// Number of nodes visited since creation, by the search and the endgame solver.
+    pub fn nodes(&self) -> u64 {
+        self.nodes + self.endgame.nodes()
+    }
+
+    /// Search best move and its value under given control.
+    /// Return None if the search was stopped before completion.
+    pub fn search(
+        &mut self,
+        state: &Configuration,
+        control: &SearchControl,
+    ) -> Option<(Score, Option<Movement>)> {
+        self.search_window(state, control, Score::MIN, Score::MAX)
+    }
+
+    /// Search best move and its value in window `alpha..beta`.
+    /// A value lower or equal than alpha (resp. greater or equal than beta) is only an upper
+    /// (resp. lower) bound of the real value, and the returned move is then meaningless.
+    /// Counters and the principal variation are recorded in the control.
+    pub fn search_window(
+        &mut self,
+        state: &Configuration,
+        control: &SearchControl,
+        alpha: Score,
+        beta: Score,
+    ) -> Option<(Score, Option<Movement>)> {
+        let root = Some((state.zobrist_hash(), self.depth));
+        if self.last_root != root {
+            self.last_root = root;
+            self.ordering.age();
+            self.endgame.age();
+        }
+        let mut search = Search {
+            player: state.current_player,
+            root_depth: self.depth,
+            table: &mut self.table,
+            ordering: &self.ordering,
+            control,
+            evaluator: &self.evaluator,
+            endgame: &self.endgame,
+            counters: Default::default(),
+        };
+        let result = search.alpha_beta_rec(state, self.depth, alpha, beta);
+        self.nodes += search.counters.nodes;
+        control.record(search.counters);
+        let (value, movement) = result?;
+        let table = &self.table;
+        control.set_result(
+            value,
+            table_line(state, movement, self.depth, |key| table.get(key)),
+        );
+        result
+    }
+}
+
+impl<E> fmt::Display for AlphaBeta<E> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Alpha - Beta (max level: {})", self.0)
+        write!(f, "Alpha - Beta (max level: {})", self.depth)
     }
 }
 
-impl Strategy for AlphaBeta {
+impl<E: Evaluator> Strategy for AlphaBeta<E> {
     fn compute_next_move(&mut self, state: &Configuration) -> Option<Movement> {
-        let (_, mov) = alpha_beta_rec(state.current_player, &state, self.0, i8::MIN, i8::MAX)?;
+        let control = SearchControl::default();
+        let start = Instant::now();
+        let (_, mov) = self.search(state, &control)?;
+        self.stats = control.stats(self.depth, vec![start.elapsed()]);
         mov
     }
+
+    fn search_stats(&self) -> Option<SearchStats> {
+        Some(self.stats.clone())
+    }
 }
@@ -1,205 +1,312 @@
-//! Alpha - Beta algorithm.
+//! Parallel Alpha - Beta algorithm ("young brothers wait" splitting).
+//! At each node the eldest child is searched first, alone. Its value bounds the search of its
+//! younger brothers, which are then searched in parallel. They share the best value found so
+//! far through an atomic, so that each brother starts with the tightest known window, and a
+//! cutoff cancels all brothers still running.
+//! Values match sequential searches. Which thread stores a transposition table entry or records
+//! a cutoff first changes the move ordering of later searches, so ties between children are
+//! broken by the rank of their moves rather than by the order they were searched in (see
+//! `tie_rank`) : the chosen moves match sequential searches too.
 use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Instant;
 
-use rayon::prelude::{ParallelBridge, ParallelIterator};
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 
-use super::Strategy;
+use super::endgame::Endgame;
+use super::evaluator::{Evaluator, Material, Score};
+use super::iterative::{aspiration, AspirationStats};
+use super::ordering::MoveOrdering;
+use super::search::{
+    blocked, table_line, terminal_value, tie_rank, value_from_table, value_to_table, Counters,
+    SearchStats,
+};
+use super::transposition::{Entry, SharedTranspositionTable, TableStats};
+use super::{SearchControl, Strategy};
 use crate::configuration::{Configuration, Movement};
 use crate::shmem::AtomicMove;
 
-fn alpha_beta_par_rec(
+/// Cancellation flag of the children of a node, linked to the flags of its ancestors.
+struct Cancel<'p> {
+    flag: AtomicBool,
+    parent: Option<&'p Cancel<'p>>,
+}
+
+impl<'p> Cancel<'p> {
+    fn new(parent: Option<&'p Cancel<'p>>) -> Self {
+        Cancel {
+            flag: AtomicBool::new(false),
+            parent,
+        }
+    }
+
+    fn cancel(&self) {
+        self.flag.store(true, Ordering::Relaxed)
+    }
+
+    /// Was this flag or any ancestor's raised ?
+    fn cancelled(&self) -> bool {
+        self.flag.load(Ordering::Relaxed) || self.parent.is_some_and(Cancel::cancelled)
+    }
+}
+
+/// Best child value found so far at a node, with the rank of the child's move (see
+/// `tie_rank`), shared between the threads searching its children.
+/// Both are packed in one integer so that an atomic min (or max) keeps the best value and,
+/// amongst equal values, the lowest rank.
+struct Best {
+    packed: AtomicU64,
+    minimizing: bool,
+}
+
+impl Best {
+    fn new(minimizing: bool) -> Self {
+        Best {
+            packed: AtomicU64::new(if minimizing { u64::MAX } else { 0 }),
+            minimizing,
+        }
+    }
+
+    fn pack(&self, value: Score, rank: u32) -> u64 {
+        let value = (value as i64 - Score::MIN as i64) as u64;
+        let rank = if self.minimizing {
+            rank
+        } else {
+            u32::MAX - rank
+        };
+        value << 32 | rank as u64
+    }
+
+    /// Best value and rank of the child (`u32::MAX` if none).
+    fn get(&self) -> (Score, u32) {
+        let packed = self.packed.load(Ordering::Relaxed);
+        let value = ((packed >> 32) as i64 + Score::MIN as i64) as Score;
+        let rank = packed as u32;
+        let rank = if self.minimizing {
+            rank
+        } else {
+            u32::MAX - rank
+        };
+        (value, rank)
+    }
+
+    fn update(&self, value: Score, rank: u32) {
+        let packed = self.pack(value, rank);
+        if self.minimizing {
+            self.packed.fetch_min(packed, Ordering::Relaxed);
+        } else {
+            self.packed.fetch_max(packed, Ordering::Relaxed);
+        }
+    }
+
+    /// Window of the child of given rank in the node's window `alpha..beta` : it needs to
+    /// beat the best child, or to equal it if its rank is lower.
+    fn window(&self, rank: u32, alpha: Score, beta: Score) -> (Score, Score) {
+        let (best, best_rank) = self.get();
+        if self.minimizing {
+            let bound = if rank < best_rank {
+                best.saturating_add(1)
+            } else {
+                best
+            };
+            (alpha, beta.min(bound))
+        } else {
+            let bound = if rank < best_rank {
+                best.saturating_sub(1)
+            } else {
+                best
+            };
+            (alpha.max(bound), beta)
+        }
+    }
+
+    /// Does the best value cut the node's window `alpha..beta` ?
+    fn cuts(&self, alpha: Score, beta: Score) -> bool {
+        let (best, _) = self.get();
+        if self.minimizing {
+            best < alpha
+        } else {
+            best > beta
+        }
+    }
+}
+
+/// Everything a search needs besides the current configuration.
+struct Search<'s, E> {
+    /// Player we search a move for. Values are seen from him : lower is better.
     player: bool,
-    state: &Configuration,
-    depth: u8,
-    alpha: i8,
-    beta: i8,
-) -> Option<(i8, Option<Movement>)> {
-    if depth == 0 {
-        return Some((
-            state.value()
-                * if state.current_player == player {
-                    1
-                } else {
-                    -1
-                },
-            None,
-        ));
-    }
-
-    let ok_moves = state.movements().filter(|mov| state.check_move(mov));
-
-    let mut check_moves_size = ok_moves.peekable();
-
-    // If no move is doable, return the value
-    if check_moves_size.peek() == None {
-        return Some((
-            state.value()
-                * if state.current_player == player {
-                    1
-                } else {
-                    -1
-                },
-            None,
-        ));
-    }
-
-    if depth > 1 {
-        let (_, _, val, mov, _) = if state.current_player == player {
-            check_moves_size
-                .par_bridge()
-                .fold(
-                    || (alpha, beta, i8::MAX, None, true),
-                    |(alpha, beta, v, old_mov, should_continue), new_mov| {
-                        //We use try_fold to be able to break from the fold, and we simply return the result from the last Ok or first Err with the identity in unwrap_or_else
-                        if !should_continue {
-                            return (alpha, beta, v, old_mov, false);
-                        } //Softly break out of the fold
-
-                        if let Some((resval, _)) = alpha_beta_par_rec(
-                            player,
-                            &state.play(&new_mov),
-                            depth - 1,
-                            alpha,
-                            beta,
-                        ) {
-                            let new_v = v.min(resval);
-                            if new_v < alpha {
-                                return (alpha, beta, new_v, Some(new_mov), false);
-                            };
-
-                            let (new_beta, best_mov) = if new_v < beta {
-                                (new_v, Some(new_mov))
-                            } else {
-                                (beta, old_mov)
-                            };
-
-                            (alpha, new_beta, new_v, best_mov, true)
-                        } else {
-                            (alpha, beta, v, None, true)
-                        }
-                    },
-                )
-                .reduce(
-                    || (alpha, beta, i8::MAX, None, true),
-                    |(alpha, beta, old_v, old_mov, _), (_, _, v, mov, _)| {
-                        if v < old_v {
-                            (alpha, beta, v, mov, true)
-                        } else {
-                            (alpha, beta, old_v, old_mov, true)
-                        }
-                    },
-                )
+    /// Depth of the root, to know at which ply we are.
+    root_depth: u8,
+    table: &'s SharedTranspositionTable,
+    ordering: &'s MoveOrdering,
+    control: &'s SearchControl,
+    evaluator: &'s E,
+    endgame: &'s Endgame,
+    nodes: AtomicU64,
+    evaluations: AtomicU64,
+    cutoffs: AtomicU64,
+    table_hits: AtomicU64,
+}
+
+impl<E: Evaluator> Search<'_, E> {
+    /// Evaluate leaf configuration.
+    fn evaluate(&self, state: &Configuration) -> Score {
+        self.evaluator.evaluate(state)
+            * if state.current_player == self.player {
+                1
+            } else {
+                -1
+            }
+    }
+
+    /// Value of a finished game.
+    fn terminal_value(&self, state: &Configuration, ply: usize) -> Score {
+        terminal_value(state, ply)
+            * if state.current_player == self.player {
+                1
+            } else {
+                -1
+            }
+    }
+
+    /// Solve configuration with few empty cells until the end of the game.
+    /// Return Some(None) if the solver could not prove its value.
+    #[allow(clippy::type_complexity)]
+    fn solve(
+        &self,
+        state: &Configuration,
+        ply: usize,
+        alpha: Score,
+        beta: Score,
+    ) -> Option<Option<(Score, Option<Movement>)>> {
+        // the solver's values are seen from the player to move, higher is better
+        if state.current_player == self.player {
+            let solved = self.endgame.search(
+                state,
+                self.control,
+                self.evaluator,
+                beta.saturating_neg(),
+                alpha.saturating_neg(),
+                ply,
+            )?;
+            Some(solved.map(|(value, movement)| (-value, movement)))
         } else {
-            check_moves_size
-                .par_bridge()
-                .fold(
-                    || (alpha, beta, i8::MIN, None, true),
-                    |(alpha, beta, v, old_mov, should_continue), new_mov| {
-                        //We use try_fold to be able to break from the fold, and we simply return the result from the last Ok or first Err with the identity in unwrap_or_else
-                        if !should_continue {
-                            return (alpha, beta, v, old_mov, false);
-                        } //Softly break out of the fold
-
-                        if let Some((resval, _)) = alpha_beta_par_rec(
-                            player,
-                            &state.play(&new_mov),
-                            depth - 1,
-                            alpha,
-                            beta,
-                        ) {
-                            let new_v = v.max(resval);
-                            if new_v > beta {
-                                return (alpha, beta, new_v, Some(new_mov), false);
-                            }
-
-                            let (new_alpha, best_mov) = if new_v > alpha {
-                                (new_v, Some(new_mov))
-                            } else {
-                                (alpha, old_mov)
-                            };
-
-                            (new_alpha, beta, new_v, best_mov, true)
-                        } else {
-                            (alpha, beta, v, None, true)
-                        }
-                    },
-                )
-                .reduce(
-                    || (alpha, beta, i8::MIN, None, true),
-                    |(alpha, beta, old_v, old_mov, _), (_, _, v, mov, _)| {
-                        if v > old_v {
-                            (alpha, beta, v, mov, true)
-                        } else {
-                            (alpha, beta, old_v, old_mov, true)
-                        }
-                    },
-                )
+            self.endgame
+                .search(state, self.control, self.evaluator, alpha, beta, ply)
+        }
+    }
+
+    /// Return None if the search was stopped or cancelled.
+    fn alpha_beta_par_rec(
+        &self,
+        state: &Configuration,
+        depth: u8,
+        alpha: Score,
+        beta: Score,
+        cancel: &Cancel,
+    ) -> Option<(Score, Option<Movement>)> {
+        if self.control.stopped() || cancel.cancelled() {
+            return None;
+        }
+        self.nodes.fetch_add(1, Ordering::Relaxed);
+        let ply = (self.root_depth - depth) as usize;
+        if state.game_over() {
+            return Some((self.terminal_value(state, ply), None));
+        }
+        if self.endgame.applies(state) {
+            if let Some(solved) = self.solve(state, ply, alpha, beta)? {
+                return Some(solved);
+            }
+        }
+        if depth == 0 {
+            self.control.reach_horizon();
+            self.evaluations.fetch_add(1, Ordering::Relaxed);
+            return Some((self.evaluate(state), None));
+        }
+
+        // entries are stored from red's point of view
+        let player = self.player;
+        let key = state.zobrist_hash();
+        let mut hash_move = None;
+        if let Some(entry) = self.table.probe(key) {
+            self.table_hits.fetch_add(1, Ordering::Relaxed);
+            let mut entry = if player { entry.flipped() } else { entry };
+            entry.value = value_from_table(entry.value, ply);
+            if let Some(value) = entry.cutoff(depth, alpha, beta, true) {
+                // we cannot know if the stored search was cut by its depth limit
+                self.control.reach_horizon();
+                return Some((value, entry.best_move));
+            }
+            hash_move = entry.best_move;
+        }
+
+        let moves = self.ordering.order(state, hash_move, ply);
+
+        // If no move is doable, pass (the game ends if no one can move)
+        if moves.is_empty() {
+            if blocked(state) {
+                return Some((self.terminal_value(state, ply), None));
+            }
+            let (value, _) =
+                self.alpha_beta_par_rec(&state.skip_play(), depth - 1, alpha, beta, cancel)?;
+            return Some((value, None));
+        }
+
+        let minimizing = state.current_player == player;
+        let best = Best::new(minimizing);
+        let children = Cancel::new(Some(cancel));
+        // search given child and tell if it causes a cutoff
+        let search_child = |movement: &Movement| {
+            let rank = tie_rank(movement);
+            let (child_alpha, child_beta) = best.window(rank, alpha, beta);
+            let child = state.play(movement);
+            match self.alpha_beta_par_rec(&child, depth - 1, child_alpha, child_beta, &children) {
+                Some((value, _)) => {
+                    best.update(value, rank);
+                    let cut = best.cuts(alpha, beta);
+                    if cut {
+                        self.ordering.record_cutoff(movement, ply, depth);
+                        self.cutoffs.fetch_add(1, Ordering::Relaxed);
+                        children.cancel();
+                    }
+                    cut
+                }
+                None => false,
+            }
         };
-        Some((val, mov))
-    } else {
-        let (_, _, val, mov) = if state.current_player == player {
-            check_moves_size
-                .try_fold(
-                    (alpha, beta, i8::MAX, None),
-                    |(alpha, beta, v, old_mov), new_mov| {
-                        //We use try_fold to be able to break from the fold, and we simply return the result from the last Ok or first Err with the identity in unwrap_or_else
-                        if let Some((resval, _)) = alpha_beta_par_rec(
-                            player,
-                            &state.play(&new_mov),
-                            depth - 1,
-                            alpha,
-                            beta,
-                        ) {
-                            let new_v = v.min(resval);
-                            if new_v < alpha {
-                                return Err((alpha, beta, new_v, Some(new_mov)));
-                            };
-
-                            let (new_beta, best_mov) = if new_v < beta {
-                                (new_v, Some(new_mov))
-                            } else {
-                                (beta, old_mov)
-                            };
-
-                            Ok((alpha, new_beta, new_v, best_mov))
-                        } else {
-                            Ok((alpha, beta, v, None))
-                        }
-                    },
-                )
-                .unwrap_or_else(|a| a)
+        // young brothers wait for the eldest
+        if !search_child(&moves[0]) {
+            if depth > 1 {
+                moves[1..].par_iter().for_each(|movement| {
+                    if !children.cancelled() {
+                        search_child(movement);
+                    }
+                });
+            } else {
+                for movement in &moves[1..] {
+                    if search_child(movement) {
+                        break;
+                    }
+                }
+            }
+        }
+        if self.control.stopped() || cancel.cancelled() {
+            return None;
+        }
+        let (val, rank) = best.get();
+        let improved = if minimizing { val < beta } else { val > alpha };
+        let mov = if improved {
+            moves
+                .iter()
+                .copied()
+                .find(|movement| tie_rank(movement) == rank)
         } else {
-            check_moves_size
-                .try_fold(
-                    (alpha, beta, i8::MIN, None),
-                    |(alpha, beta, v, old_mov), new_mov| {
-                        //We use try_fold to be able to break from the fold, and we simply return the result from the last Ok or first Err with the identity in unwrap_or_else
-                        if let Some((resval, _)) = alpha_beta_par_rec(
-                            player,
-                            &state.play(&new_mov),
-                            depth - 1,
-                            alpha,
-                            beta,
-                        ) {
-                            let new_v = v.max(resval);
-                            if new_v > beta {
-                                return Err((alpha, beta, new_v, Some(new_mov)));
-                            }
-
-                            let (new_alpha, best_mov) = if new_v > alpha {
-                                (new_v, Some(new_mov))
-                            } else {
-                                (alpha, old_mov)
-                            };
-
-                            Ok((new_alpha, beta, new_v, best_mov))
-                        } else {
-                            Ok((alpha, beta, v, None))
-                        }
-                    },
-                )
-                .unwrap_or_else(|a| a)
+            None
         };
+        let mut entry = Entry::new(depth, val, alpha, beta, mov);
+        entry.value = value_to_table(val, ply);
+        self.table
+            .store(key, if player { entry.flipped() } else { entry });
         Some((val, mov))
     }
 }
@@ -210,24 +317,192 @@ fn alpha_beta_par_rec(
 /// This function is intended to be called from blobwar_iterative_deepening.
 pub fn alpha_beta_par_anytime(state: &Configuration) {
     let mut movement = AtomicMove::connect().expect("failed connecting to shmem");
+    let mut alpha_beta = AlphaBetaPar::new(2);
+    // each iteration is searched in a window around the value of the previous one
+    let control = SearchControl::default();
+    let mut stats = AspirationStats::default();
+    let mut previous = None;
+    let mut iterations = Vec::new();
     for depth in 2..100 {
-        let chosen_movement = AlphaBetaPar(depth).compute_next_move(state);
-        movement.store(chosen_movement);
+        let start = Instant::now();
+        alpha_beta.depth = depth;
+        let result = aspiration(previous, &mut stats, |alpha, beta| {
+            alpha_beta.search_window(state, &control, alpha, beta)
+        });
+        previous = result.map(|(value, _)| value);
+        iterations.push(start.elapsed());
+        movement.store(
+            result.and_then(|(_, chosen_movement)| chosen_movement),
+            &control.stats(depth, iterations.clone()),
+        );
     }
 }
 
 /// Alpha - Beta algorithm with given maximum number of recursions.
-pub struct AlphaBetaPar(pub u8);
+/// Threads share a lock-free transposition table, kept between calls.
+/// Leaves are evaluated with `E` (blob count by default). Positions with few empty cells are
+/// solved until the end of the game instead, when the solver can prove their value (see
+/// `endgame`).
+pub struct AlphaBetaPar<E = Material> {
+    /// Maximum number of recursions.
+    pub depth: u8,
+    table: SharedTranspositionTable,
+    ordering: MoveOrdering,
+    endgame: Endgame,
+    evaluator: E,
+    nodes: u64,
+    /// Root key and depth of the last search : re-searches of the same depth (in other windows)
+    /// keep the move ordering.
+    last_root: Option<(u64, u8)>,
+    stats: SearchStats,
+}
+
+impl AlphaBetaPar {
+    /// Parallel Alpha - Beta with given depth and a default sized transposition table.
+    pub fn new(depth: u8) -> Self {
+        AlphaBetaPar {
+            depth,
+            table: Default::default(),
+            ordering: Default::default(),
+            endgame: Default::default(),
+            evaluator: Material,
+            nodes: 0,
+            last_root: None,
+            stats: Default::default(),
+        }
+    }
+}
+
+impl<E: Evaluator> AlphaBetaPar<E> {
+    /// Use a transposition table of 2^bits entries.
+    pub fn table_bits(self, bits: u8) -> Self {
+        AlphaBetaPar {
+            table: SharedTranspositionTable::new(bits),
+            ..self
+        }
+    }
+
+    /// Enable or disable move ordering.
+    pub fn ordering(self, enabled: bool) -> Self {
+        AlphaBetaPar {
+            ordering: if enabled {
+                Default::default()
+            } else {
+                MoveOrdering::disabled()
+            },
+            ..self
+        }
+    }
 
-impl fmt::Display for AlphaBetaPar {
+    /// Solve positions with at most given number of empty cells until the end of the game with
+    /// the endgame solver, instead of searching them (`DEFAULT_THRESHOLD` by default, 0 disables
+    /// it). Positions whose values the solver cannot prove are searched.
+    pub fn endgame(self, threshold: usize) -> Self {
+        AlphaBetaPar {
+            endgame: Endgame::new(threshold),
+            ..self
+        }
+    }
+
+    /// Evaluate leaves with given evaluator.
+    pub fn evaluator<F: Evaluator>(self, evaluator: F) -> AlphaBetaPar<F> {
+        AlphaBetaPar {
+            depth: self.depth,
+            table: self.table,
+            ordering: self.ordering,
+            endgame: self.endgame,
+            evaluator,
+            nodes: self.nodes,
+            last_root: self.last_root,
+            stats: self.stats,
+        }
+    }
+
+    /// Transposition table usage counters.
+    pub fn table_stats(&self) -> TableStats {
+        self.table.stats()
+    }
+
+    /// Number of nodes visited since creation, by the search and the endgame solver.
+    pub fn nodes(&self) -> u64 {
+        self.nodes + self.endgame.nodes()
+    }
+
+    /// Search best move and its value under given control.
+    /// Return None if the search was stopped before completion.
+    pub fn search(
+        &mut self,
+        state: &Configuration,
+        control: &SearchControl,
+    ) -> Option<(Score, Option<Movement>)> {
+        self.search_window(state, control, Score::MIN, Score::MAX)
+    }
+
+    /// Search best move and its value in window `alpha..beta`.
+    /// A value lower or equal than alpha (resp. greater or equal than beta) is only an upper
+    /// (resp. lower) bound of the real value, and the returned move is then meaningless.
+    /// Counters and the principal variation are recorded in the control.
+    pub fn search_window(
+        &mut self,
+        state: &Configuration,
+        control: &SearchControl,
+        alpha: Score,
+        beta: Score,
+    ) -> Option<(Score, Option<Movement>)> {
+        let root = Some((state.zobrist_hash(), self.depth));
+        if self.last_root != root {
+            self.last_root = root;
+            self.ordering.age();
+            self.endgame.age();
+        }
+        let search = Search {
+            player: state.current_player,
+            root_depth: self.depth,
+            table: &self.table,
+            ordering: &self.ordering,
+            control,
+            evaluator: &self.evaluator,
+            endgame: &self.endgame,
+            nodes: AtomicU64::new(0),
+            evaluations: AtomicU64::new(0),
+            cutoffs: AtomicU64::new(0),
+            table_hits: AtomicU64::new(0),
+        };
+        let result = search.alpha_beta_par_rec(state, self.depth, alpha, beta, &Cancel::new(None));
+        let counters = Counters {
+            nodes: search.nodes.into_inner(),
+            evaluations: search.evaluations.into_inner(),
+            cutoffs: search.cutoffs.into_inner(),
+            table_hits: search.table_hits.into_inner(),
+        };
+        self.nodes += counters.nodes;
+        control.record(counters);
+        let (value, movement) = result?;
+        let table = &self.table;
+        control.set_result(
+            value,
+            table_line(state, movement, self.depth, |key| table.get(key)),
+        );
+        result
+    }
+}
+
+impl<E> fmt::Display for AlphaBetaPar<E> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Alpha - Beta Par (max level: {})", self.0)
+        write!(f, "Alpha - Beta Par (max level: {})", self.depth)
     }
 }
 
-impl Strategy for AlphaBetaPar {
+impl<E: Evaluator> Strategy for AlphaBetaPar<E> {
     fn compute_next_move(&mut self, state: &Configuration) -> Option<Movement> {
-        let (_, mov) = alpha_beta_par_rec(state.current_player, &state, self.0, i8::MIN, i8::MAX)?;
+        let control = SearchControl::default();
+        let start = Instant::now();
+        let (_, mov) = self.search(state, &control)?;
+        self.stats = control.stats(self.depth, vec![start.elapsed()]);
         mov
     }
+
+    fn search_stats(&self) -> Option<SearchStats> {
+        Some(self.stats.clone())
+    }
 }
@@ -0,0 +1,66 @@
+//! Play moves from an opening book while the game is in it.
+use std::fmt;
+
+use super::{SearchStats, Strategy};
+use crate::book::OpeningBook;
+use crate::clock::Clock;
+use crate::configuration::{Configuration, Movement};
+
+/// Wrap a strategy : book moves are played when available, the strategy is asked otherwise.
+pub struct BookStrategy<S> {
+    book: OpeningBook,
+    strategy: S,
+    hits: u64,
+    /// Was the last move found in the book ?
+    in_book: bool,
+}
+
+impl<S: Strategy> BookStrategy<S> {
+    /// Play moves of given book before falling back to given strategy.
+    pub fn new(book: OpeningBook, strategy: S) -> Self {
+        BookStrategy {
+            book,
+            strategy,
+            hits: 0,
+            in_book: false,
+        }
+    }
+
+    /// Number of moves played from the book since creation.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+}
+
+impl<S: fmt::Display> fmt::Display for BookStrategy<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} with opening book", self.strategy)
+    }
+}
+
+impl<S: Strategy> Strategy for BookStrategy<S> {
+    fn compute_next_move(&mut self, state: &Configuration) -> Option<Movement> {
+        let entry = self.book.probe(state);
+        self.in_book = entry.is_some();
+        match entry {
+            Some(entry) => {
+                self.hits += 1;
+                Some(entry.movement)
+            }
+            None => self.strategy.compute_next_move(state),
+        }
+    }
+
+    /// Book moves are not searched : they have no statistics.
+    fn search_stats(&self) -> Option<SearchStats> {
+        if self.in_book {
+            None
+        } else {
+            self.strategy.search_stats()
+        }
+    }
+
+    fn set_clock(&mut self, clock: Clock) {
+        self.strategy.set_clock(clock)
+    }
+}
@@ -0,0 +1,583 @@
+//! Endgame solver.
+//! When few empty cells remain, searching until the end of the game is affordable and gives the
+//! real outcome instead of an evaluation.
+//! The game ends when a player is wiped out, when the board is full or when no one can move.
+//! A line coming back to a position it went through never ends : it is scored as a draw. Values
+//! depending on such a repetition depend on the line reaching the position and are not kept in
+//! the table.
+//! Jumps do not fill the board so lines of jumps can still go on for very long : values are
+//! proven by searches cutting lines after a given number of plies in a row without a
+//! duplication. Cut lines are valued as the best possible outcome for a player then the other,
+//! giving a lower and an upper bound of the real value. The limit is raised until both agree :
+//! solved values are always exact. Solving gives up after a given number of nodes (see
+//! `Endgame::proof_nodes`).
+//! Cut lines can be evaluated instead (see `Endgame::jump_limit`) : values of positions the
+//! solver could not prove are then approximations, which solutions report (see
+//! `Solution::exact`), and the control is told that the horizon was reached.
+//! Standalone solutions (see `Endgame::solve`) maximize the final margin and prefer the fastest
+//! win (or slowest loss) amongst equal margins. Within alpha - beta searches only the winner
+//! counts, as for games ended within the search (see `MATE`).
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::evaluator::{Evaluator, Material, Score};
+use super::ordering::MoveOrdering;
+use super::search::{blocked, terminal_value, value_from_table, value_to_table, Counters, MATE};
+use super::transposition::{Entry, SharedTranspositionTable, DEFAULT_TABLE_BITS};
+use super::SearchControl;
+use crate::configuration::{Configuration, Movement};
+
+/// Number of empty cells below which positions are solved unless told otherwise.
+/// Values of positions with more empty cells are rarely proven within the default budget.
+pub const DEFAULT_THRESHOLD: usize = 2;
+
+/// Number of nodes searched by default before giving up on proving a value.
+pub const DEFAULT_PROOF_NODES: u64 = 100_000;
+
+/// Depth of table entries whose subtree was never cut by the jump limit : they are valid
+/// whatever the number of plies left before it.
+const SOLVED: u8 = u8::MAX;
+
+/// Line index of searches whose values do not depend on a repetition.
+const NO_REPETITION: usize = usize::MAX;
+
+/// Bound of the full search window (not `Score::MIN` so that it can be negated).
+const INFINITY: Score = Score::MAX;
+
+/// Margin values are margins times this scale, plus a bonus for ending the game sooner.
+const SCALE: Score = 1024;
+
+/// Xored with the keys of positions solved for alpha - beta searches : their values are not on
+/// the same scale as margin values.
+const WINNER_KEY: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Xored with the keys of positions whose values are bounds favoring red or blue.
+const FAVOR_KEYS: [u64; 2] = [0xC2B2_AE3D_27D4_EB4F, 0x1656_67B1_9E37_79F9];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// How finished games are valued.
+enum Scoring {
+    /// Final margin first, then number of plies.
+    Margin,
+    /// Winner first, then number of plies (see `terminal_value`).
+    Winner,
+}
+
+impl Scoring {
+    /// Value of the end of the game on given configuration after given number of plies, for the
+    /// player to move.
+    fn final_value(self, state: &Configuration, ply: usize) -> Score {
+        match self {
+            Scoring::Margin => margin_value(-state.value() as Score, ply),
+            Scoring::Winner => -terminal_value(state, ply),
+        }
+    }
+
+    /// Value of a line coming back to a position it went through.
+    fn repetition_value(self) -> Score {
+        0
+    }
+
+    /// Best value the player to move could get from a position with given number of cells,
+    /// found after given number of plies : winning the whole board on next ply.
+    fn best_value(self, cells: Score, ply: usize) -> Score {
+        match self {
+            Scoring::Margin => margin_value(cells, ply + 1),
+            Scoring::Winner => MATE - (ply + 1) as Score,
+        }
+    }
+
+    /// Value of given configuration, where a line was cut, for the player to move.
+    fn leaf_value(self, state: &Configuration, evaluator: &dyn Evaluator) -> Score {
+        let value = -evaluator.evaluate(state);
+        match self {
+            Scoring::Margin => value * SCALE,
+            Scoring::Winner => value,
+        }
+    }
+
+    /// Values of finished games are stored in the transposition table relatively to the stored
+    /// position. Values of cut lines do not depend on the ply and are stored as they are.
+    fn stored_value(self, value: Score, ply: usize) -> Score {
+        match self {
+            Scoring::Margin if value % SCALE == 0 => value,
+            Scoring::Margin => value + value.signum() * ply as Score,
+            Scoring::Winner => value_to_table(value, ply),
+        }
+    }
+
+    /// Value found in the transposition table, back to the ply we are at.
+    fn probed_value(self, value: Score, ply: usize) -> Score {
+        match self {
+            Scoring::Margin if value % SCALE == 0 => value,
+            Scoring::Margin => value - value.signum() * ply as Score,
+            Scoring::Winner => value_from_table(value, ply),
+        }
+    }
+
+    /// Table key of given zobrist hash.
+    fn key(self, hash: u64) -> u64 {
+        match self {
+            Scoring::Margin => hash,
+            Scoring::Winner => hash ^ WINNER_KEY,
+        }
+    }
+}
+
+/// Margin value of a game ended with given margin after given number of plies.
+fn margin_value(margin: Score, ply: usize) -> Score {
+    // keep a bonus so that final values are told apart from cut lines
+    let ply = (ply as Score).min(SCALE - 2);
+    margin * SCALE + margin.signum() * (SCALE - 1 - ply)
+}
+
+/// Margin of a margin value.
+fn value_margin(value: Score) -> Score {
+    value / SCALE
+}
+
+/// Number of plies until the end of the game of a margin value (0 for draws).
+fn value_plies(value: Score) -> u32 {
+    let bonus = (value % SCALE).abs();
+    if bonus == 0 {
+        0
+    } else {
+        (SCALE - 1 - bonus) as u32
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// How lines cut by the jump limit are valued.
+enum Cut {
+    /// With the evaluator : values are approximations.
+    Evaluate,
+    /// As the best possible outcome for given player (true for red) : values are lower bounds
+    /// of the real ones for the other player and upper bounds for this one.
+    Favor(bool),
+}
+
+impl Cut {
+    /// Table key of positions whose values depend on cut lines.
+    fn key(self, key: u64) -> u64 {
+        match self {
+            Cut::Evaluate => key,
+            Cut::Favor(player) => key ^ FAVOR_KEYS[player as usize],
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Result of a solved position.
+pub struct Solution {
+    /// Final margin (blobs of the player to move minus blobs of his adversary).
+    pub margin: Score,
+    /// Number of plies until the end of the game (0 for draws).
+    pub plies: u32,
+    /// Best move, None if the player to move has to pass.
+    pub movement: Option<Movement>,
+    /// Did all lines reach the end of the game or a repetition ? Otherwise the margin counts
+    /// blobs where lines were cut by the jump limit (see `Endgame::jump_limit`).
+    pub exact: bool,
+}
+
+/// Everything a search needs besides the current configuration.
+struct Search<'s> {
+    table: &'s SharedTranspositionTable,
+    ordering: &'s MoveOrdering,
+    control: &'s SearchControl,
+    scoring: Scoring,
+    /// Valuation of cut lines.
+    cut: Cut,
+    /// Evaluation of positions where lines are cut.
+    evaluator: &'s dyn Evaluator,
+    counters: Counters,
+    /// Plies in a row without a duplication after which lines are cut.
+    jump_limit: u8,
+    /// Number of cells of the board, blobs or empty.
+    cells: Score,
+    /// Number of nodes left before giving up.
+    nodes_left: u64,
+    /// Were lines cut by the jump limit ?
+    limited: bool,
+    /// Keys of the positions of the current line.
+    line: Vec<u64>,
+    /// Lowest line index of the positions repeated by the current subtree.
+    repeated: usize,
+}
+
+impl Search<'_> {
+    /// Negamax search : values are final values seen from the player to move.
+    /// `plies_left` is the number of plies without a duplication left before the line is cut.
+    fn solve_rec(
+        &mut self,
+        state: &Configuration,
+        mut alpha: Score,
+        beta: Score,
+        ply: usize,
+        plies_left: u8,
+    ) -> Option<(Score, Option<Movement>)> {
+        if self.control.stopped() || self.nodes_left == 0 {
+            return None;
+        }
+        self.nodes_left -= 1;
+        self.counters.nodes += 1;
+        if state.game_over() {
+            return Some((self.scoring.final_value(state, ply), None));
+        }
+        let key = self.scoring.key(state.zobrist_hash());
+        if let Some(index) = self.line.iter().position(|&seen| seen == key) {
+            self.repeated = self.repeated.min(index);
+            return Some((self.scoring.repetition_value(), None));
+        }
+        if plies_left == 0 {
+            self.limited = true;
+            return Some((self.cut_value(state, ply), None));
+        }
+
+        // solved values do not depend on cut lines : they are shared by all valuations
+        let cut_key = self.cut.key(key);
+        let mut hash_move = None;
+        for &probed_key in [key, cut_key].iter() {
+            if let Some(mut entry) = self.table.probe(probed_key) {
+                self.counters.table_hits += 1;
+                entry.value = self.scoring.probed_value(entry.value, ply);
+                let solved = entry.depth == SOLVED;
+                let depth = if solved { SOLVED } else { plies_left };
+                if let Some(value) = entry.cutoff(depth, alpha, beta, true) {
+                    if !solved {
+                        self.limited = true;
+                        self.reach_horizon();
+                    }
+                    return Some((value, entry.best_move));
+                }
+                hash_move = entry.best_move.or(hash_move);
+            }
+            if cut_key == key {
+                break;
+            }
+        }
+
+        let moves = self.ordering.order(state, hash_move, ply);
+        if moves.is_empty() && blocked(state) {
+            return Some((self.scoring.final_value(state, ply), None));
+        }
+        // track cut lines and repetitions in this subtree only
+        let limited_before = std::mem::replace(&mut self.limited, false);
+        let repeated_before = std::mem::replace(&mut self.repeated, NO_REPETITION);
+        let index = self.line.len();
+        self.line.push(key);
+        let original_alpha = alpha;
+        let mut best_value = -INFINITY;
+        let mut best_move = None;
+        if moves.is_empty() {
+            let (value, _) =
+                self.solve_rec(&state.skip_play(), -beta, -alpha, ply + 1, plies_left - 1)?;
+            best_value = -value;
+        }
+        for movement in moves {
+            let child_plies_left = match movement {
+                Movement::Duplicate(_) => self.jump_limit,
+                Movement::Jump(_, _) => plies_left - 1,
+            };
+            let (value, _) = self.solve_rec(
+                &state.play(&movement),
+                -beta,
+                -alpha,
+                ply + 1,
+                child_plies_left,
+            )?;
+            let value = -value;
+            if value > best_value {
+                best_value = value;
+                best_move = Some(movement);
+                if value > alpha {
+                    alpha = value;
+                    if value >= beta {
+                        let empty = state.empty_cells().count() as u8;
+                        self.ordering.record_cutoff(&movement, ply, empty + 1);
+                        self.counters.cutoffs += 1;
+                        break;
+                    }
+                }
+            }
+        }
+        self.line.pop();
+        if self.control.stopped() || self.nodes_left == 0 {
+            return None;
+        }
+        // repetitions of positions above this one depend on the line reaching it
+        if self.repeated >= index {
+            let (depth, stored_key) = if self.limited {
+                (plies_left, cut_key)
+            } else {
+                (SOLVED, key)
+            };
+            let mut entry = Entry::new(depth, best_value, original_alpha, beta, best_move);
+            entry.value = self.scoring.stored_value(best_value, ply);
+            self.table.store(stored_key, entry);
+        }
+        self.limited |= limited_before;
+        self.repeated = self.repeated.min(repeated_before);
+        Some((best_value, best_move))
+    }
+
+    /// Value of given configuration, found after given number of plies, where a line was cut.
+    fn cut_value(&mut self, state: &Configuration, ply: usize) -> Score {
+        match self.cut {
+            Cut::Evaluate => {
+                self.reach_horizon();
+                self.counters.evaluations += 1;
+                self.scoring.leaf_value(state, self.evaluator)
+            }
+            Cut::Favor(player) if player == state.current_player => {
+                self.scoring.best_value(self.cells, ply)
+            }
+            Cut::Favor(_) => -self.scoring.best_value(self.cells, ply),
+        }
+    }
+
+    /// Tell the control that values of cut lines were used. Bounds never make it into solved
+    /// values.
+    fn reach_horizon(&self) {
+        if self.cut == Cut::Evaluate {
+            self.control.reach_horizon();
+        }
+    }
+}
+
+/// Search until the end of the game, for positions with few empty cells.
+/// Search results are kept in a transposition table between calls. Threads can share a solver.
+pub struct Endgame {
+    threshold: usize,
+    /// Jump limit of searches evaluating cut lines, None to prove values.
+    jump_limit: Option<u8>,
+    proof_nodes: u64,
+    table: SharedTranspositionTable,
+    ordering: MoveOrdering,
+    nodes: AtomicU64,
+}
+
+impl Default for Endgame {
+    /// Solver for positions with at most `DEFAULT_THRESHOLD` empty cells.
+    fn default() -> Self {
+        Endgame::new(DEFAULT_THRESHOLD)
+    }
+}
+
+impl Endgame {
+    /// Solver for positions with at most `threshold` empty cells (0 disables it).
+    pub fn new(threshold: usize) -> Self {
+        Endgame {
+            threshold,
+            jump_limit: None,
+            proof_nodes: DEFAULT_PROOF_NODES,
+            // a disabled solver needs no table
+            table: SharedTranspositionTable::new(if threshold == 0 {
+                0
+            } else {
+                DEFAULT_TABLE_BITS
+            }),
+            ordering: Default::default(),
+            nodes: AtomicU64::new(0),
+        }
+    }
+
+    /// Give up on proving a value after searching given number of nodes : more nodes prove more
+    /// values but take longer to fail.
+    pub fn proof_nodes(self, nodes: u64) -> Self {
+        Endgame {
+            proof_nodes: nodes,
+            ..self
+        }
+    }
+
+    /// Evaluate lines after given number of plies in a row without a duplication (at least 1)
+    /// instead of proving values : solving always succeeds and is faster, but values of cut
+    /// lines are only evaluations.
+    pub fn jump_limit(self, plies: u8) -> Self {
+        let mut solver = Endgame {
+            jump_limit: Some(plies.clamp(1, SOLVED - 1)),
+            ..self
+        };
+        // values found under another limit are wrong
+        solver.table.clear();
+        solver
+    }
+
+    /// Maximum number of empty cells of solved positions.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Is given configuration close enough to the end of the game to be solved ?
+    pub fn applies(&self, state: &Configuration) -> bool {
+        self.threshold > 0 && state.empty_cells().nth(self.threshold).is_none()
+    }
+
+    /// Number of nodes visited since creation.
+    pub fn nodes(&self) -> u64 {
+        self.nodes.load(Ordering::Relaxed)
+    }
+
+    /// Prepare for a search from a new root (see `MoveOrdering::age`).
+    pub(crate) fn age(&self) {
+        if self.threshold > 0 {
+            self.ordering.age();
+        }
+    }
+
+    /// Negamax search of given configuration, found at given ply, in window `alpha..beta`,
+    /// cutting lines after given number of plies without a duplication. Nodes searched are taken
+    /// from given budget.
+    /// Also tell if lines were cut. Return None if the search was stopped or ran out of nodes.
+    #[allow(clippy::too_many_arguments)]
+    fn run(
+        &self,
+        state: &Configuration,
+        control: &SearchControl,
+        scoring: Scoring,
+        cut: Cut,
+        evaluator: &dyn Evaluator,
+        alpha: Score,
+        beta: Score,
+        ply: usize,
+        jump_limit: u8,
+        budget: &mut u64,
+    ) -> Option<(Score, Option<Movement>, bool)> {
+        let blobs = state.blobs(true).len() as usize + state.blobs(false).len() as usize;
+        let cells = blobs + state.empty_cells().count();
+        let mut search = Search {
+            table: &self.table,
+            ordering: &self.ordering,
+            control,
+            scoring,
+            cut,
+            evaluator,
+            counters: Default::default(),
+            jump_limit,
+            cells: cells as Score,
+            nodes_left: *budget,
+            limited: false,
+            line: Vec::new(),
+            repeated: NO_REPETITION,
+        };
+        let result = search.solve_rec(state, alpha, beta, ply, jump_limit);
+        *budget = search.nodes_left;
+        self.nodes
+            .fetch_add(search.counters.nodes, Ordering::Relaxed);
+        control.record(search.counters);
+        let (value, movement) = result?;
+        Some((value, movement, search.limited))
+    }
+
+    /// Value of given configuration, found at given ply, in window `alpha..beta`, and the move
+    /// reaching it. Also tell if lines were cut by the jump limit.
+    /// Without a jump limit, the value is proven by raising the limit of bounded searches until
+    /// they agree : Some(None) is returned if they still do not when the proof budget runs out.
+    /// Return None if the search was stopped before completion.
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    fn prove(
+        &self,
+        state: &Configuration,
+        control: &SearchControl,
+        scoring: Scoring,
+        evaluator: &dyn Evaluator,
+        alpha: Score,
+        beta: Score,
+        ply: usize,
+    ) -> Option<Option<(Score, Option<Movement>, bool)>> {
+        let run = |cut, jump_limit, budget: &mut u64| {
+            self.run(
+                state, control, scoring, cut, evaluator, alpha, beta, ply, jump_limit, budget,
+            )
+        };
+        if let Some(jump_limit) = self.jump_limit {
+            let mut unlimited = u64::MAX;
+            return run(Cut::Evaluate, jump_limit, &mut unlimited).map(Some);
+        }
+        let player = state.current_player;
+        let mut budget = self.proof_nodes;
+        // SOLVED marks table entries valid whatever the plies left
+        for jump_limit in 1..SOLVED {
+            let (lower, movement, limited) = match run(Cut::Favor(!player), jump_limit, &mut budget)
+            {
+                Some(bound) => bound,
+                None => break,
+            };
+            if !limited || lower >= beta {
+                return Some(Some((lower, movement, false)));
+            }
+            let (upper, upper_movement, _) = match run(Cut::Favor(player), jump_limit, &mut budget)
+            {
+                Some(bound) => bound,
+                None => break,
+            };
+            if upper <= alpha {
+                return Some(Some((upper, upper_movement, false)));
+            }
+            // the lower bound is reached by its move
+            if upper == lower {
+                return Some(Some((lower, movement, false)));
+            }
+        }
+        if control.stopped() {
+            None
+        } else {
+            Some(None)
+        }
+    }
+
+    /// Search the final margin of given configuration and the move reaching it. Counters are
+    /// recorded in the control.
+    /// Repetitions count as draws. The margin is only exact if no line was cut by the jump
+    /// limit (see `Solution::exact`).
+    /// Return None if the search was stopped before completion, or if the margin could not be
+    /// proven (see `Endgame::proof_nodes`).
+    pub fn solve(&self, state: &Configuration, control: &SearchControl) -> Option<Solution> {
+        self.age();
+        let (value, movement, limited) = self.prove(
+            state,
+            control,
+            Scoring::Margin,
+            &Material,
+            -INFINITY,
+            INFINITY,
+            0,
+        )??;
+        Some(Solution {
+            margin: value_margin(value),
+            plies: value_plies(value),
+            movement,
+            exact: !limited,
+        })
+    }
+
+    /// Solve given configuration, reached at given ply of an alpha - beta search, in window
+    /// `alpha..beta`. Values are seen from the player to move and higher is better ; wins and
+    /// losses are valued as games ended within the search (see `terminal_value`) and lines cut
+    /// by the jump limit with given evaluator.
+    /// A value lower or equal than alpha (resp. greater or equal than beta) is only an upper
+    /// (resp. lower) bound of the real value.
+    /// Return None if the search was stopped before completion and Some(None) if the value
+    /// could not be proven (see `Endgame::proof_nodes`) : the position should then be searched.
+    pub(crate) fn search(
+        &self,
+        state: &Configuration,
+        control: &SearchControl,
+        evaluator: &dyn Evaluator,
+        alpha: Score,
+        beta: Score,
+        ply: usize,
+    ) -> Option<Option<(Score, Option<Movement>)>> {
+        // alpha - beta searches open their windows with `Score::MIN`, which cannot be negated
+        let (alpha, beta) = (alpha.max(-INFINITY), beta.max(-INFINITY));
+        let solved = self.prove(state, control, Scoring::Winner, evaluator, alpha, beta, ply)?;
+        Some(solved.map(|(value, movement, _)| (value, movement)))
+    }
+}
+
+impl fmt::Display for Endgame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Endgame solver ({} empty cells)", self.threshold)
+    }
+}
@@ -0,0 +1,126 @@
+//! Leaf evaluation functions used by searches.
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use serde_derive::{Deserialize, Serialize};
+
+use super::search::{MATE, MAX_MATE_PLIES};
+use crate::configuration::Configuration;
+use crate::error::BlobwarError;
+use crate::positions::{Positions, MAX_CELLS};
+
+/// Values computed by evaluators and searches.
+pub type Score = i32;
+
+/// Evaluate configurations at the leaves of searches.
+/// Values follow the convention of `Configuration::value` : the higher, the better for the
+/// player who just played (the one *not* to move).
+pub trait Evaluator: Sync {
+    /// Evaluate given configuration.
+    fn evaluate(&self, configuration: &Configuration) -> Score;
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+/// Blob count difference.
+pub struct Material;
+
+impl Evaluator for Material {
+    fn evaluate(&self, configuration: &Configuration) -> Score {
+        configuration.value() as Score
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+/// Difference in number of possible movements.
+pub struct Mobility;
+
+impl Evaluator for Mobility {
+    fn evaluate(&self, configuration: &Configuration) -> Score {
+        let own = configuration.skip_play().movements().count() as Score;
+        let other = configuration.movements().count() as Score;
+        own - other
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+/// Difference in number of stable blobs.
+/// A blob is stable if no empty cell is next to it, so that it cannot be taken back right
+/// away.
+pub struct Frontier;
+
+impl Evaluator for Frontier {
+    fn evaluate(&self, configuration: &Configuration) -> Score {
+        let frontier = configuration
+            .empty_cells()
+            .fold(Default::default(), |frontier: Positions, cell| {
+                frontier.union_with(configuration.board().neighbours[cell as usize])
+            })
+            .invert();
+        let stable = |player: bool| {
+            configuration
+                .blobs(player)
+                .intersection_with(frontier)
+                .len() as Score
+        };
+        stable(!configuration.current_player) - stable(configuration.current_player)
+    }
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+/// Weighted sum of the other evaluators.
+pub struct Weighted {
+    /// Weight of `Material`.
+    pub material: Score,
+    /// Weight of `Mobility`.
+    pub mobility: Score,
+    /// Weight of `Frontier`.
+    pub frontier: Score,
+}
+
+impl Default for Weighted {
+    fn default() -> Self {
+        Weighted {
+            material: 4,
+            mobility: 1,
+            frontier: 2,
+        }
+    }
+}
+
+impl Weighted {
+    /// Load weights from given JSON file, for example
+    /// `{"material": 4, "mobility": 1, "frontier": 2}`.
+    /// Weights allowing evaluations to be mistaken for won or lost games are rejected.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, BlobwarError> {
+        let file = File::open(path)?;
+        let weights: Weighted = serde_json::from_reader(io::BufReader::new(file))?;
+        if weights.bound() >= i64::from(MATE - MAX_MATE_PLIES) {
+            return Err(BlobwarError::InvalidWeights(format!("{:?}", weights)));
+        }
+        Ok(weights)
+    }
+
+    /// Upper bound of absolute evaluations : at most one blob, one duplication and 16 jumps
+    /// per cell.
+    fn bound(&self) -> i64 {
+        let cells = MAX_CELLS as i64;
+        i64::from(self.material).abs() * cells
+            + i64::from(self.mobility).abs() * 17 * cells
+            + i64::from(self.frontier).abs() * cells
+    }
+}
+
+impl Evaluator for Weighted {
+    fn evaluate(&self, configuration: &Configuration) -> Score {
+        let mut score = self.material * Material.evaluate(configuration);
+        // skip costly evaluations when unused
+        if self.mobility != 0 {
+            score += self.mobility * Mobility.evaluate(configuration);
+        }
+        if self.frontier != 0 {
+            score += self.frontier * Frontier.evaluate(configuration);
+        }
+        score
+    }
+}
@@ -0,0 +1,198 @@
+//! Build any strategy from a textual spec such as "alphabeta:5", "pvs:5:endgame=8",
+//! "id:alphabeta:1500ms", "greedy", "human", "net:host:port" or
+//! "book:books/standard.json:alphabeta:5".
+use std::net::TcpStream;
+use std::time::Duration;
+
+use super::endgame::DEFAULT_THRESHOLD;
+use super::{
+    AlphaBeta, AlphaBetaPar, BookStrategy, Budget, Greedy, Human, IterativeDeepening,
+    IterativeStrategy, Mcts, MinMax, NetworkPlayer, Pvs, Rollout, Strategy,
+};
+use crate::book::OpeningBook;
+use crate::clock::MAX_DURATION;
+use crate::error::BlobwarError;
+
+/// Iterative deepening duration when none is given.
+const DEFAULT_DURATION: u64 = 1000;
+
+/// All available specs with their description.
+pub const STRATEGIES: &[(&str, &str)] = &[
+    ("greedy", "best immediate value"),
+    ("human", "moves entered on the terminal"),
+    ("minmax:DEPTH", "min - max search"),
+    (
+        "alphabeta:DEPTH[:endgame[=CELLS]]",
+        "alpha - beta search, solving positions with at most CELLS (2 by default, 0 disables it) empty cells until the end of the game",
+    ),
+    (
+        "alphabetapar:DEPTH[:endgame[=CELLS]]",
+        "parallel alpha - beta search, with the same endgame option",
+    ),
+    (
+        "pvs:DEPTH[:endgame[=CELLS]]",
+        "principal variation search, with the same endgame option",
+    ),
+    (
+        "id:ALGORITHM[:TIME][:log]",
+        "iterative deepening of minmax, alphabeta, alphabetapar or pvs for TIME (1000ms by default), logging search statistics with log",
+    ),
+    (
+        "mcts:BUDGET[:ROLLOUT]",
+        "monte carlo tree search for BUDGET playouts (or time, as in 500ms) with random or greedy rollouts",
+    ),
+    ("net:HOST:PORT", "remote player answering configurations"),
+    (
+        "book:FILE:SPEC",
+        "SPEC playing moves from the opening book FILE (see book-builder) first",
+    ),
+];
+
+/// Parse a duration such as "500ms" or "2s" into milliseconds, from 1 up to `MAX_DURATION`.
+pub fn parse_duration(duration: &str) -> Result<u64, BlobwarError> {
+    let invalid = || BlobwarError::InvalidSpec {
+        spec: duration.to_owned(),
+        reason: format!(
+            "durations look like 500ms or 2s and last from 1ms to {}s",
+            MAX_DURATION / 1000
+        ),
+    };
+    let milliseconds = if let Some(milliseconds) = duration.strip_suffix("ms") {
+        milliseconds.parse::<u64>().map_err(|_| invalid())?
+    } else if let Some(seconds) = duration.strip_suffix('s') {
+        let seconds = seconds.parse::<f64>().map_err(|_| invalid())?;
+        // also rejects NaN
+        if !(0.0..=(MAX_DURATION / 1000) as f64).contains(&seconds) {
+            return Err(invalid());
+        }
+        (seconds * 1000.0) as u64
+    } else {
+        return Err(invalid());
+    };
+    // searches of no time never find a move
+    if milliseconds == 0 || milliseconds > MAX_DURATION {
+        return Err(invalid());
+    }
+    Ok(milliseconds)
+}
+
+/// Build the strategy described by given spec (see `STRATEGIES`).
+/// "id-ALGORITHM:TIME" is accepted as a synonym of "id:ALGORITHM:TIME".
+pub fn from_spec(spec: &str) -> Result<Box<dyn Strategy>, BlobwarError> {
+    let invalid = |reason: &str| BlobwarError::InvalidSpec {
+        spec: spec.to_owned(),
+        reason: reason.to_owned(),
+    };
+    let mut parts = spec.splitn(2, ':');
+    let name = parts.next().unwrap_or_default();
+    let argument = parts.next();
+    // searches of depth 0 never find a move
+    let depth = |depth: Option<&str>| -> Result<u8, BlobwarError> {
+        depth
+            .ok_or_else(|| invalid("missing depth"))?
+            .parse()
+            .ok()
+            .filter(|&depth| depth > 0)
+            .ok_or_else(|| invalid("depth should be between 1 and 255"))
+    };
+    // alpha - beta searches take a depth and the endgame solver threshold
+    let search = || -> Result<(u8, usize), BlobwarError> {
+        let mut parts = argument.unwrap_or_default().splitn(2, ':');
+        let search_depth = depth(parts.next().filter(|depth| !depth.is_empty()))?;
+        let threshold = match parts.next() {
+            None | Some("endgame") => DEFAULT_THRESHOLD,
+            Some(option) => option
+                .strip_prefix("endgame=")
+                .and_then(|cells| cells.parse().ok())
+                .ok_or_else(|| invalid("the search option is endgame or endgame=CELLS"))?,
+        };
+        Ok((search_depth, threshold))
+    };
+    let iterative = |algorithm: &str, options: Option<&str>| {
+        let strategy =
+            match algorithm {
+                "minmax" => IterativeStrategy::MinMax,
+                "alphabeta" => IterativeStrategy::AlphaBeta,
+                "alphabetapar" => IterativeStrategy::AlphaBetaPar,
+                "pvs" => IterativeStrategy::Pvs,
+                _ => return Err(invalid(
+                    "iterative deepening algorithms are minmax, alphabeta, alphabetapar and pvs",
+                )),
+            };
+        let mut duration = DEFAULT_DURATION;
+        let mut log = false;
+        for option in options.into_iter().flat_map(|options| options.split(':')) {
+            if option == "log" {
+                log = true;
+            } else {
+                duration = parse_duration(option)?;
+            }
+        }
+        Ok(Box::new(
+            IterativeDeepening::new(strategy)
+                .duration(duration)
+                .log(log),
+        ) as Box<dyn Strategy>)
+    };
+    match name {
+        "greedy" => Ok(Box::new(Greedy())),
+        "human" => Ok(Box::new(Human())),
+        "minmax" => Ok(Box::new(MinMax::new(depth(argument)?))),
+        "alphabeta" => {
+            let (depth, threshold) = search()?;
+            Ok(Box::new(AlphaBeta::new(depth).endgame(threshold)))
+        }
+        "alphabetapar" => {
+            let (depth, threshold) = search()?;
+            Ok(Box::new(AlphaBetaPar::new(depth).endgame(threshold)))
+        }
+        "pvs" => {
+            let (depth, threshold) = search()?;
+            Ok(Box::new(Pvs::new(depth).endgame(threshold)))
+        }
+        "id" => {
+            let mut parts = argument
+                .ok_or_else(|| invalid("missing algorithm"))?
+                .splitn(2, ':');
+            iterative(parts.next().unwrap_or_default(), parts.next())
+        }
+        "mcts" => {
+            let mut parts = argument
+                .ok_or_else(|| invalid("missing budget"))?
+                .splitn(2, ':');
+            let budget = parts.next().unwrap_or_default();
+            let budget = match budget.parse() {
+                Ok(0) => return Err(invalid("budget must be positive")),
+                Ok(playouts) => Budget::Playouts(playouts),
+                Err(_) => Budget::Time(Duration::from_millis(parse_duration(budget)?)),
+            };
+            let rollout = match parts.next() {
+                None | Some("random") => Rollout::Random,
+                Some("greedy") => Rollout::Greedy,
+                Some(_) => return Err(invalid("rollouts are random or greedy")),
+            };
+            Ok(Box::new(Mcts::new(budget).rollout(rollout)))
+        }
+        "book" => {
+            let mut parts = argument
+                .ok_or_else(|| invalid("missing book file"))?
+                .splitn(2, ':');
+            let book = OpeningBook::load(parts.next().unwrap_or_default())?;
+            let strategy = from_spec(parts.next().ok_or_else(|| invalid("missing strategy"))?)?;
+            Ok(Box::new(BookStrategy::new(book, strategy)))
+        }
+        "net" => {
+            let address = argument.ok_or_else(|| invalid("missing address"))?;
+            let stream = TcpStream::connect(address)?;
+            stream.set_nodelay(true)?;
+            Ok(Box::new(NetworkPlayer::new(stream)?))
+        }
+        _ => match name.strip_prefix("id-") {
+            Some(algorithm) => iterative(algorithm, argument),
+            None => Err(BlobwarError::UnknownStrategy {
+                name: name.to_owned(),
+                available: STRATEGIES,
+            }),
+        },
+    }
+}
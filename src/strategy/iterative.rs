@@ -1,15 +1,127 @@
 //! Generic iterative deepening strategies (with variable algorithms).
 use std::fmt;
 
+use std::env;
 use std::io;
+use std::ops::AddAssign;
 use std::process::Command;
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::thread;
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use super::Strategy;
+use super::evaluator::Score;
+use super::{AlphaBeta, AlphaBetaPar, MinMax, Outcome, Pvs, SearchControl, SearchStats, Strategy};
+use crate::clock::{Clock, MAX_DURATION};
 use crate::configuration::{Configuration, Movement};
 use crate::shmem::AtomicMove;
 
+/// Deepest iteration we will ever try.
+pub const MAX_DEPTH: u8 = 100;
+
+/// Initial half width of aspiration windows.
+const ASPIRATION_WINDOW: Score = 3;
+
+/// Factor by which the failing side of an aspiration window widens the first time. It opens
+/// completely the second time.
+const ASPIRATION_WIDENING: Score = 4;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+/// How aspiration windows fared.
+pub struct AspirationStats {
+    /// Number of searches started with a narrow window.
+    pub searches: u64,
+    /// Number of re-searches because the real value was below the window.
+    pub fail_lows: u64,
+    /// Number of re-searches because the real value was above the window.
+    pub fail_highs: u64,
+}
+
+impl AspirationStats {
+    /// Total number of re-searches.
+    pub fn re_searches(&self) -> u64 {
+        self.fail_lows + self.fail_highs
+    }
+}
+
+impl AddAssign for AspirationStats {
+    fn add_assign(&mut self, other: Self) {
+        self.searches += other.searches;
+        self.fail_lows += other.fail_lows;
+        self.fail_highs += other.fail_highs;
+    }
+}
+
+impl fmt::Display for AspirationStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} aspiration searches, {} re-searches ({} fail lows, {} fail highs)",
+            self.searches,
+            self.re_searches(),
+            self.fail_lows,
+            self.fail_highs
+        )
+    }
+}
+
+/// Search in a narrow window around the value of the previous iteration (full window if
+/// there is none), widening the failing side of the window until the value falls inside : once,
+/// then up to `Score::MIN` or `Score::MAX` so that far values (as wins) cost few re-searches.
+/// `search` takes the window bounds and follows `AlphaBeta::search_window` conventions.
+pub fn aspiration<F>(
+    previous: Option<Score>,
+    stats: &mut AspirationStats,
+    mut search: F,
+) -> Option<(Score, Option<Movement>)>
+where
+    F: FnMut(Score, Score) -> Option<(Score, Option<Movement>)>,
+{
+    let previous = match previous {
+        Some(previous) => previous,
+        None => return search(Score::MIN, Score::MAX),
+    };
+    stats.searches += 1;
+    // half widths of both sides, None once open
+    let (mut low, mut high) = (Some(ASPIRATION_WINDOW), Some(ASPIRATION_WINDOW));
+    let widen = |width: Option<Score>| {
+        width
+            .filter(|&width| width == ASPIRATION_WINDOW)
+            .map(|width| width * ASPIRATION_WIDENING)
+    };
+    loop {
+        let alpha = low.map_or(Score::MIN, |low| previous.saturating_sub(low));
+        let beta = high.map_or(Score::MAX, |high| previous.saturating_add(high));
+        let (value, movement) = search(alpha, beta)?;
+        if value <= alpha && alpha != Score::MIN {
+            stats.fail_lows += 1;
+            low = widen(low);
+        } else if value >= beta && beta != Score::MAX {
+            stats.fail_highs += 1;
+            high = widen(high);
+        } else {
+            return Some((value, movement));
+        }
+    }
+}
+
+/// Turn a windowed search at given depth into a deepening step using aspiration windows
+/// around the value of the previous step.
+fn with_aspiration<'f, F>(
+    mut search: F,
+    stats: &'f mut AspirationStats,
+) -> impl FnMut(u8) -> Option<(Score, Option<Movement>)> + 'f
+where
+    F: FnMut(u8, Score, Score) -> Option<(Score, Option<Movement>)> + 'f,
+{
+    let mut previous = None;
+    move |depth| {
+        let result = aspiration(previous, stats, |alpha, beta| search(depth, alpha, beta));
+        previous = result.map(|(value, _)| value);
+        result
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 /// All possible strategies for iterative deepening.
 pub enum IterativeStrategy {
@@ -17,59 +129,345 @@ pub enum IterativeStrategy {
     MinMax,
     /// AlphaBeta algorithm
     AlphaBeta,
+    /// Parallel AlphaBeta algorithm
+    AlphaBetaPar,
+    /// Principal variation search
+    Pvs,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Where anytime searches run.
+pub enum SearchMode {
+    /// In a worker thread, stopped cooperatively at the deadline.
+    Thread,
+    /// In the `blobwar_iterative_deepening` helper process, killed at the deadline.
+    Process,
 }
 
-/// Anytime algorithms strategies. Implemented in another process.
+/// Anytime algorithms strategies.
+/// Run in a worker thread by default, or in another process.
+/// Statistics of each search can be logged on the standard error.
+/// In timed matches the thinking time is budgeted from the clock instead of the fixed duration.
 pub struct IterativeDeepening {
     strategy: IterativeStrategy,
     duration: u64,
+    clock_budget: Option<Duration>,
+    mode: SearchMode,
+    log: bool,
+    aspiration: AspirationStats,
+    stats: SearchStats,
 }
 
 impl fmt::Display for IterativeDeepening {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "{:?} (iterative deepening {}ms)",
-            self.strategy, self.duration
-        )
+        match self.clock_budget {
+            Some(budget) => write!(
+                f,
+                "{:?} (iterative deepening {}ms from the clock)",
+                self.strategy,
+                budget.as_millis()
+            ),
+            None => write!(
+                f,
+                "{:?} (iterative deepening {}ms)",
+                self.strategy, self.duration
+            ),
+        }
     }
 }
 
 impl Strategy for IterativeDeepening {
     fn compute_next_move(&mut self, state: &Configuration) -> Option<Movement> {
+        let movement = match self.mode {
+            SearchMode::Thread => {
+                let start = Instant::now();
+                // thinking times too long for the clock never end
+                let deadline = start
+                    .checked_add(self.thinking_time())
+                    .unwrap_or_else(|| start + Duration::from_millis(MAX_DURATION));
+                let (movement, aspiration, stats) =
+                    search_in_thread(self.strategy, state, deadline);
+                self.aspiration += aspiration;
+                self.stats = stats;
+                movement
+            }
+            SearchMode::Process => self.compute_in_process(state),
+        };
+        if self.log {
+            eprintln!(
+                "{} : {}, pv {}",
+                self,
+                self.stats,
+                self.stats.principal_variation(state.board())
+            );
+        }
+        movement
+    }
+
+    fn search_stats(&self) -> Option<SearchStats> {
+        Some(self.stats.clone())
+    }
+
+    fn set_clock(&mut self, clock: Clock) {
+        self.clock_budget = clock.budget();
+    }
+}
+
+impl IterativeDeepening {
+    /// New iterative deepening strategy with given algorithm.
+    /// default time is 1 second.
+    pub fn new(strategy: IterativeStrategy) -> IterativeDeepening {
+        IterativeDeepening {
+            strategy,
+            duration: 1000,
+            clock_budget: None,
+            mode: SearchMode::Thread,
+            log: false,
+            aspiration: Default::default(),
+            stats: Default::default(),
+        }
+    }
+
+    /// Aspiration windows statistics of all in-thread searches so far.
+    pub fn aspiration_stats(&self) -> AspirationStats {
+        self.aspiration
+    }
+
+    /// Sets duration in milliseconds on given algorithm.
+    pub fn duration(self, duration: u64) -> Self {
+        IterativeDeepening { duration, ..self }
+    }
+
+    /// Time to spend on the next move.
+    fn thinking_time(&self) -> Duration {
+        self.clock_budget
+            .unwrap_or_else(|| Duration::from_millis(self.duration))
+    }
+
+    /// Sets where the search runs.
+    pub fn mode(self, mode: SearchMode) -> Self {
+        IterativeDeepening { mode, ..self }
+    }
+
+    /// Print the statistics of each search on the standard error.
+    pub fn log(self, log: bool) -> Self {
+        IterativeDeepening { log, ..self }
+    }
+
+    /// Run the search in the helper process (which should be next to our executable) and
+    /// kill it when time is over.
+    fn compute_in_process(&mut self, state: &Configuration) -> Option<Movement> {
         let movement = AtomicMove::new().expect("failed creating shmem");
         let configuration = state.serialize();
-        let mut child = Command::new("blobwar_iterative_deepening")
-            .current_dir("./target/release/")
+        let directory = env::current_exe()
+            .expect("failed finding current executable")
+            .with_file_name("");
+        let mut child = Command::new(directory.join("blobwar_iterative_deepening"))
             .arg(configuration)
             .arg((self.strategy as usize).to_string())
             .spawn()
             .expect("failed to start child process");
-        sleep(Duration::from_millis(self.duration));
+        sleep(self.thinking_time());
         if let Err(e) = child.kill() {
             if e.kind() != io::ErrorKind::InvalidInput {
                 panic!("failed to kill child")
             }
         }
+        child.wait().expect("failed to wait for child");
+        self.stats = movement.load_stats();
         movement.load()
     }
 }
 
-impl IterativeDeepening {
-    /// New iterative deepening strategy with given algorithm.
-    /// default time is 2 seconds.
-    pub fn new(strategy: IterativeStrategy) -> IterativeDeepening {
-        IterativeDeepening {
-            strategy,
-            duration: 1000,
+#[derive(Clone, Debug)]
+/// Completed iteration of a deepening search.
+pub struct Iteration {
+    /// Depth of the iteration.
+    pub depth: u8,
+    /// Value found, seen from the player to move (lower is better, see `Outcome`).
+    pub value: Score,
+    /// Best move, None to pass.
+    pub movement: Option<Movement>,
+    /// Statistics of the search so far.
+    pub stats: SearchStats,
+}
+
+/// Search with increasing depths up to `max_depth`, reporting each completed iteration.
+/// Stop when there is no use searching deeper, when control says so or when `report` returns
+/// false.
+fn deepen<F, R>(mut search: F, control: &SearchControl, max_depth: u8, mut report: R)
+where
+    F: FnMut(u8) -> Option<(Score, Option<Movement>)>,
+    R: FnMut(Iteration) -> bool,
+{
+    let mut iterations = Vec::new();
+    for depth in 1..=max_depth {
+        control.new_iteration();
+        let start = Instant::now();
+        match search(depth) {
+            Some((value, movement)) => {
+                iterations.push(start.elapsed());
+                let iteration = Iteration {
+                    depth,
+                    value,
+                    movement,
+                    stats: control.stats(depth, iterations.clone()),
+                };
+                if !report(iteration) {
+                    return;
+                }
+                // no leaf was cut by the depth limit : the whole game tree was explored
+                if movement.is_none() || !control.horizon_reached() {
+                    return;
+                }
+                // a faster win would have been found by a previous iteration
+                if let Outcome::Win(_) = Outcome::of(value) {
+                    return;
+                }
+            }
+            None => return,
         }
     }
+}
 
-    /// Sets duration in milliseconds on given algorithm.
-    pub fn duration(&self, duration: u64) -> Self {
-        IterativeDeepening {
-            strategy: self.strategy,
-            duration,
+/// Run given algorithm with increasing depths up to `max_depth` under given control, calling
+/// `report` after each completed iteration. It may return false to stop the search.
+/// The search also stops when deeper searches cannot change the result or when control says
+/// so. Alpha - beta based algorithms search each iteration in a window around the value of
+/// the previous one, updating `aspiration`.
+pub fn deepening<R>(
+    strategy: IterativeStrategy,
+    state: &Configuration,
+    control: &SearchControl,
+    max_depth: u8,
+    aspiration: &mut AspirationStats,
+    report: R,
+) where
+    R: FnMut(Iteration) -> bool,
+{
+    match strategy {
+        IterativeStrategy::MinMax => deepen(
+            |depth| MinMax::new(depth).search(state, control),
+            control,
+            max_depth,
+            report,
+        ),
+        IterativeStrategy::AlphaBeta => {
+            let mut alpha_beta = AlphaBeta::new(1);
+            let search = |depth, alpha, beta| {
+                alpha_beta.depth = depth;
+                alpha_beta.search_window(state, control, alpha, beta)
+            };
+            deepen(
+                with_aspiration(search, aspiration),
+                control,
+                max_depth,
+                report,
+            )
+        }
+        IterativeStrategy::AlphaBetaPar => {
+            let mut alpha_beta = AlphaBetaPar::new(1);
+            let search = |depth, alpha, beta| {
+                alpha_beta.depth = depth;
+                alpha_beta.search_window(state, control, alpha, beta)
+            };
+            deepen(
+                with_aspiration(search, aspiration),
+                control,
+                max_depth,
+                report,
+            )
+        }
+        IterativeStrategy::Pvs => {
+            let mut pvs = Pvs::new(1);
+            let search = |depth, alpha, beta| {
+                pvs.depth = depth;
+                pvs.search_window(state, control, alpha, beta)
+            };
+            deepen(
+                with_aspiration(search, aspiration),
+                control,
+                max_depth,
+                report,
+            )
+        }
+    }
+}
+
+/// In-process anytime search.
+/// Run given algorithm with increasing depth on a worker thread until the deadline and return
+/// the best move of the deepest completed iteration.
+/// Return earlier if deeper searches cannot change the result.
+pub fn iterative_deepening(
+    strategy: IterativeStrategy,
+    state: &Configuration,
+    deadline: Instant,
+) -> Option<Movement> {
+    iterative_deepening_with_stats(strategy, state, deadline).0
+}
+
+/// Same as `iterative_deepening`, also returning how aspiration windows fared.
+/// Alpha - beta based algorithms search each iteration in a window around the value of the
+/// previous one.
+pub fn iterative_deepening_with_stats(
+    strategy: IterativeStrategy,
+    state: &Configuration,
+    deadline: Instant,
+) -> (Option<Movement>, AspirationStats) {
+    let (movement, aspiration, _) = search_in_thread(strategy, state, deadline);
+    (movement, aspiration)
+}
+
+/// Iterative deepening on a worker thread, returning the move with aspiration windows and
+/// search statistics.
+/// Counters include the unfinished iteration while the depth, iteration times and principal
+/// variation are the ones of the deepest completed iteration.
+fn search_in_thread(
+    strategy: IterativeStrategy,
+    state: &Configuration,
+    deadline: Instant,
+) -> (Option<Movement>, AspirationStats, SearchStats) {
+    let control = SearchControl::default();
+    let (sender, receiver) = channel();
+    let mut best = None;
+    let mut stats = AspirationStats::default();
+    thread::scope(|scope| {
+        let control = &control;
+        let stats = &mut stats;
+        let worker = scope.spawn(move || {
+            deepening(strategy, state, control, MAX_DEPTH, stats, |iteration| {
+                sender.send(iteration).is_ok()
+            })
+        });
+        loop {
+            match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(iteration) => best = Some(iteration),
+                Err(RecvTimeoutError::Timeout) => {
+                    control.stop();
+                    break;
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        worker.join().expect("search thread panicked");
+    });
+    // an iteration might have completed while we were stopping
+    if let Some(iteration) = receiver.try_iter().last() {
+        best = Some(iteration);
+    }
+    match best {
+        Some(iteration) => {
+            let search_stats = SearchStats {
+                counters: control.counters(),
+                ..iteration.stats
+            };
+            (iteration.movement, stats, search_stats)
         }
+        // if not even the first iteration completed, play anything
+        None => (
+            state.movements().next(),
+            stats,
+            control.stats(0, Vec::new()),
+        ),
     }
 }
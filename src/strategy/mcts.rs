@@ -0,0 +1,381 @@
+//! Monte Carlo tree search.
+//! The tree is grown one node per playout, descending with the UCT formula and finishing games
+//! with random (or greedy-biased) rollouts. Several independent trees can be searched in
+//! parallel (root parallelization) ; their root statistics are merged to choose the move.
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+
+use super::Strategy;
+use crate::clock::Clock;
+use crate::configuration::{Configuration, Movement};
+use crate::zobrist::split_mix;
+
+/// Default exploration constant of the UCT formula.
+const DEFAULT_EXPLORATION: f64 = 1.4;
+
+/// Default number of trees. It does not depend on the number of threads so that seeded
+/// searches play the same moves on all machines.
+pub const DEFAULT_TREES: usize = 4;
+
+/// Probability of playing a random move instead of the greedy one in greedy rollouts.
+const GREEDY_RANDOMNESS: f64 = 0.2;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// When to stop searching.
+pub enum Budget {
+    /// Total number of playouts (shared between trees).
+    Playouts(u64),
+    /// Thinking time.
+    Time(Duration),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// How games are finished from the leaves of the tree.
+pub enum Rollout {
+    /// Uniformly random movements.
+    Random,
+    /// Movements converting the most blobs (duplicates first), sometimes random ones.
+    Greedy,
+}
+
+/// Visits and wins of each root movement.
+type RootStatistics = Vec<(Option<Movement>, u32, f64)>;
+
+/// SplitMix64 random numbers.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        let (state, number) = split_mix(self.0);
+        self.0 = state;
+        number
+    }
+
+    /// Random index below given bound.
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    /// Random number in 0..1.
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Legal movements of given configuration.
+fn legal_movements(state: &Configuration) -> Vec<Movement> {
+    state
+        .movements()
+        .filter(|movement| state.check_move(movement))
+        .collect()
+}
+
+/// Tree node, reached by playing `movement` (None for a pass).
+struct Node {
+    movement: Option<Movement>,
+    children: Vec<usize>,
+    /// Movements not expanded yet.
+    untried: Vec<Option<Movement>>,
+    visits: u32,
+    /// Sum of the rewards of the player who played `movement`.
+    wins: f64,
+}
+
+impl Node {
+    fn new(state: &Configuration, movement: Option<Movement>, passed: bool) -> Self {
+        let untried = if state.game_over() {
+            Vec::new()
+        } else {
+            let movements = legal_movements(state);
+            if movements.is_empty() {
+                // two passes in a row end the game
+                if passed {
+                    Vec::new()
+                } else {
+                    vec![None]
+                }
+            } else {
+                movements.into_iter().map(Some).collect()
+            }
+        };
+        Node {
+            movement,
+            children: Vec::new(),
+            untried,
+            visits: 0,
+            wins: 0.0,
+        }
+    }
+}
+
+/// One search tree.
+struct Tree<'c, 'a> {
+    root: &'c Configuration<'a>,
+    nodes: Vec<Node>,
+    rng: Rng,
+    rollout: Rollout,
+    exploration: f64,
+}
+
+impl<'c, 'a> Tree<'c, 'a> {
+    fn new(root: &'c Configuration<'a>, seed: u64, rollout: Rollout, exploration: f64) -> Self {
+        Tree {
+            root,
+            nodes: vec![Node::new(root, None, false)],
+            rng: Rng(seed),
+            rollout,
+            exploration,
+        }
+    }
+
+    /// Child of given node with the best UCT value.
+    fn select(&self, node: usize) -> usize {
+        let log_visits = (self.nodes[node].visits as f64).ln();
+        let uct = |child: usize| {
+            let child = &self.nodes[child];
+            child.wins / child.visits as f64
+                + self.exploration * (log_visits / child.visits as f64).sqrt()
+        };
+        *self.nodes[node]
+            .children
+            .iter()
+            .max_by(|&&a, &&b| uct(a).partial_cmp(&uct(b)).unwrap())
+            .unwrap()
+    }
+
+    /// Play given movement (or pass).
+    fn play(state: &Configuration<'a>, movement: Option<Movement>) -> Configuration<'a> {
+        match movement {
+            Some(movement) => state.play(&movement),
+            None => state.skip_play(),
+        }
+    }
+
+    /// Movement chosen by the rollout policy amongst given legal movements.
+    fn rollout_movement(&mut self, state: &Configuration, movements: &[Movement]) -> Movement {
+        if self.rollout == Rollout::Greedy && self.rng.unit() >= GREEDY_RANDOMNESS {
+            let start = self.rng.below(movements.len());
+            // start at a random index so that ties are broken randomly
+            *movements[start..]
+                .iter()
+                .chain(&movements[..start])
+                .max_by_key(|movement| {
+                    (
+                        state.captures(movement),
+                        matches!(movement, Movement::Duplicate(_)),
+                    )
+                })
+                .unwrap()
+        } else {
+            movements[self.rng.below(movements.len())]
+        }
+    }
+
+    /// Finish the game randomly and return red's margin.
+    fn simulate(&mut self, mut state: Configuration<'a>, mut passed: bool) -> i16 {
+        // jumps alone could go on forever
+        let mut remaining_plies = 4 * state.board().size();
+        while !state.game_over() && remaining_plies > 0 {
+            let movements = legal_movements(&state);
+            if movements.is_empty() {
+                if passed {
+                    break;
+                }
+                passed = true;
+                state = state.skip_play();
+            } else {
+                passed = false;
+                let movement = self.rollout_movement(&state, &movements);
+                state.apply_movement(&movement);
+            }
+            remaining_plies -= 1;
+        }
+        state.red_margin()
+    }
+
+    /// Run one playout : selection, expansion, simulation and backpropagation.
+    fn playout(&mut self) {
+        let mut state = *self.root;
+        let mut path = vec![0];
+        let mut node = 0;
+        let mut passed = false;
+        while self.nodes[node].untried.is_empty() && !self.nodes[node].children.is_empty() {
+            node = self.select(node);
+            let movement = self.nodes[node].movement;
+            passed = movement.is_none();
+            state = Tree::play(&state, movement);
+            path.push(node);
+        }
+        if !self.nodes[node].untried.is_empty() {
+            let untried = &mut self.nodes[node].untried;
+            let movement = untried.swap_remove(self.rng.below(untried.len()));
+            passed = movement.is_none();
+            state = Tree::play(&state, movement);
+            let child = self.nodes.len();
+            self.nodes.push(Node::new(&state, movement, passed));
+            self.nodes[node].children.push(child);
+            path.push(child);
+        }
+        let margin = self.simulate(state, passed);
+
+        // rewards of the player who moved into each node : walk back the path, players
+        // alternate (a pass is a move)
+        let mut player = !state.current_player;
+        for &node in path.iter().rev() {
+            let player_margin = if player { -margin } else { margin };
+            let node = &mut self.nodes[node];
+            node.visits += 1;
+            node.wins += 0.5 + 0.5 * player_margin.signum() as f64;
+            player = !player;
+        }
+    }
+
+    fn root_statistics(&self) -> RootStatistics {
+        self.nodes[0]
+            .children
+            .iter()
+            .map(|&child| {
+                let child = &self.nodes[child];
+                (child.movement, child.visits, child.wins)
+            })
+            .collect()
+    }
+}
+
+/// Monte Carlo tree search with UCT selection.
+pub struct Mcts {
+    budget: Budget,
+    clock_budget: Option<Duration>,
+    rollout: Rollout,
+    exploration: f64,
+    trees: usize,
+    seed: u64,
+    playouts: u64,
+}
+
+impl Mcts {
+    /// Search with given budget, random rollouts and `DEFAULT_TREES` trees.
+    pub fn new(budget: Budget) -> Self {
+        Mcts {
+            budget,
+            clock_budget: None,
+            rollout: Rollout::Random,
+            exploration: DEFAULT_EXPLORATION,
+            trees: DEFAULT_TREES,
+            seed: 0,
+            playouts: 0,
+        }
+    }
+
+    /// Finish games with given rollout policy.
+    pub fn rollout(self, rollout: Rollout) -> Self {
+        Mcts { rollout, ..self }
+    }
+
+    /// Use given exploration constant in the UCT formula.
+    pub fn exploration(self, exploration: f64) -> Self {
+        Mcts {
+            exploration,
+            ..self
+        }
+    }
+
+    /// Search given number of independent trees in parallel (on as many rayon threads as
+    /// available).
+    pub fn trees(self, trees: usize) -> Self {
+        Mcts {
+            trees: trees.max(1),
+            ..self
+        }
+    }
+
+    /// Seed random numbers (searches are reproducible with a playouts budget).
+    pub fn seed(self, seed: u64) -> Self {
+        Mcts { seed, ..self }
+    }
+
+    /// Number of playouts since creation.
+    pub fn playouts(&self) -> u64 {
+        self.playouts
+    }
+}
+
+impl fmt::Display for Mcts {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.budget {
+            Budget::Playouts(playouts) => write!(f, "MCTS ({} playouts", playouts)?,
+            Budget::Time(duration) => write!(f, "MCTS ({}ms", duration.as_millis())?,
+        }
+        write!(f, ", {:?} rollouts)", self.rollout)
+    }
+}
+
+impl Strategy for Mcts {
+    fn compute_next_move(&mut self, state: &Configuration) -> Option<Movement> {
+        let start = Instant::now();
+        // time budgets follow the clock in timed matches
+        let budget = match (self.budget, self.clock_budget) {
+            (Budget::Time(_), Some(time)) => Budget::Time(time),
+            (budget, _) => budget,
+        };
+        // each tree plays at least one playout : small budgets use fewer trees
+        let trees = match budget {
+            Budget::Playouts(total) => (self.trees as u64).min(total).max(1),
+            Budget::Time(_) => self.trees as u64,
+        };
+        let (rollout, exploration) = (self.rollout, self.exploration);
+        let seed = self.seed ^ state.zobrist_hash();
+        let results: Vec<(u64, RootStatistics)> = (0..trees)
+            .into_par_iter()
+            .map(|index| {
+                let mut tree = Tree::new(state, split_mix(seed ^ index).1, rollout, exploration);
+                let mut playouts = 0;
+                loop {
+                    let over = match budget {
+                        // spread the remainder on the first trees
+                        Budget::Playouts(total) => {
+                            playouts >= total / trees + (index < total % trees) as u64
+                        }
+                        Budget::Time(duration) => start.elapsed() >= duration,
+                    };
+                    // we need at least one playout to know the root movements
+                    if over && playouts > 0 {
+                        break;
+                    }
+                    tree.playout();
+                    playouts += 1;
+                }
+                (playouts, tree.root_statistics())
+            })
+            .collect();
+
+        let mut merged = RootStatistics::new();
+        for (playouts, statistics) in results {
+            self.playouts += playouts;
+            for (movement, visits, wins) in statistics {
+                match merged.iter_mut().find(|(m, _, _)| *m == movement) {
+                    Some((_, total_visits, total_wins)) => {
+                        *total_visits += visits;
+                        *total_wins += wins;
+                    }
+                    None => merged.push((movement, visits, wins)),
+                }
+            }
+        }
+        // most visited movement, best win rate on ties
+        merged
+            .into_iter()
+            .max_by(|(_, visits_a, wins_a), (_, visits_b, wins_b)| {
+                (visits_a, wins_a / *visits_a as f64)
+                    .partial_cmp(&(visits_b, wins_b / *visits_b as f64))
+                    .unwrap()
+            })
+            .and_then(|(movement, _, _)| movement)
+    }
+
+    fn set_clock(&mut self, clock: Clock) {
+        self.clock_budget = clock.budget();
+    }
+}
@@ -1,93 +1,202 @@
 //! Implementation of the min max algorithm.
 use rayon::prelude::{ParallelBridge, ParallelIterator};
 
-use super::Strategy;
+use super::evaluator::{Evaluator, Material, Score};
+use super::search::{blocked, terminal_value, Counters, SearchStats};
+use super::{SearchControl, Strategy};
 use crate::configuration::{Configuration, Movement};
 use crate::shmem::AtomicMove;
 use std::fmt;
+use std::time::Instant;
 
 /// Min-Max algorithm with a given recursion depth.
-pub struct MinMax(pub u8);
-
-/// .
-///
-/// # Panics
-///
-/// Panics if .
-pub fn min_max_rec(
+/// Leaves are evaluated with `E` (blob count by default).
+pub struct MinMax<E = Material> {
+    /// Maximum number of recursions.
+    pub depth: u8,
+    evaluator: E,
+    stats: SearchStats,
+}
+
+/// Value of a searched position with the move reaching it.
+type Node = (Score, Option<Movement>);
+
+/// Best of given (value, move) pairs for the player to move : values are seen from the player
+/// we search a move for, who minimizes them.
+fn best<I: Iterator<Item = Node>>(minimize: bool, nodes: I) -> Option<Node> {
+    if minimize {
+        nodes.min_by_key(|a: &Node| a.0)
+    } else {
+        nodes.max_by_key(|a: &Node| a.0)
+    }
+}
+
+/// Min-Max search of given depth, values are seen from given player (lower is better).
+/// `ply` is the number of plies played since the root of the search.
+/// Visited nodes and evaluations are added to given counters.
+/// Return None if the search was stopped by given control.
+pub fn min_max_rec<E: Evaluator>(
     player: bool,
     state: &Configuration,
     depth: u8,
-) -> Option<(i8, Option<Movement>)> {
+    ply: usize,
+    control: &SearchControl,
+    evaluator: &E,
+    counters: &mut Counters,
+) -> Option<(Score, Option<Movement>)> {
+    if control.stopped() {
+        return None;
+    }
+    counters.nodes += 1;
+    let sign = if state.current_player == player {
+        1
+    } else {
+        -1
+    };
+    if state.game_over() {
+        return Some((terminal_value(state, ply) * sign, None));
+    }
     if depth == 0 {
-        return Some((
-            state.value()
-                * if state.current_player == player {
-                    1
-                } else {
-                    -1
-                },
-            None,
-        ));
+        control.reach_horizon();
+        counters.evaluations += 1;
+        return Some((evaluator.evaluate(state) * sign, None));
     }
 
     let ok_moves = state.movements().filter(|mov| state.check_move(mov));
 
     let mut check_moves_size = ok_moves.peekable();
 
-    // If no move is doable, return the value
-    if check_moves_size.peek() == None {
-        return Some((
-            state.value()
-                * if state.current_player == player {
-                    1
-                } else {
-                    -1
-                },
-            None,
-        ));
+    // If no move is doable, pass (the game ends if no one can move)
+    if check_moves_size.peek().is_none() {
+        if blocked(state) {
+            return Some((terminal_value(state, ply) * sign, None));
+        }
+        let (value, _) = min_max_rec(
+            player,
+            &state.skip_play(),
+            depth - 1,
+            ply + 1,
+            control,
+            evaluator,
+            counters,
+        )?;
+        return Some((value, None));
     }
 
-    if depth > 1 {
-        let nodes = check_moves_size.par_bridge().filter_map(|mov| {
-            Some((
-                min_max_rec(player, &state.play(&mov), depth - 1)?.0,
-                Some(mov),
-            ))
-        });
-        if state.current_player == player {
-            nodes.min_by_key(|a: &(i8, Option<Movement>)| a.0)
-        } else {
-            nodes.max_by_key(|a: &(i8, Option<Movement>)| a.0)
+    let minimize = state.current_player == player;
+    let best = if depth > 1 {
+        // each task counts on its own, counters are merged afterwards
+        let nodes: Vec<(Option<Node>, Counters)> = check_moves_size
+            .par_bridge()
+            .map(|mov| {
+                let mut task_counters = Counters::default();
+                let value = min_max_rec(
+                    player,
+                    &state.play(&mov),
+                    depth - 1,
+                    ply + 1,
+                    control,
+                    evaluator,
+                    &mut task_counters,
+                );
+                (value.map(|(value, _)| (value, Some(mov))), task_counters)
+            })
+            .collect();
+        for (_, task_counters) in &nodes {
+            *counters += *task_counters;
         }
+        best(minimize, nodes.into_iter().filter_map(|(node, _)| node))
     } else {
         let nodes = check_moves_size.filter_map(|mov| {
             Some((
-                min_max_rec(player, &state.play(&mov), depth - 1)?.0,
+                min_max_rec(
+                    player,
+                    &state.play(&mov),
+                    depth - 1,
+                    ply + 1,
+                    control,
+                    evaluator,
+                    counters,
+                )?
+                .0,
                 Some(mov),
             ))
         });
-        if state.current_player == player {
-            nodes.min_by_key(|a: &(i8, Option<Movement>)| a.0)
-        } else {
-            nodes.max_by_key(|a: &(i8, Option<Movement>)| a.0)
+        best(minimize, nodes)
+    };
+    if control.stopped() {
+        None
+    } else {
+        best
+    }
+}
+
+impl MinMax {
+    /// Min-Max with given depth.
+    pub fn new(depth: u8) -> Self {
+        MinMax {
+            depth,
+            evaluator: Material,
+            stats: Default::default(),
+        }
+    }
+}
+
+impl<E: Evaluator> MinMax<E> {
+    /// Evaluate leaves with given evaluator.
+    pub fn evaluator<F: Evaluator>(self, evaluator: F) -> MinMax<F> {
+        MinMax {
+            depth: self.depth,
+            evaluator,
+            stats: self.stats,
         }
     }
+
+    /// Search best move and its value under given control.
+    /// Counters and the best move (as principal variation) are recorded in the control.
+    /// Return None if the search was stopped before completion.
+    pub fn search(
+        &self,
+        state: &Configuration,
+        control: &SearchControl,
+    ) -> Option<(Score, Option<Movement>)> {
+        let mut counters = Counters::default();
+        let result = min_max_rec(
+            state.current_player,
+            state,
+            self.depth,
+            0,
+            control,
+            &self.evaluator,
+            &mut counters,
+        );
+        control.record(counters);
+        let result = result?;
+        control.set_result(result.0, result.1.into_iter().collect());
+        Some(result)
+    }
 }
 
-impl Strategy for MinMax {
+impl<E: Evaluator> Strategy for MinMax<E> {
     fn compute_next_move(&mut self, state: &Configuration) -> Option<Movement> {
-        if let Some((_, mov)) = min_max_rec(state.current_player, state, self.0) {
+        let control = SearchControl::default();
+        let start = Instant::now();
+        if let Some((_, mov)) = self.search(state, &control) {
+            self.stats = control.stats(self.depth, vec![start.elapsed()]);
             mov
         } else {
             None
         }
     }
+
+    fn search_stats(&self) -> Option<SearchStats> {
+        Some(self.stats.clone())
+    }
 }
 
-impl fmt::Display for MinMax {
+impl<E> fmt::Display for MinMax<E> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Min - Max (max level: {})", self.0)
+        write!(f, "Min - Max (max level: {})", self.depth)
     }
 }
 
@@ -97,9 +206,15 @@ impl fmt::Display for MinMax {
 /// This function is intended to be called from blobwar_iterative_deepening.
 pub fn min_max_anytime(state: &Configuration) {
     let mut movement = AtomicMove::connect().expect("failed connecting to shmem");
+    let control = SearchControl::default();
+    let mut iterations = Vec::new();
     for depth in 2..100 {
-        movement.store(MinMax(depth).compute_next_move(state));
+        let start = Instant::now();
+        let result = MinMax::new(depth).search(state, &control);
+        iterations.push(start.elapsed());
+        movement.store(
+            result.and_then(|(_, chosen_movement)| chosen_movement),
+            &control.stats(depth, iterations.clone()),
+        );
     }
-    
-    movement.store(MinMax(4).compute_next_move(&state));
 }
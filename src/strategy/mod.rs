@@ -1,4 +1,5 @@
 //! We provide here structs for all possible kinds of players and AI.
+use crate::clock::Clock;
 use crate::configuration::{Configuration, Movement};
 use std::fmt;
 
@@ -7,8 +8,37 @@ pub trait Strategy: fmt::Display {
     /// Take current `Configuration` and return what to do next.
     /// None if no move is possible.
     fn compute_next_move(&mut self, configuration: &Configuration) -> Option<Movement>;
+
+    /// Statistics of the search behind the last computed move, for searching strategies.
+    fn search_stats(&self) -> Option<SearchStats> {
+        None
+    }
+
+    /// Time left to the player, given by timed matches before each move so that strategies
+    /// can budget their thinking time. Ignored by default.
+    fn set_clock(&mut self, _clock: Clock) {}
+}
+
+impl<S: Strategy + ?Sized> Strategy for Box<S> {
+    fn compute_next_move(&mut self, configuration: &Configuration) -> Option<Movement> {
+        (**self).compute_next_move(configuration)
+    }
+
+    fn search_stats(&self) -> Option<SearchStats> {
+        (**self).search_stats()
+    }
+
+    fn set_clock(&mut self, clock: Clock) {
+        (**self).set_clock(clock)
+    }
 }
 
+pub mod evaluator;
+pub use self::evaluator::{Evaluator, Frontier, Material, Mobility, Score, Weighted};
+pub mod ordering;
+pub use self::ordering::MoveOrdering;
+pub mod search;
+pub use self::search::{Counters, Outcome, SearchControl, SearchStats, MATE};
 pub mod human;
 pub use self::human::Human;
 pub mod network;
@@ -21,6 +51,21 @@ pub mod alphabeta;
 pub use self::alphabeta::{alpha_beta_anytime, AlphaBeta};
 pub mod alphabetapar;
 pub use self::alphabetapar::{alpha_beta_par_anytime, AlphaBetaPar};
+pub mod mcts;
+pub use self::mcts::{Budget, Mcts, Rollout, DEFAULT_TREES};
+pub mod pvs;
+pub use self::pvs::{pvs_anytime, Pvs};
+pub mod book;
+pub use self::book::BookStrategy;
+pub mod endgame;
+pub use self::endgame::{Endgame, Solution};
+pub mod transposition;
+pub use self::transposition::{SharedTranspositionTable, TableStats, TranspositionTable};
+pub mod factory;
+pub use self::factory::from_spec;
 pub mod iterative;
 pub use self::iterative::IterativeDeepening;
-pub use self::iterative::IterativeStrategy;
+pub use self::iterative::{
+    aspiration, deepening, iterative_deepening, iterative_deepening_with_stats, AspirationStats,
+    Iteration, IterativeStrategy, SearchMode, MAX_DEPTH,
+};
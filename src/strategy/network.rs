@@ -1,10 +1,14 @@
 //! Network player (server side)
+//! A remote client sending garbage or closing its connection forfeits its remaining turns : it
+//! passes from then on, which loses the match as soon as it could move. Movements outside of
+//! the board are returned as they are : matches reject them as any other illegal movement.
 use super::Strategy;
 use crate::configuration::{Configuration, Movement};
+use crate::error::BlobwarError;
 
 use serde_json::{de, Deserializer, StreamDeserializer};
 use std::fmt;
-use std::io::prelude::*;
+use std::io::{self, prelude::*};
 use std::net::TcpStream;
 
 /// Let a remote client enter moves.
@@ -12,6 +16,8 @@ pub struct NetworkPlayer {
     connection: TcpStream,
     movements: StreamDeserializer<'static, de::IoRead<TcpStream>, Option<Movement>>,
     name: String,
+    /// Did the client misbehave or leave ?
+    disconnected: bool,
 }
 
 impl fmt::Display for NetworkPlayer {
@@ -22,25 +28,58 @@ impl fmt::Display for NetworkPlayer {
 
 impl NetworkPlayer {
     /// Create a new network player
-    pub fn new(data: TcpStream) -> Self {
-        let origin = data.peer_addr().unwrap().to_string();
-        let connection = data.try_clone().unwrap();
+    pub fn new(data: TcpStream) -> Result<Self, BlobwarError> {
+        let origin = data.peer_addr()?.to_string();
+        let connection = data.try_clone()?;
         let movements = Deserializer::from_reader(data).into_iter::<Option<Movement>>();
-        NetworkPlayer {
+        Ok(NetworkPlayer {
             connection,
             movements,
             name: origin,
-        }
+            disconnected: false,
+        })
+    }
+
+    /// Has the client forfeited its remaining turns ?
+    pub fn disconnected(&self) -> bool {
+        self.disconnected
+    }
+
+    /// Send given configuration and receive the answer of the client.
+    fn exchange(
+        &mut self,
+        configuration: &Configuration,
+    ) -> Result<Option<Movement>, BlobwarError> {
+        let mut message = configuration.serialize();
+        message.push('\n');
+        self.connection.write_all(message.as_bytes())?;
+        let movement = self.movements.next().ok_or_else(|| {
+            BlobwarError::Io(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "connection closed",
+            ))
+        })??;
+        Ok(movement)
     }
 }
 
 impl Strategy for NetworkPlayer {
     fn compute_next_move(&mut self, configuration: &Configuration) -> Option<Movement> {
-        let mut message = configuration.serialize();
-        message.push('\n');
-        self.connection
-            .write_all(message.into_bytes().as_slice())
-            .expect("sending configuration remotely failed");
-        self.movements.next().unwrap().unwrap()
+        if self.disconnected {
+            return None;
+        }
+        match self.exchange(configuration) {
+            Ok(movement) => {
+                if let Some(Err(e)) = movement.map(|m| m.check_range(configuration.board())) {
+                    eprintln!("{} : {}, an illegal movement", self.name, e);
+                }
+                movement
+            }
+            Err(e) => {
+                eprintln!("{} : {}, forfeiting remaining turns", self.name, e);
+                self.disconnected = true;
+                None
+            }
+        }
     }
 }
@@ -0,0 +1,135 @@
+//! Move ordering for alpha - beta searches.
+//! The sooner the best move is tried, the more branches get pruned.
+//! Movements are tried in this order :
+//! - the best move stored in the transposition table ("hash move")
+//! - killer moves : moves which recently caused a cutoff at the same ply
+//! - all other moves, by decreasing number of converted blobs, duplicates before jumps and
+//!   then by decreasing history score (how much they caused cutoffs anywhere in the tree).
+//!
+//! Tables use atomics so that parallel searches can share them.
+use std::cmp::Reverse;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::configuration::{Configuration, Movement};
+use crate::positions::MAX_CELLS;
+
+/// Number of killer moves remembered per ply.
+const KILLERS: usize = 2;
+
+/// Deepest ply for which we remember killer moves.
+const MAX_PLY: usize = 128;
+
+/// Index of given movement in the history table.
+fn history_index(movement: &Movement) -> usize {
+    match *movement {
+        Movement::Duplicate(destination) => destination as usize,
+        Movement::Jump(source, destination) => {
+            MAX_CELLS + source as usize * MAX_CELLS + destination as usize
+        }
+    }
+}
+
+/// Killer and history tables.
+pub struct MoveOrdering {
+    enabled: bool,
+    killers: Vec<[AtomicU32; KILLERS]>,
+    history: Vec<AtomicU32>,
+}
+
+impl Default for MoveOrdering {
+    fn default() -> Self {
+        MoveOrdering {
+            enabled: true,
+            killers: (0..MAX_PLY)
+                .map(|_| [AtomicU32::new(0), AtomicU32::new(0)])
+                .collect(),
+            history: (0..MAX_CELLS + MAX_CELLS * MAX_CELLS)
+                .map(|_| AtomicU32::new(0))
+                .collect(),
+        }
+    }
+}
+
+impl MoveOrdering {
+    /// Ordering keeping movements in generation order.
+    pub fn disabled() -> Self {
+        MoveOrdering {
+            enabled: false,
+            ..Default::default()
+        }
+    }
+
+    /// Are movements really sorted ?
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn killers(&self, ply: usize) -> impl Iterator<Item = Movement> + '_ {
+        self.killers
+            .get(ply)
+            .into_iter()
+            .flat_map(|killers| killers.iter())
+            .filter_map(|killer| Movement::unpack(killer.load(Ordering::Relaxed)))
+    }
+
+    /// Return all possible movements on given configuration at given ply, best first.
+    pub fn order(
+        &self,
+        state: &Configuration,
+        hash_move: Option<Movement>,
+        ply: usize,
+    ) -> Vec<Movement> {
+        let mut movements: Vec<Movement> = state
+            .movements()
+            .filter(|mov| state.check_move(mov))
+            .collect();
+        if !self.enabled {
+            return movements;
+        }
+        let priority = |movement: &Movement| {
+            if Some(*movement) == hash_move {
+                0
+            } else if self.killers(ply).any(|killer| killer == *movement) {
+                1
+            } else {
+                2
+            }
+        };
+        movements.sort_by_cached_key(|movement| {
+            (
+                priority(movement),
+                Reverse(state.captures(movement)),
+                matches!(movement, Movement::Jump(_, _)),
+                Reverse(self.history[history_index(movement)].load(Ordering::Relaxed)),
+            )
+        });
+        movements
+    }
+
+    /// Remember that given movement caused a cutoff at given ply with given remaining depth.
+    pub fn record_cutoff(&self, movement: &Movement, ply: usize, depth: u8) {
+        if !self.enabled {
+            return;
+        }
+        if let Some(killers) = self.killers.get(ply) {
+            let packed = Movement::pack(Some(*movement));
+            if killers[0].load(Ordering::Relaxed) != packed {
+                killers[1].store(killers[0].load(Ordering::Relaxed), Ordering::Relaxed);
+                killers[0].store(packed, Ordering::Relaxed);
+            }
+        }
+        let depth = depth as u32;
+        self.history[history_index(movement)].fetch_add(depth * depth, Ordering::Relaxed);
+    }
+
+    /// Prepare for a search from a new root : forget killers (plies changed) and halve history
+    /// scores so that recent cutoffs weigh more.
+    pub fn age(&self) {
+        for killer in self.killers.iter().flat_map(|killers| killers.iter()) {
+            killer.store(0, Ordering::Relaxed);
+        }
+        for score in &self.history {
+            score.store(score.load(Ordering::Relaxed) / 2, Ordering::Relaxed);
+        }
+    }
+}
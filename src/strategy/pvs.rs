@@ -0,0 +1,346 @@
+//! Principal variation search (NegaScout) : negamax alpha - beta where only the first move is
+//! searched with the full window. Other moves are searched with a null window to prove they are
+//! worse, and re-searched with the full window when the proof fails.
+use std::fmt;
+use std::time::Instant;
+
+use super::endgame::Endgame;
+use super::evaluator::{Evaluator, Material, Score};
+use super::iterative::{aspiration, AspirationStats};
+use super::ordering::MoveOrdering;
+use super::search::{
+    blocked, terminal_value, value_from_table, value_to_table, Counters, SearchStats,
+};
+use super::transposition::{Entry, TableStats, TranspositionTable};
+use super::{SearchControl, Strategy};
+use crate::configuration::{Configuration, Movement};
+use crate::shmem::AtomicMove;
+
+/// Bound of the full search window (not `Score::MIN` so that it can be negated).
+const INFINITY: Score = Score::MAX;
+
+/// Everything a search needs besides the current configuration.
+struct Search<'s, E> {
+    table: &'s mut TranspositionTable,
+    ordering: &'s MoveOrdering,
+    control: &'s SearchControl,
+    evaluator: &'s E,
+    endgame: &'s Endgame,
+    /// Triangular table of principal variations : line found at each ply.
+    pv: Vec<Vec<Movement>>,
+    counters: Counters,
+    re_searches: u64,
+}
+
+impl<E: Evaluator> Search<'_, E> {
+    /// Evaluate leaf configuration, for the player to move.
+    fn evaluate(&self, state: &Configuration) -> Score {
+        -self.evaluator.evaluate(state)
+    }
+
+    /// Negamax search : values are seen from the player to move, higher is better.
+    fn pvs_rec(
+        &mut self,
+        state: &Configuration,
+        depth: u8,
+        mut alpha: Score,
+        beta: Score,
+        ply: usize,
+    ) -> Option<Score> {
+        if self.control.stopped() {
+            return None;
+        }
+        self.counters.nodes += 1;
+        if self.pv.len() <= ply {
+            self.pv.resize_with(ply + 1, Vec::new);
+        }
+        self.pv[ply].clear();
+        if state.game_over() {
+            return Some(-terminal_value(state, ply));
+        }
+        if self.endgame.applies(state) {
+            let solved =
+                self.endgame
+                    .search(state, self.control, self.evaluator, alpha, beta, ply)?;
+            if let Some((value, movement)) = solved {
+                self.pv[ply].extend(movement);
+                return Some(value);
+            }
+        }
+        if depth == 0 {
+            self.control.reach_horizon();
+            self.counters.evaluations += 1;
+            return Some(self.evaluate(state));
+        }
+
+        // the key includes the player to move so entries are simply stored from his point of
+        // view
+        let key = state.zobrist_hash();
+        let mut hash_move = None;
+        if let Some(mut entry) = self.table.probe(key) {
+            self.counters.table_hits += 1;
+            entry.value = value_from_table(entry.value, ply);
+            if let Some(value) = entry.cutoff(depth, alpha, beta, false) {
+                // we cannot know if the stored search was cut by its depth limit
+                self.control.reach_horizon();
+                self.pv[ply].extend(entry.best_move);
+                return Some(value);
+            }
+            hash_move = entry.best_move;
+        }
+
+        let moves = self.ordering.order(state, hash_move, ply);
+        if moves.is_empty() {
+            // pass, the game ends if no one can move
+            if blocked(state) {
+                return Some(-terminal_value(state, ply));
+            }
+            return Some(-self.pvs_rec(&state.skip_play(), depth - 1, -beta, -alpha, ply + 1)?);
+        }
+        let original_alpha = alpha;
+        let mut best_value = -INFINITY;
+        let mut best_move = None;
+        for (index, movement) in moves.into_iter().enumerate() {
+            let child = state.play(&movement);
+            let value = if index == 0 {
+                -self.pvs_rec(&child, depth - 1, -beta, -alpha, ply + 1)?
+            } else {
+                let value = -self.pvs_rec(&child, depth - 1, -alpha - 1, -alpha, ply + 1)?;
+                if value > alpha && value < beta {
+                    self.re_searches += 1;
+                    -self.pvs_rec(&child, depth - 1, -beta, -alpha, ply + 1)?
+                } else {
+                    value
+                }
+            };
+            if value > best_value {
+                best_value = value;
+                best_move = Some(movement);
+                if value > alpha {
+                    alpha = value;
+                    let (line, rest) = self.pv.split_at_mut(ply + 1);
+                    let line = &mut line[ply];
+                    line.clear();
+                    line.push(movement);
+                    line.extend_from_slice(&rest[0]);
+                    if value >= beta {
+                        self.ordering.record_cutoff(&movement, ply, depth);
+                        self.counters.cutoffs += 1;
+                        break;
+                    }
+                }
+            }
+        }
+        if self.control.stopped() {
+            return None;
+        }
+        let mut entry = Entry::new(depth, best_value, original_alpha, beta, best_move);
+        entry.value = value_to_table(best_value, ply);
+        self.table.store(key, entry);
+        Some(best_value)
+    }
+}
+
+/// Anytime principal variation search.
+/// Any time algorithms will compute until a deadline is hit and the process is killed.
+/// They are therefore run in another process and communicate through shared memory.
+/// This function is intended to be called from blobwar_iterative_deepening.
+pub fn pvs_anytime(state: &Configuration) {
+    let mut movement = AtomicMove::connect().expect("failed connecting to shmem");
+    let mut pvs = Pvs::new(2);
+    // each iteration is searched in a window around the value of the previous one
+    let control = SearchControl::default();
+    let mut stats = AspirationStats::default();
+    let mut previous = None;
+    let mut iterations = Vec::new();
+    for depth in 2..100 {
+        let start = Instant::now();
+        pvs.depth = depth;
+        let result = aspiration(previous, &mut stats, |alpha, beta| {
+            pvs.search_window(state, &control, alpha, beta)
+        });
+        previous = result.map(|(value, _)| value);
+        iterations.push(start.elapsed());
+        movement.store(
+            result.and_then(|(_, chosen_movement)| chosen_movement),
+            &control.stats(depth, iterations.clone()),
+        );
+    }
+}
+
+/// Principal variation search with given maximum number of recursions.
+/// Search results are kept in a transposition table between calls and the expected line of
+/// play of the last search is available with `principal_variation`.
+/// Leaves are evaluated with `E` (blob count by default). Positions with few empty cells are
+/// solved until the end of the game instead, when the solver can prove their value (see
+/// `endgame`).
+pub struct Pvs<E = Material> {
+    /// Maximum number of recursions.
+    pub depth: u8,
+    table: TranspositionTable,
+    ordering: MoveOrdering,
+    endgame: Endgame,
+    evaluator: E,
+    principal_variation: Vec<Movement>,
+    nodes: u64,
+    /// Root key and depth of the last search : re-searches of the same depth (in other windows)
+    /// keep the move ordering.
+    last_root: Option<(u64, u8)>,
+    re_searches: u64,
+    stats: SearchStats,
+}
+
+impl Pvs {
+    /// Principal variation search with given depth and a default sized transposition table.
+    pub fn new(depth: u8) -> Self {
+        Pvs {
+            depth,
+            table: Default::default(),
+            ordering: Default::default(),
+            endgame: Default::default(),
+            evaluator: Material,
+            principal_variation: Vec::new(),
+            nodes: 0,
+            last_root: None,
+            re_searches: 0,
+            stats: Default::default(),
+        }
+    }
+}
+
+impl<E: Evaluator> Pvs<E> {
+    /// Use a transposition table of 2^bits entries.
+    pub fn table_bits(self, bits: u8) -> Self {
+        Pvs {
+            table: TranspositionTable::new(bits),
+            ..self
+        }
+    }
+
+    /// Enable or disable move ordering.
+    pub fn ordering(self, enabled: bool) -> Self {
+        Pvs {
+            ordering: if enabled {
+                Default::default()
+            } else {
+                MoveOrdering::disabled()
+            },
+            ..self
+        }
+    }
+
+    /// Solve positions with at most given number of empty cells until the end of the game with
+    /// the endgame solver, instead of searching them (`DEFAULT_THRESHOLD` by default, 0 disables
+    /// it). Positions whose values the solver cannot prove are searched.
+    pub fn endgame(self, threshold: usize) -> Self {
+        Pvs {
+            endgame: Endgame::new(threshold),
+            ..self
+        }
+    }
+
+    /// Evaluate leaves with given evaluator.
+    pub fn evaluator<F: Evaluator>(self, evaluator: F) -> Pvs<F> {
+        Pvs {
+            depth: self.depth,
+            table: self.table,
+            ordering: self.ordering,
+            endgame: self.endgame,
+            evaluator,
+            principal_variation: self.principal_variation,
+            nodes: self.nodes,
+            last_root: self.last_root,
+            re_searches: self.re_searches,
+            stats: self.stats,
+        }
+    }
+
+    /// Transposition table usage counters.
+    pub fn table_stats(&self) -> TableStats {
+        self.table.stats()
+    }
+
+    /// Number of nodes visited since creation, by the search and the endgame solver.
+    pub fn nodes(&self) -> u64 {
+        self.nodes + self.endgame.nodes()
+    }
+
+    /// Number of full window re-searches after failed null window searches since creation.
+    pub fn re_searches(&self) -> u64 {
+        self.re_searches
+    }
+
+    /// Expected line of play found by the last completed search, starting with the best move.
+    /// It might be shorter than the search depth when it ends on a transposition.
+    pub fn principal_variation(&self) -> &[Movement] {
+        &self.principal_variation
+    }
+
+    /// Search best move and its value under given control.
+    /// As for `AlphaBeta`, the value is seen from the player to move and lower is better.
+    /// Return None if the search was stopped before completion.
+    pub fn search(
+        &mut self,
+        state: &Configuration,
+        control: &SearchControl,
+    ) -> Option<(Score, Option<Movement>)> {
+        self.search_window(state, control, Score::MIN, Score::MAX)
+    }
+
+    /// Search best move and its value in window `alpha..beta` (see `AlphaBeta::search_window`).
+    /// Counters and the principal variation are recorded in the control.
+    pub fn search_window(
+        &mut self,
+        state: &Configuration,
+        control: &SearchControl,
+        alpha: Score,
+        beta: Score,
+    ) -> Option<(Score, Option<Movement>)> {
+        let root = Some((state.zobrist_hash(), self.depth));
+        if self.last_root != root {
+            self.last_root = root;
+            self.ordering.age();
+            self.endgame.age();
+        }
+        let mut search = Search {
+            table: &mut self.table,
+            ordering: &self.ordering,
+            control,
+            evaluator: &self.evaluator,
+            endgame: &self.endgame,
+            pv: Vec::new(),
+            counters: Default::default(),
+            re_searches: 0,
+        };
+        // negamax window of the player to move
+        let (alpha, beta) = (-beta, -alpha.max(-INFINITY));
+        let result = search.pvs_rec(state, self.depth, alpha, beta, 0);
+        self.nodes += search.counters.nodes;
+        self.re_searches += search.re_searches;
+        control.record(search.counters);
+        let value = result?;
+        self.principal_variation = search.pv.swap_remove(0);
+        control.set_result(-value, self.principal_variation.clone());
+        Some((-value, self.principal_variation.first().copied()))
+    }
+}
+
+impl<E> fmt::Display for Pvs<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PVS (max level: {})", self.depth)
+    }
+}
+
+impl<E: Evaluator> Strategy for Pvs<E> {
+    fn compute_next_move(&mut self, state: &Configuration) -> Option<Movement> {
+        let control = SearchControl::default();
+        let start = Instant::now();
+        let (_, mov) = self.search(state, &control)?;
+        self.stats = control.stats(self.depth, vec![start.elapsed()]);
+        mov
+    }
+
+    fn search_stats(&self) -> Option<SearchStats> {
+        Some(self.stats.clone())
+    }
+}
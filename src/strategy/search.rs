@@ -0,0 +1,311 @@
+//! State shared between a running search and whoever drives it, the meaning of the values
+//! it returns and statistics about its work.
+use std::fmt;
+use std::ops::AddAssign;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::evaluator::Score;
+use super::transposition::Entry;
+use crate::board::Board;
+use crate::configuration::{Configuration, Movement};
+
+/// Value of a game won right now. Games won in n plies are worth `MATE - n` so that faster wins
+/// are preferred (and slower losses). Evaluations must stay far below.
+pub const MATE: Score = 1_000_000;
+
+/// Longest win we can tell from an evaluation.
+pub(crate) const MAX_MATE_PLIES: Score = 10_000;
+
+/// Value of a finished game reached after given number of plies, following the convention of
+/// `Configuration::value` : positive if the player to move lost.
+pub fn terminal_value(state: &Configuration, ply: usize) -> Score {
+    (MATE - ply as Score) * state.value().signum() as Score
+}
+
+/// Does no one have any legal move ? The game then ends even with empty cells left.
+pub fn blocked(state: &Configuration) -> bool {
+    let other = state.skip_play();
+    !state
+        .movements()
+        .any(|movement| state.check_move(&movement))
+        && !other
+            .movements()
+            .any(|movement| other.check_move(&movement))
+}
+
+/// Rank breaking ties between root moves of equal value : the lowest rank is chosen, whatever
+/// the order moves were searched in. Parallel searches, whose move ordering depends on the
+/// timing of threads, then choose the same moves as sequential ones.
+pub(crate) fn tie_rank(movement: &Movement) -> u32 {
+    Movement::pack(Some(*movement))
+}
+
+/// Mate values found at given ply are stored in transposition tables as seen from the stored
+/// position so that they stay correct when it is reached at another ply.
+pub(crate) fn value_to_table(value: Score, ply: usize) -> Score {
+    if value >= MATE - MAX_MATE_PLIES {
+        value.saturating_add(ply as Score)
+    } else if value <= -(MATE - MAX_MATE_PLIES) {
+        value.saturating_sub(ply as Score)
+    } else {
+        value
+    }
+}
+
+/// Mate value found in a transposition table, back to the ply we are at.
+pub(crate) fn value_from_table(value: Score, ply: usize) -> Score {
+    if value >= MATE - MAX_MATE_PLIES {
+        value.saturating_sub(ply as Score)
+    } else if value <= -(MATE - MAX_MATE_PLIES) {
+        value.saturating_add(ply as Score)
+    } else {
+        value
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Meaning of a search value : a game over within the search or an evaluation at the horizon.
+pub enum Outcome {
+    /// The searching player wins in given number of plies.
+    Win(u32),
+    /// The searching player loses in given number of plies.
+    Loss(u32),
+    /// Evaluation (or a drawn game).
+    Evaluation(Score),
+}
+
+impl Outcome {
+    /// Interpret a value following the alpha - beta searches convention (seen from the
+    /// searching player, lower is better).
+    pub fn of(value: Score) -> Self {
+        if value <= -(MATE - MAX_MATE_PLIES) {
+            Outcome::Win(MATE.saturating_add(value).max(0) as u32)
+        } else if value >= MATE - MAX_MATE_PLIES {
+            Outcome::Loss(MATE.saturating_sub(value).max(0) as u32)
+        } else {
+            Outcome::Evaluation(value)
+        }
+    }
+
+    /// Is it a game over rather than an evaluation ?
+    pub fn game_over(&self) -> bool {
+        !matches!(self, Outcome::Evaluation(_))
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Win(plies) => write!(f, "win in {} plies", plies),
+            Outcome::Loss(plies) => write!(f, "loss in {} plies", plies),
+            // evaluations are seen from the searching player, higher is better
+            Outcome::Evaluation(value) => write!(f, "{:+}", -value),
+        }
+    }
+}
+
+/// Line of play following the best moves stored in a transposition table, starting with given
+/// move from given configuration and at most `length` moves long.
+/// `probe` returns the entry stored for a key. The line stops on passes and missing entries.
+pub(crate) fn table_line<F>(
+    state: &Configuration,
+    first: Option<Movement>,
+    length: u8,
+    mut probe: F,
+) -> Vec<Movement>
+where
+    F: FnMut(u64) -> Option<Entry>,
+{
+    let mut line = Vec::new();
+    let mut state = *state;
+    let mut movement = first;
+    while let Some(next) = movement {
+        if line.len() >= length as usize
+            || next.check_range(state.board()).is_err()
+            || !state.check_move(&next)
+        {
+            break;
+        }
+        line.push(next);
+        state.apply_movement(&next);
+        movement = probe(state.zobrist_hash()).and_then(|entry| entry.best_move);
+    }
+    line
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+/// Events counted by searches.
+pub struct Counters {
+    /// Number of visited nodes.
+    pub nodes: u64,
+    /// Number of leaves evaluated at the horizon.
+    pub evaluations: u64,
+    /// Number of nodes cut because a move was too good.
+    pub cutoffs: u64,
+    /// Number of transposition table probes finding an entry.
+    pub table_hits: u64,
+}
+
+impl AddAssign for Counters {
+    fn add_assign(&mut self, other: Self) {
+        self.nodes += other.nodes;
+        self.evaluations += other.evaluations;
+        self.cutoffs += other.cutoffs;
+        self.table_hits += other.table_hits;
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+/// What a search did to choose its move.
+/// The principal variation is not displayed : moves need the board to be named (see
+/// `SearchStats::principal_variation`).
+pub struct SearchStats {
+    /// Events counted during the whole search.
+    pub counters: Counters,
+    /// Depth of the deepest completed search.
+    pub depth: u8,
+    /// Time taken by each completed iteration (a single one for fixed depth searches).
+    pub iterations: Vec<Duration>,
+    /// Expected line of play, starting with the chosen move.
+    pub principal_variation: Vec<Movement>,
+    /// Value of the deepest completed search, seen from the player to move : lower is better
+    /// (None if no search completed).
+    pub value: Option<Score>,
+}
+
+impl SearchStats {
+    /// Total time of completed iterations.
+    pub fn time(&self) -> Duration {
+        self.iterations.iter().sum()
+    }
+
+    /// Nodes visited per second.
+    pub fn nodes_per_second(&self) -> f64 {
+        self.counters.nodes as f64 / self.time().as_secs_f64().max(1e-9)
+    }
+
+    /// Principal variation in algebraic notation on given board, such as "a1c3 g7 b2".
+    pub fn principal_variation(&self, board: &Board) -> String {
+        self.principal_variation
+            .iter()
+            .map(|movement| movement.notation(board).to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "depth {}, {} nodes, {} evaluations, {} cutoffs, {} table hits, iterations",
+            self.depth,
+            self.counters.nodes,
+            self.counters.evaluations,
+            self.counters.cutoffs,
+            self.counters.table_hits
+        )?;
+        for time in &self.iterations {
+            write!(f, " {}ms", time.as_millis())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+/// Cooperative control of a search.
+/// The driver raises the stop flag and the search gives up as soon as it sees it.
+/// The search records whether it had to stop on its depth limit, so that the driver knows if
+/// searching deeper is of any use. It also counts events and records its principal variation
+/// for the driver's statistics.
+pub struct SearchControl {
+    stop: AtomicBool,
+    horizon_reached: AtomicBool,
+    nodes: AtomicU64,
+    evaluations: AtomicU64,
+    cutoffs: AtomicU64,
+    table_hits: AtomicU64,
+    principal_variation: Mutex<Vec<Movement>>,
+    value: Mutex<Option<Score>>,
+}
+
+impl SearchControl {
+    /// Ask the search to stop.
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed)
+    }
+
+    /// Should the search stop ?
+    pub fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    /// Record that a leaf was evaluated because of the depth limit.
+    pub fn reach_horizon(&self) {
+        // avoid writing the shared flag at each leaf
+        if !self.horizon_reached.load(Ordering::Relaxed) {
+            self.horizon_reached.store(true, Ordering::Relaxed)
+        }
+    }
+
+    /// Did the search hit its depth limit since the last `new_iteration` ?
+    pub fn horizon_reached(&self) -> bool {
+        self.horizon_reached.load(Ordering::Relaxed)
+    }
+
+    /// Prepare for a new iteration of a deepening search.
+    pub fn new_iteration(&self) {
+        self.horizon_reached.store(false, Ordering::Relaxed)
+    }
+
+    /// Add given events to the counters.
+    pub fn record(&self, counters: Counters) {
+        let pairs = [
+            (&self.nodes, counters.nodes),
+            (&self.evaluations, counters.evaluations),
+            (&self.cutoffs, counters.cutoffs),
+            (&self.table_hits, counters.table_hits),
+        ];
+        for (counter, count) in pairs.iter() {
+            if *count != 0 {
+                counter.fetch_add(*count, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Events counted since creation.
+    pub fn counters(&self) -> Counters {
+        Counters {
+            nodes: self.nodes.load(Ordering::Relaxed),
+            evaluations: self.evaluations.load(Ordering::Relaxed),
+            cutoffs: self.cutoffs.load(Ordering::Relaxed),
+            table_hits: self.table_hits.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Record the value (see `SearchStats::value`) and principal variation of the last
+    /// completed search.
+    pub fn set_result(&self, value: Score, line: Vec<Movement>) {
+        *self.value.lock().unwrap() = Some(value);
+        *self.principal_variation.lock().unwrap() = line;
+    }
+
+    /// Principal variation of the last completed search.
+    pub fn principal_variation(&self) -> Vec<Movement> {
+        self.principal_variation.lock().unwrap().clone()
+    }
+
+    /// Statistics of the searches run so far, the deepest completed one being of given depth
+    /// and iterations having taken given times.
+    pub fn stats(&self, depth: u8, iterations: Vec<Duration>) -> SearchStats {
+        SearchStats {
+            counters: self.counters(),
+            depth,
+            iterations,
+            principal_variation: self.principal_variation(),
+            value: *self.value.lock().unwrap(),
+        }
+    }
+}
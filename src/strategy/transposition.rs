@@ -0,0 +1,322 @@
+//! Transposition tables : cache search results so that transposed positions are only searched
+//! once.
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::evaluator::Score;
+use crate::configuration::Movement;
+
+/// Default number of bits used to index tables (2^18 entries).
+pub const DEFAULT_TABLE_BITS: u8 = 18;
+
+/// Bit set in all packed entries, so that empty slots (all zeros) are never mistaken for an
+/// entry with depth 0, value 0, exact bound and no move.
+const OCCUPIED: u64 = 1 << 28;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// How a stored value relates to the real value of the position.
+pub enum Bound {
+    /// Stored value is the real value.
+    Exact,
+    /// Real value is greater or equal than the stored one.
+    Lower,
+    /// Real value is lower or equal than the stored one.
+    Upper,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Search result stored for a position.
+pub struct Entry {
+    /// Depth of the search which produced this entry.
+    pub depth: u8,
+    /// Value found, seen from the red player.
+    pub value: Score,
+    /// How `value` relates to the real value.
+    pub bound: Bound,
+    /// Best move found (if any).
+    pub best_move: Option<Movement>,
+}
+
+impl Entry {
+    /// Build entry for a search of given depth in window `alpha..beta` which returned `value`.
+    pub fn new(
+        depth: u8,
+        value: Score,
+        alpha: Score,
+        beta: Score,
+        best_move: Option<Movement>,
+    ) -> Self {
+        let bound = if value <= alpha {
+            Bound::Upper
+        } else if value >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        Entry {
+            depth,
+            value,
+            bound,
+            best_move,
+        }
+    }
+
+    /// Same entry with value seen from the other player.
+    pub fn flipped(self) -> Self {
+        Entry {
+            value: self.value.saturating_neg(),
+            bound: match self.bound {
+                Bound::Exact => Bound::Exact,
+                Bound::Lower => Bound::Upper,
+                Bound::Upper => Bound::Lower,
+            },
+            ..self
+        }
+    }
+
+    /// Return the value to use for a search of given depth in window `alpha..beta` if this entry
+    /// allows to skip it.
+    /// Entries of deeper searches are used too, unless `same_depth_only` is set : they make the
+    /// values found depend on the order in which positions are searched, which parallel searches
+    /// cannot fix.
+    pub fn cutoff(
+        &self,
+        depth: u8,
+        alpha: Score,
+        beta: Score,
+        same_depth_only: bool,
+    ) -> Option<Score> {
+        if self.depth < depth || (same_depth_only && self.depth != depth) {
+            return None;
+        }
+        match self.bound {
+            Bound::Exact => Some(self.value),
+            Bound::Lower if self.value >= beta => Some(self.value),
+            Bound::Upper if self.value <= alpha => Some(self.value),
+            _ => None,
+        }
+    }
+
+    /// Pack entry into 64 bits.
+    /// Bits 0-17 store the move, 18-25 the depth, 26-27 the bound, 28 the `OCCUPIED` flag
+    /// and 32-63 the value.
+    fn pack(&self) -> u64 {
+        let movement = Movement::pack(self.best_move) as u64;
+        let bound = match self.bound {
+            Bound::Exact => 0,
+            Bound::Lower => 1,
+            Bound::Upper => 2,
+        };
+        movement
+            | (self.depth as u64) << 18
+            | bound << 26
+            | OCCUPIED
+            | (self.value as u32 as u64) << 32
+    }
+
+    /// Unpack entry packed with `pack`.
+    fn unpack(data: u64) -> Self {
+        let best_move = Movement::unpack(data as u32 & 0x3_FFFF);
+        let bound = match (data >> 26) & 3 {
+            0 => Bound::Exact,
+            1 => Bound::Lower,
+            _ => Bound::Upper,
+        };
+        Entry {
+            depth: (data >> 18) as u8,
+            value: (data >> 32) as u32 as Score,
+            bound,
+            best_move,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+/// Usage counters of a transposition table.
+pub struct TableStats {
+    /// Number of probes finding an entry for the position.
+    pub hits: u64,
+    /// Number of probes finding nothing.
+    pub misses: u64,
+    /// Number of stored entries.
+    pub stores: u64,
+}
+
+impl TableStats {
+    /// Ratio of successful probes.
+    pub fn hit_rate(&self) -> f64 {
+        let probes = self.hits + self.misses;
+        if probes == 0 {
+            0.0
+        } else {
+            self.hits as f64 / probes as f64
+        }
+    }
+}
+
+/// Bounded transposition table for sequential searches.
+/// Each hash is stored at a fixed index, deeper searches replace shallower ones.
+pub struct TranspositionTable {
+    entries: Vec<Option<(u64, Entry)>>,
+    stats: TableStats,
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        TranspositionTable::new(DEFAULT_TABLE_BITS)
+    }
+}
+
+impl TranspositionTable {
+    /// Create a table holding 2^bits entries.
+    pub fn new(bits: u8) -> Self {
+        TranspositionTable {
+            entries: vec![None; 1 << bits],
+            stats: Default::default(),
+        }
+    }
+
+    fn index(&self, key: u64) -> usize {
+        (key as usize) & (self.entries.len() - 1)
+    }
+
+    /// Look for an entry stored with given key.
+    pub fn probe(&mut self, key: u64) -> Option<Entry> {
+        match self.entries[self.index(key)] {
+            Some((stored_key, entry)) if stored_key == key => {
+                self.stats.hits += 1;
+                Some(entry)
+            }
+            _ => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Look for an entry stored with given key without counting it in usage counters.
+    pub fn get(&self, key: u64) -> Option<Entry> {
+        match self.entries[self.index(key)] {
+            Some((stored_key, entry)) if stored_key == key => Some(entry),
+            _ => None,
+        }
+    }
+
+    /// Store given entry for given key.
+    pub fn store(&mut self, key: u64, entry: Entry) {
+        let index = self.index(key);
+        let replace = match self.entries[index] {
+            Some((stored_key, stored)) => stored_key != key || stored.depth <= entry.depth,
+            None => true,
+        };
+        if replace {
+            self.entries[index] = Some((key, entry));
+            self.stats.stores += 1;
+        }
+    }
+
+    /// Usage counters since creation (or last `clear`).
+    pub fn stats(&self) -> TableStats {
+        self.stats
+    }
+
+    /// Remove all entries and reset counters.
+    pub fn clear(&mut self) {
+        self.entries.iter_mut().for_each(|entry| *entry = None);
+        self.stats = Default::default();
+    }
+}
+
+/// Lock-free transposition table shared between threads.
+/// Each slot stores the packed entry and its key xored with the entry. A slot torn by
+/// concurrent writes fails the key check and is just seen as a miss.
+pub struct SharedTranspositionTable {
+    slots: Vec<(AtomicU64, AtomicU64)>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    stores: AtomicU64,
+}
+
+impl Default for SharedTranspositionTable {
+    fn default() -> Self {
+        SharedTranspositionTable::new(DEFAULT_TABLE_BITS)
+    }
+}
+
+impl SharedTranspositionTable {
+    /// Create a table holding 2^bits entries.
+    pub fn new(bits: u8) -> Self {
+        SharedTranspositionTable {
+            slots: (0..1usize << bits)
+                .map(|_| (AtomicU64::new(0), AtomicU64::new(0)))
+                .collect(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            stores: AtomicU64::new(0),
+        }
+    }
+
+    fn slot(&self, key: u64) -> &(AtomicU64, AtomicU64) {
+        &self.slots[(key as usize) & (self.slots.len() - 1)]
+    }
+
+    /// Look for an entry stored with given key.
+    pub fn probe(&self, key: u64) -> Option<Entry> {
+        let (checksum, data) = self.slot(key);
+        let data = data.load(Ordering::Relaxed);
+        // an empty slot holds (0, 0) which would match key 0
+        if data & OCCUPIED != 0 && checksum.load(Ordering::Relaxed) ^ data == key {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            Some(Entry::unpack(data))
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            None
+        }
+    }
+
+    /// Look for an entry stored with given key without counting it in usage counters.
+    pub fn get(&self, key: u64) -> Option<Entry> {
+        let (checksum, data) = self.slot(key);
+        let data = data.load(Ordering::Relaxed);
+        if data & OCCUPIED != 0 && checksum.load(Ordering::Relaxed) ^ data == key {
+            Some(Entry::unpack(data))
+        } else {
+            None
+        }
+    }
+
+    /// Store given entry for given key.
+    pub fn store(&self, key: u64, entry: Entry) {
+        let (checksum, data) = self.slot(key);
+        let old_data = data.load(Ordering::Relaxed);
+        if old_data & OCCUPIED != 0
+            && checksum.load(Ordering::Relaxed) ^ old_data == key
+            && Entry::unpack(old_data).depth > entry.depth
+        {
+            return;
+        }
+        let new_data = entry.pack();
+        checksum.store(key ^ new_data, Ordering::Relaxed);
+        data.store(new_data, Ordering::Relaxed);
+        self.stores.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Usage counters since creation (or last `clear`).
+    pub fn stats(&self) -> TableStats {
+        TableStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            stores: self.stores.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Remove all entries and reset counters.
+    pub fn clear(&mut self) {
+        for (checksum, data) in &self.slots {
+            checksum.store(0, Ordering::Relaxed);
+            data.store(0, Ordering::Relaxed);
+        }
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+        self.stores.store(0, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,90 @@
+//! Symmetries of boards : the 8 transforms of the dihedral group of the square.
+//! Rectangular boards only admit the 4 transforms which keep the axes in place.
+//! Coordinates are (x, y) with y growing downwards.
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+/// Rotation or reflection of a board.
+pub enum Transform {
+    /// Leave everything in place.
+    Identity,
+    /// Quarter turn clockwise.
+    Rotate90,
+    /// Half turn.
+    Rotate180,
+    /// Quarter turn anticlockwise.
+    Rotate270,
+    /// Mirror left and right columns.
+    FlipHorizontal,
+    /// Mirror top and bottom rows.
+    FlipVertical,
+    /// Mirror along the diagonal going from the top left corner.
+    FlipDiagonal,
+    /// Mirror along the diagonal going from the top right corner.
+    FlipAntiDiagonal,
+}
+
+impl Transform {
+    /// All transforms, identity first.
+    pub const ALL: [Transform; 8] = [
+        Transform::Identity,
+        Transform::Rotate90,
+        Transform::Rotate180,
+        Transform::Rotate270,
+        Transform::FlipHorizontal,
+        Transform::FlipVertical,
+        Transform::FlipDiagonal,
+        Transform::FlipAntiDiagonal,
+    ];
+
+    /// Do rows become columns ? Such transforms only apply to square boards.
+    pub fn swaps_axes(self) -> bool {
+        matches!(
+            self,
+            Transform::Rotate90
+                | Transform::Rotate270
+                | Transform::FlipDiagonal
+                | Transform::FlipAntiDiagonal
+        )
+    }
+
+    /// Transform undoing this one.
+    pub fn inverse(self) -> Self {
+        match self {
+            Transform::Rotate90 => Transform::Rotate270,
+            Transform::Rotate270 => Transform::Rotate90,
+            other => other,
+        }
+    }
+
+    /// Image of given coordinates on a `width` x `height` board.
+    pub fn apply(self, x: u8, y: u8, width: u8, height: u8) -> (u8, u8) {
+        let (right, bottom) = (width - 1, height - 1);
+        match self {
+            Transform::Identity => (x, y),
+            Transform::Rotate90 => (bottom - y, x),
+            Transform::Rotate180 => (right - x, bottom - y),
+            Transform::Rotate270 => (y, right - x),
+            Transform::FlipHorizontal => (right - x, y),
+            Transform::FlipVertical => (x, bottom - y),
+            Transform::FlipDiagonal => (y, x),
+            Transform::FlipAntiDiagonal => (bottom - y, right - x),
+        }
+    }
+}
+
+impl fmt::Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Transform::Identity => "identity",
+            Transform::Rotate90 => "rotation by 90 degrees",
+            Transform::Rotate180 => "rotation by 180 degrees",
+            Transform::Rotate270 => "rotation by 270 degrees",
+            Transform::FlipHorizontal => "horizontal flip",
+            Transform::FlipVertical => "vertical flip",
+            Transform::FlipDiagonal => "diagonal flip",
+            Transform::FlipAntiDiagonal => "anti-diagonal flip",
+        };
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,193 @@
+//! Results of round-robin tournaments : games, standings, crosstable and Elo ratings.
+//! Used by the `tournament` binary.
+use std::sync::Mutex;
+use std::thread;
+
+use serde_derive::Serialize;
+
+use crate::game::MatchOutcome;
+use crate::pgn::PgnGame;
+
+/// Elo of an average player.
+pub const BASE_ELO: f64 = 1500.0;
+
+#[derive(Serialize, Debug, Clone)]
+/// One played game.
+pub struct GameResult {
+    /// Name of the board.
+    pub board: String,
+    /// Spec of the red player.
+    pub red: String,
+    /// Spec of the blue player.
+    pub blue: String,
+    /// "red", "blue" or "draw".
+    pub winner: &'static str,
+    /// Final number of red blobs.
+    pub red_blobs: u8,
+    /// Final number of blue blobs.
+    pub blue_blobs: u8,
+    /// Number of turns played (passes included).
+    pub plies: usize,
+    /// Number of passes.
+    pub passes: usize,
+    /// Thinking time of red.
+    pub red_time_ms: u128,
+    /// Thinking time of blue.
+    pub blue_time_ms: u128,
+    /// Indices of the red and blue players amongst the specs.
+    #[serde(skip)]
+    pub players: [usize; 2],
+    /// Game in PGN form, when asked for.
+    #[serde(skip)]
+    pub game: Option<PgnGame>,
+}
+
+impl GameResult {
+    /// Result of given match on given board between given players (indices in `specs`).
+    pub fn new(board: &str, specs: &[String], players: [usize; 2], outcome: &MatchOutcome) -> Self {
+        GameResult {
+            board: board.to_owned(),
+            red: specs[players[0]].clone(),
+            blue: specs[players[1]].clone(),
+            winner: match outcome.winner {
+                Some(false) => "red",
+                Some(true) => "blue",
+                None => "draw",
+            },
+            red_blobs: outcome.red,
+            blue_blobs: outcome.blue,
+            plies: outcome.plies,
+            passes: outcome.passes,
+            red_time_ms: outcome.time[0].as_millis(),
+            blue_time_ms: outcome.time[1].as_millis(),
+            players,
+            game: None,
+        }
+    }
+
+    /// Score (1 win, 0.5 draw, 0 loss) and blob margin of given side.
+    /// Forfeits count as losses whatever the margin.
+    pub fn score(&self, side: usize) -> (f64, i32) {
+        let margin = self.red_blobs as i32 - self.blue_blobs as i32;
+        let margin = if side == 0 { margin } else { -margin };
+        let score = match (self.winner, side) {
+            ("draw", _) => 0.5,
+            ("red", 0) | ("blue", 1) => 1.0,
+            _ => 0.0,
+        };
+        (score, margin)
+    }
+}
+
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+/// Results of one strategy.
+pub struct Standing {
+    /// Spec of the strategy.
+    pub strategy: String,
+    /// Number of games won.
+    pub wins: usize,
+    /// Number of drawn games.
+    pub draws: usize,
+    /// Number of games lost.
+    pub losses: usize,
+    /// Average final blob margin.
+    pub average_margin: f64,
+    /// Fitted Elo rating (see `fit_elo`).
+    pub elo: f64,
+}
+
+/// Wins, draws and losses of each player against each opponent :
+/// `crosstable[player][opponent] = [wins, draws, losses]`.
+pub type Crosstable = Vec<Vec<[usize; 3]>>;
+
+/// Fit Elo ratings to all games by gradient ascent on their likelihood.
+/// Each player gets a virtual draw against an average player so that ratings stay finite.
+pub fn fit_elo(players: usize, games: &[GameResult]) -> Vec<f64> {
+    let expected = |rating: f64, other: f64| 1.0 / (1.0 + 10f64.powf((other - rating) / 400.0));
+    let mut ratings = vec![BASE_ELO; players];
+    for _ in 0..1000 {
+        let mut gradient: Vec<f64> = ratings
+            .iter()
+            .map(|&rating| 0.5 - expected(rating, BASE_ELO))
+            .collect();
+        let mut counts = vec![1.0; players];
+        for game in games {
+            for side in 0..2 {
+                let (player, opponent) = (game.players[side], game.players[1 - side]);
+                gradient[player] +=
+                    game.score(side).0 - expected(ratings[player], ratings[opponent]);
+                counts[player] += 1.0;
+            }
+        }
+        for player in 0..players {
+            ratings[player] += 200.0 * gradient[player] / counts[player];
+        }
+    }
+    ratings
+}
+
+/// Standings of given strategies (in the order of their specs) and crosstable of all games.
+pub fn standings(specs: &[String], games: &[GameResult]) -> (Vec<Standing>, Crosstable) {
+    let elo = fit_elo(specs.len(), games);
+    let mut standings: Vec<Standing> = specs
+        .iter()
+        .zip(elo)
+        .map(|(strategy, elo)| Standing {
+            strategy: strategy.clone(),
+            elo,
+            ..Default::default()
+        })
+        .collect();
+    let mut crosstable = vec![vec![[0; 3]; specs.len()]; specs.len()];
+    for game in games {
+        for side in 0..2 {
+            let (player, opponent) = (game.players[side], game.players[1 - side]);
+            let (score, margin) = game.score(side);
+            let outcome = (2.0 - 2.0 * score) as usize;
+            crosstable[player][opponent][outcome] += 1;
+            let standing = &mut standings[player];
+            match outcome {
+                0 => standing.wins += 1,
+                1 => standing.draws += 1,
+                _ => standing.losses += 1,
+            }
+            standing.average_margin += margin as f64;
+        }
+    }
+    for standing in &mut standings {
+        let played = standing.wins + standing.draws + standing.losses;
+        standing.average_margin /= played.max(1) as f64;
+    }
+    (standings, crosstable)
+}
+
+/// Play given games with `play` on at most `concurrency` threads, returning their results in
+/// order. Games get threads of their own rather than rayon workers : they block while waiting
+/// for moves, and parallel searches need the workers of the global pool to make progress.
+pub fn play_games<G, R, F>(games: Vec<G>, concurrency: usize, play: F) -> Vec<R>
+where
+    G: Send,
+    R: Send,
+    F: Fn(G) -> R + Sync,
+{
+    let games = Mutex::new(games.into_iter().enumerate());
+    let results = Mutex::new(Vec::new());
+    thread::scope(|scope| {
+        for _ in 0..concurrency.max(1) {
+            scope.spawn(|| loop {
+                // release the lock before playing
+                let next = games.lock().unwrap().next();
+                match next {
+                    Some((index, game)) => {
+                        let result = play(game);
+                        results.lock().unwrap().push((index, result));
+                    }
+                    None => break,
+                }
+            });
+        }
+    });
+    let mut results = results.into_inner().unwrap();
+    results.sort_unstable_by_key(|&(index, _)| index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
@@ -0,0 +1,58 @@
+//! Zobrist keys : each (player, position) pair gets a random 64 bits key.
+//! The hash of a configuration is the xor of the keys of all its blobs (and of the player key
+//! if blue is to play), so that transposed positions get the same hash. Movements change few
+//! blobs : configurations update their hash with the keys of these blobs only.
+use super::positions::{Positions, MAX_CELLS};
+
+/// One key per player and per cell.
+static BLOB_KEYS: [[u64; MAX_CELLS]; 2] = generate_keys();
+
+/// Key added when blue is to play.
+const PLAYER_KEY: u64 = split_mix(0x5EED).1;
+
+/// SplitMix64 generator step : return new state and generated number.
+pub(crate) const fn split_mix(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (state, z ^ (z >> 31))
+}
+
+const fn generate_keys() -> [[u64; MAX_CELLS]; 2] {
+    let mut keys = [[0; MAX_CELLS]; 2];
+    let mut state = 0xB10B_3A12;
+    let mut player = 0;
+    while player < 2 {
+        let mut position = 0;
+        while position < MAX_CELLS {
+            let (new_state, key) = split_mix(state);
+            keys[player][position] = key;
+            state = new_state;
+            position += 1;
+        }
+        player += 1;
+    }
+    keys
+}
+
+/// Xor of the keys of given blobs of given player (0 for red, 1 for blue).
+pub fn blobs_key(player: usize, blobs: Positions) -> u64 {
+    blobs.positions().fold(0, |key, position| {
+        key ^ BLOB_KEYS[player][position as usize]
+    })
+}
+
+/// Key of given player to move.
+pub fn player_key(current_player: bool) -> u64 {
+    if current_player {
+        PLAYER_KEY
+    } else {
+        0
+    }
+}
+
+/// Hash given blobs with given player to move.
+pub fn hash(blobs: &[Positions; 2], current_player: bool) -> u64 {
+    player_key(current_player) ^ blobs_key(0, blobs[0]) ^ blobs_key(1, blobs[1])
+}
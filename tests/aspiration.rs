@@ -0,0 +1,70 @@
+//! Aspiration windows change how iterations are searched, never what they find.
+use std::time::Duration;
+
+use blobwar::board::Board;
+use blobwar::clock::TimeControl;
+use blobwar::strategy::iterative::IterativeStrategy;
+use blobwar::strategy::{
+    aspiration, deepening, AlphaBeta, AspirationStats, IterativeDeepening, Pvs, Score,
+    SearchControl, Strategy, MATE,
+};
+
+#[test]
+fn far_values_take_few_re_searches() {
+    for &real in &[MATE - 3, -MATE + 3] {
+        let mut stats = AspirationStats::default();
+        let mut searches = 0;
+        // fail hard search of a position worth `real`
+        let result = aspiration(Some(0), &mut stats, |alpha, beta| {
+            searches += 1;
+            Some((real.clamp(alpha, beta), None))
+        });
+        assert_eq!(result, Some((real, None)));
+        assert_eq!(searches, 3);
+        assert_eq!(stats.re_searches(), 2);
+    }
+}
+
+#[test]
+fn aspiration_keeps_full_window_values() {
+    let board = Board::load("standard").unwrap();
+    let state = board.initial_configuration();
+    for &strategy in &[IterativeStrategy::AlphaBeta, IterativeStrategy::Pvs] {
+        let mut values: Vec<(u8, Score)> = Vec::new();
+        let mut stats = AspirationStats::default();
+        let control = SearchControl::default();
+        deepening(strategy, &state, &control, 4, &mut stats, |iteration| {
+            values.push((iteration.depth, iteration.value));
+            true
+        });
+        assert_eq!(values.len(), 4);
+        for (depth, value) in values {
+            let control = SearchControl::default();
+            let expected = match strategy {
+                IterativeStrategy::Pvs => Pvs::new(depth).search(&state, &control),
+                _ => AlphaBeta::new(depth).search(&state, &control),
+            };
+            assert_eq!(
+                expected.map(|(value, _)| value),
+                Some(value),
+                "{:?}",
+                strategy
+            );
+        }
+        assert_eq!(stats.searches, 3);
+    }
+}
+
+#[test]
+fn clock_budgets_are_displayed() {
+    let mut player = IterativeDeepening::new(IterativeStrategy::AlphaBeta).duration(250);
+    assert!(player.to_string().contains("250ms"));
+    player.set_clock(TimeControl::PerMove(Duration::from_secs(2)).clock());
+    let budget = TimeControl::PerMove(Duration::from_secs(2))
+        .clock()
+        .budget()
+        .unwrap();
+    assert!(player
+        .to_string()
+        .contains(&format!("{}ms from the clock", budget.as_millis())));
+}
@@ -0,0 +1,60 @@
+//! Boards need two columns and two rows so that players start on different corners, and map
+//! files are checked cell by cell.
+use blobwar::board::Board;
+use blobwar::error::BlobwarError;
+
+#[test]
+fn narrow_boards_are_rejected() {
+    for map in &["....\n", ".\n.\n.\n", "r\nb\n"] {
+        assert!(
+            matches!(
+                Board::parse_map(map),
+                Err(BlobwarError::BoardTooSmall { .. })
+            ),
+            "{:?}",
+            map
+        );
+    }
+    assert!(matches!(
+        Board::from_fen("x/o x"),
+        Err(BlobwarError::BoardTooSmall {
+            width: 1,
+            height: 2
+        })
+    ));
+    assert!(matches!(
+        Board::new(0, 0, Default::default()),
+        Err(BlobwarError::BoardTooSmall { .. })
+    ));
+}
+
+#[test]
+fn smallest_boards_have_distinct_corners() {
+    let board = Board::parse_map("..\n..\n").unwrap();
+    let [red, blue] = board.starting_blobs;
+    assert_eq!(red.len(), 2);
+    assert_eq!(blue.len(), 2);
+    assert!(red.intersection_with(blue).is_empty());
+}
+
+#[test]
+fn map_errors_give_file_locations() {
+    // blank lines are skipped but still counted
+    let board = Board::parse_map("\n..x.\n\n. O.\n").unwrap();
+    assert_eq!((board.width, board.height), (4, 2));
+    assert_eq!(board.holes.len(), 1);
+    match Board::parse_map("....\n\n..?.\n") {
+        Err(BlobwarError::InvalidCell {
+            character,
+            location,
+        }) => assert_eq!((character, location.line, location.column), ('?', 3, 3)),
+        other => panic!("{:?}", other.map(|_| ())),
+    }
+    // the red corner is a hole
+    match Board::parse_map("\n\n..\n.x\n") {
+        Err(BlobwarError::OverlappingCells { location }) => {
+            assert_eq!((location.line, location.column), (4, 2))
+        }
+        other => panic!("{:?}", other.map(|_| ())),
+    }
+}
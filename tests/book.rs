@@ -0,0 +1,72 @@
+//! Opening books answer symmetric positions with symmetric moves, and only on their board.
+use blobwar::board::Board;
+use blobwar::book::OpeningBook;
+use blobwar::configuration::Movement;
+use blobwar::strategy::{BookStrategy, Greedy, Strategy};
+use blobwar::symmetry::Transform;
+
+#[test]
+fn symmetric_positions_share_their_entry() {
+    let board = Board::default();
+    let start = board.initial_configuration();
+    // red duplicates right of its top left corner, or below it
+    let state = start.play(&Movement::Duplicate(1));
+    let mirrored = state.transformed(Transform::FlipDiagonal);
+    assert!(mirrored == start.play(&Movement::Duplicate(8)));
+    let mut book = OpeningBook::new(&board);
+    book.insert(&state, Movement::Duplicate(6), -1, 3);
+    assert_eq!(book.len(), 1);
+    assert_eq!(OpeningBook::key(&state), OpeningBook::key(&mirrored));
+    let entry = book.probe(&state).unwrap();
+    assert_eq!(
+        (entry.movement, entry.value, entry.depth),
+        (Movement::Duplicate(6), -1, 3)
+    );
+    let entry = book.probe(&mirrored).unwrap();
+    let expected = Movement::Duplicate(6).transform(Transform::FlipDiagonal, &board);
+    assert_eq!(entry.movement, expected);
+    assert!(mirrored.check_move(&entry.movement));
+    // positions out of the book
+    assert!(book.probe(&start).is_none());
+}
+
+#[test]
+fn books_only_apply_to_their_board() {
+    let board = Board::default();
+    let state = board.initial_configuration();
+    let mut book = OpeningBook::new(&board);
+    book.insert(&state, Movement::Duplicate(1), 0, 1);
+    let other = Board::load("cross").unwrap();
+    assert!(!book.matches(&other));
+    assert!(book.probe(&other.initial_configuration()).is_none());
+}
+
+#[test]
+fn books_are_saved_and_loaded() {
+    let board = Board::default();
+    let state = board.initial_configuration();
+    let mut book = OpeningBook::new(&board).board_name("default");
+    book.insert(&state, Movement::Duplicate(1), 0, 4);
+    let path = std::env::temp_dir().join(format!("blobwar-book-{}.json", std::process::id()));
+    book.save(&path).unwrap();
+    let loaded = OpeningBook::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, book);
+}
+
+#[test]
+fn book_strategies_fall_back_out_of_the_book() {
+    let board = Board::default();
+    let mut state = board.initial_configuration();
+    let mut book = OpeningBook::new(&board);
+    book.insert(&state, Movement::Duplicate(9), 0, 1);
+    let mut player = BookStrategy::new(book, Greedy());
+    assert_eq!(
+        player.compute_next_move(&state),
+        Some(Movement::Duplicate(9))
+    );
+    state.apply_movement(&Movement::Duplicate(9));
+    let movement = player.compute_next_move(&state).unwrap();
+    assert_eq!(Some(movement), Greedy().compute_next_move(&state));
+    assert_eq!(player.hits(), 1);
+}
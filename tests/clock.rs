@@ -0,0 +1,87 @@
+//! Time controls are parsed from their textual form and clocks never overflow.
+use std::time::Duration;
+
+use blobwar::board::Board;
+use blobwar::clock::{Clock, TimeControl};
+use blobwar::configuration::Configuration;
+use blobwar::error::BlobwarError;
+use blobwar::strategy::{IterativeDeepening, IterativeStrategy, Strategy};
+
+#[test]
+fn time_controls_are_parsed() {
+    assert_eq!("-".parse::<TimeControl>().unwrap(), TimeControl::Unlimited);
+    assert_eq!(
+        "300+2".parse::<TimeControl>().unwrap(),
+        TimeControl::Fischer {
+            base: Duration::from_secs(300),
+            increment: Duration::from_secs(2),
+        }
+    );
+    assert_eq!(
+        "0.5/move".parse::<TimeControl>().unwrap(),
+        TimeControl::PerMove(Duration::from_millis(500))
+    );
+}
+
+#[test]
+fn out_of_range_time_controls_are_rejected() {
+    for time_control in &[
+        "1e30",
+        "1e300+1",
+        "10/move+",
+        "-1",
+        "inf",
+        "NaN/move",
+        "1e19",
+        "1e19/move",
+        "1e19+1e19",
+        "1+1e19",
+        "86401",
+    ] {
+        assert!(
+            matches!(
+                time_control.parse::<TimeControl>(),
+                Err(BlobwarError::InvalidTimeControl(_))
+            ),
+            "{}",
+            time_control
+        );
+    }
+}
+
+#[test]
+fn longest_time_controls_are_accepted() {
+    assert_eq!(
+        "86400/move".parse::<TimeControl>().unwrap(),
+        TimeControl::PerMove(Duration::from_secs(86_400))
+    );
+}
+
+#[test]
+fn clocks_do_not_overflow() {
+    let time_control = TimeControl::Fischer {
+        base: Duration::MAX,
+        increment: Duration::MAX,
+    };
+    let mut clock = time_control.clock();
+    assert!(clock.budget().unwrap() <= Duration::MAX);
+    assert!(time_control.charge(&mut clock, Duration::from_secs(1)));
+    assert_eq!(clock.remaining, Some(Duration::MAX));
+    // the whole clock spent, then given back
+    assert!(time_control.charge(&mut clock, Duration::MAX));
+    assert_eq!(clock.remaining, Some(Duration::MAX));
+}
+
+#[test]
+fn endless_budgets_do_not_overflow_deadlines() {
+    // the game ends on the next move : the search stops at once whatever the budget
+    let board = Board::from_fen("x1o/3 x").unwrap();
+    let state = Configuration::from_fen("x1o/3 x", &board).unwrap();
+    let mut strategy = IterativeDeepening::new(IterativeStrategy::AlphaBeta);
+    strategy.set_clock(Clock {
+        remaining: Some(Duration::MAX),
+        increment: Duration::MAX,
+        moves_to_go: Some(1),
+    });
+    assert!(strategy.compute_next_move(&state).is_some());
+}
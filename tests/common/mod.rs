@@ -0,0 +1,23 @@
+//! Fixtures shared by integration tests.
+// every test crate only uses some of them
+#![allow(dead_code)]
+use blobwar::board::Board;
+use blobwar::configuration::Configuration;
+use blobwar::strategy::{Greedy, Strategy};
+
+/// Play the greedy movement of given configuration, or pass if there is none.
+pub fn play_greedy(state: &mut Configuration) {
+    match Greedy().compute_next_move(state) {
+        Some(movement) => state.apply_movement(&movement),
+        None => *state = state.skip_play(),
+    }
+}
+
+/// Configuration after given number of greedy plies from the start of given board.
+pub fn greedy_plies(board: &Board, plies: usize) -> Configuration<'_> {
+    let mut state = board.initial_configuration();
+    for _ in 0..plies {
+        play_greedy(&mut state);
+    }
+    state
+}
@@ -0,0 +1,38 @@
+//! Configurations are displayed with the file names of algebraic notation, in colours on
+//! demand only.
+use blobwar::board::Board;
+
+#[test]
+fn narrow_boards_have_one_line_of_files() {
+    let board = Board::default();
+    let display = board.initial_configuration().to_string();
+    assert_eq!(display.lines().nth(1), Some("  abcdefgh "));
+}
+
+#[test]
+fn wide_boards_have_files_of_several_letters() {
+    let board = Board::new(28, 2, Default::default()).unwrap();
+    let display = board.initial_configuration().to_string();
+    let lines: Vec<&str> = display.lines().skip(1).take(2).collect();
+    assert_eq!(
+        lines,
+        vec![
+            format!("  {}aa ", " ".repeat(26)),
+            format!("  {}ab ", "abcdefghijklmnopqrstuvwxyz"),
+        ]
+    );
+    assert_eq!(board.square(27), "ab2");
+}
+
+#[test]
+fn only_the_alternate_form_is_coloured() {
+    let board = Board::from_fen("x-/1o x").unwrap();
+    let state = blobwar::configuration::Configuration::from_fen("x-/1o x", &board).unwrap();
+    let plain = state.to_string();
+    assert!(!plain.contains('\x1b'));
+    assert!(plain.contains("|x-|"));
+    assert!(plain.contains("| o|"));
+    let coloured = format!("{:#}", state);
+    assert!(coloured.contains("\x1b[31mx\x1b[0m-"));
+    assert!(coloured.contains("\x1b[36mo\x1b[0m"));
+}
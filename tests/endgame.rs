@@ -0,0 +1,270 @@
+//! Positions solved by the endgame solver get the same values, whatever the search reaching them.
+use blobwar::board::Board;
+use blobwar::configuration::Configuration;
+use blobwar::strategy::{AlphaBeta, AlphaBetaPar, Endgame, Outcome, Pvs, SearchControl};
+
+mod common;
+use common::play_greedy;
+
+/// Empty cells of solved positions.
+const THRESHOLD: usize = 4;
+
+/// Endgames the solver proves with default settings, on given boards.
+const ENDGAMES: [(&str, &str); 3] = [
+    (
+        "island",
+        "ooxooxxx/x----xxo/1-xxo-xx/o-ooxo-o/x-oooo-x/xo-xxo-x/xxx----o/1xoooooo x",
+    ),
+    (
+        "path",
+        "x--ox--1/-x-ox-o-/-o----x-/--xoxo--/--ooxx--/-x----x-/-x-1o-x-/o--oo--x x",
+    ),
+    (
+        "path",
+        "x--ox--1/-x-ox-1-/-o----x-/--xoxo--/--ooxx--/-o----x-/-x-1o-x-/o--oo--x x",
+    ),
+];
+
+/// Play greedily on given board until at most `THRESHOLD` empty cells remain.
+fn endgame(board: &Board) -> Configuration<'_> {
+    let mut state = board.initial_configuration();
+    while state.empty_cells().nth(THRESHOLD).is_some() && !state.game_over() {
+        play_greedy(&mut state);
+    }
+    state
+}
+
+#[test]
+fn solved_values_do_not_depend_on_the_search() {
+    for &(name, fen) in ENDGAMES.iter() {
+        let board = Board::load(name).unwrap();
+        let state = Configuration::from_fen(fen, &board).unwrap();
+        let control = SearchControl::default();
+        let (value, _) = AlphaBeta::new(1)
+            .endgame(THRESHOLD)
+            .search(&state, &control)
+            .unwrap();
+        assert!(Outcome::of(value).game_over(), "{}", fen);
+        assert!(!control.horizon_reached(), "{}", fen);
+        for depth in 2..4 {
+            let alpha_beta = AlphaBeta::new(depth)
+                .endgame(THRESHOLD)
+                .search(&state, &control);
+            let parallel = AlphaBetaPar::new(depth)
+                .endgame(THRESHOLD)
+                .search(&state, &control);
+            let pvs = Pvs::new(depth).endgame(THRESHOLD).search(&state, &control);
+            assert_eq!(alpha_beta.map(|(value, _)| value), Some(value), "{}", fen);
+            assert_eq!(parallel.map(|(value, _)| value), Some(value), "{}", fen);
+            assert_eq!(pvs.map(|(value, _)| value), Some(value), "{}", fen);
+        }
+        // a window around the value does not change it
+        let windowed = AlphaBeta::new(2).endgame(THRESHOLD).search_window(
+            &state,
+            &control,
+            value - 1,
+            value + 1,
+        );
+        assert_eq!(windowed.map(|(value, _)| value), Some(value), "{}", fen);
+    }
+}
+
+#[test]
+fn solutions_agree_with_searches() {
+    for &(name, fen) in ENDGAMES.iter() {
+        let board = Board::load(name).unwrap();
+        let state = Configuration::from_fen(fen, &board).unwrap();
+        let solution = Endgame::new(THRESHOLD)
+            .solve(&state, &SearchControl::default())
+            .unwrap();
+        assert!(solution.exact, "{}", fen);
+        let (value, _) = AlphaBeta::new(1)
+            .endgame(THRESHOLD)
+            .search(&state, &SearchControl::default())
+            .unwrap();
+        let expected = match Outcome::of(value) {
+            Outcome::Win(_) => 1,
+            Outcome::Loss(_) => -1,
+            Outcome::Evaluation(_) => 0,
+        };
+        assert_eq!(solution.margin.signum(), expected, "{}", fen);
+    }
+}
+
+#[test]
+fn default_searches_solve_near_final_positions() {
+    // the board only fills up after three plies, beyond the search depth
+    let board = Board::from_fen("xx1/oo1 o").unwrap();
+    let state = Configuration::from_fen("xx1/oo1 o", &board).unwrap();
+    let searches = [
+        AlphaBeta::new(1).search(&state, &SearchControl::default()),
+        AlphaBetaPar::new(1).search(&state, &SearchControl::default()),
+        Pvs::new(1).search(&state, &SearchControl::default()),
+    ];
+    for search in searches.iter() {
+        let (value, _) = search.unwrap();
+        assert_eq!(Outcome::of(value), Outcome::Win(3));
+    }
+    // unless disabled
+    let control = SearchControl::default();
+    let (value, _) = AlphaBeta::new(1)
+        .endgame(0)
+        .search(&state, &control)
+        .unwrap();
+    assert_eq!(Outcome::of(value), Outcome::Evaluation(value));
+    assert!(control.horizon_reached());
+}
+
+#[test]
+fn lines_of_jumps_are_solved() {
+    // red jumps and blue has to pass before red can fill the board
+    let board = Board::from_fen("x-1-o/x---- x").unwrap();
+    let state = Configuration::from_fen("x-1-o/x---- x", &board).unwrap();
+    let control = SearchControl::default();
+    let (value, _) = AlphaBeta::new(1)
+        .endgame(THRESHOLD)
+        .search(&state, &control)
+        .unwrap();
+    assert_eq!(Outcome::of(value), Outcome::Win(3));
+    assert!(!control.horizon_reached());
+    let control = SearchControl::default();
+    let (value, _) = Pvs::new(1)
+        .endgame(THRESHOLD)
+        .search(&state, &control)
+        .unwrap();
+    assert_eq!(Outcome::of(value), Outcome::Win(3));
+    assert!(!control.horizon_reached());
+    let solution = Endgame::new(THRESHOLD)
+        .solve(&state, &SearchControl::default())
+        .unwrap();
+    assert_eq!((solution.margin, solution.plies), (2, 3));
+    assert!(solution.exact);
+}
+
+#[test]
+fn evaluated_cut_lines_are_not_games_over() {
+    let board = Board::from_fen("x-1-o/x---- x").unwrap();
+    let state = Configuration::from_fen("x-1-o/x---- x", &board).unwrap();
+    let control = SearchControl::default();
+    let solution = Endgame::new(THRESHOLD)
+        .jump_limit(1)
+        .solve(&state, &control)
+        .unwrap();
+    assert!(!solution.exact);
+    assert_eq!(solution.plies, 0);
+    assert!(control.horizon_reached());
+}
+
+#[test]
+fn repetitions_are_draws() {
+    // blobs can only jump between cells whose neighbours are all holes : the game never ends
+    let board = Board::from_fen("x-1-o-1/------- x").unwrap();
+    let state = Configuration::from_fen("x-1-o-1/------- x", &board).unwrap();
+    let solution = Endgame::new(THRESHOLD)
+        .solve(&state, &SearchControl::default())
+        .unwrap();
+    assert_eq!((solution.margin, solution.plies), (0, 0));
+    assert!(solution.exact);
+    let control = SearchControl::default();
+    let (value, _) = AlphaBeta::new(1)
+        .endgame(THRESHOLD)
+        .search(&state, &control)
+        .unwrap();
+    assert_eq!(value, 0);
+    assert!(!control.horizon_reached());
+}
+
+#[test]
+fn unproven_positions_are_searched() {
+    let board = Board::load("island").unwrap();
+    let state = endgame(&board);
+    let solver = Endgame::new(THRESHOLD).proof_nodes(100);
+    assert_eq!(solver.solve(&state, &SearchControl::default()), None);
+    // the search falls back to evaluations instead of failing
+    let control = SearchControl::default();
+    let (value, _) = AlphaBeta::new(1)
+        .endgame(THRESHOLD)
+        .search(&state, &control)
+        .unwrap();
+    assert_eq!(Outcome::of(value), Outcome::Evaluation(value));
+}
+
+#[test]
+fn solved_values_do_not_depend_on_the_ply() {
+    for &(name, fen) in ENDGAMES.iter() {
+        let board = Board::load(name).unwrap();
+        let state = Configuration::from_fen(fen, &board).unwrap();
+        // the solver keeps its table : children are first found one ply deeper
+        let solver = Endgame::new(THRESHOLD);
+        solver.solve(&state, &SearchControl::default()).unwrap();
+        for movement in state.movements().filter(|m| state.check_move(m)) {
+            let child = state.play(&movement);
+            let fresh = Endgame::new(THRESHOLD).solve(&child, &SearchControl::default());
+            let shared = solver.solve(&child, &SearchControl::default());
+            // equal moves may be found in another order
+            if let (Some(fresh), Some(shared)) = (fresh, shared) {
+                assert_eq!(
+                    (shared.margin, shared.plies),
+                    (fresh.margin, fresh.plies),
+                    "{}",
+                    fen
+                );
+            }
+        }
+        // same within alpha - beta searches, whose solver is kept between searches
+        let (value, _) = AlphaBeta::new(1)
+            .endgame(THRESHOLD)
+            .search(&state, &SearchControl::default())
+            .unwrap();
+        let mut searcher = AlphaBeta::new(2).endgame(THRESHOLD);
+        for _ in 0..2 {
+            let (again, _) = searcher.search(&state, &SearchControl::default()).unwrap();
+            assert_eq!(again, value, "{}", fen);
+        }
+    }
+}
+
+#[test]
+fn default_settings_solve_exact_margins() {
+    // (board, position, final margin, plies until the end of the game)
+    let positions = [
+        // red duplicates below the blue pair, wiping out blue
+        (None, "x1oo/xx1o x", 7, 1),
+        // red jumps next to blue and takes it
+        (None, "x1-/1-1/-1o x", 2, 1),
+        // the board only fills up after three plies
+        (None, "xx1/oo1 o", 2, 3),
+        // red opens with a jump
+        (Some("path"), ENDGAMES[1].1, 4, 4),
+        (Some("island"), ENDGAMES[0].1, 2, 3),
+    ];
+    for &(name, fen, margin, plies) in positions.iter() {
+        let board = match name {
+            Some(name) => Board::load(name).unwrap(),
+            None => Board::from_fen(fen).unwrap(),
+        };
+        let mut state = Configuration::from_fen(fen, &board).unwrap();
+        let solver = Endgame::new(THRESHOLD);
+        let solution = solver.solve(&state, &SearchControl::default()).unwrap();
+        assert!(solution.exact, "{}", fen);
+        assert_eq!(
+            (solution.margin, solution.plies),
+            (margin, plies),
+            "{}",
+            fen
+        );
+        // the margin is reached by playing solved moves until the end of the game
+        let mut sign = 1;
+        while !state.game_over() {
+            let next = solver.solve(&state, &SearchControl::default()).unwrap();
+            assert!(next.exact, "{}", fen);
+            assert_eq!(next.margin * sign, margin, "{}", fen);
+            state = match next.movement {
+                Some(movement) => state.play(&movement),
+                None => state.skip_play(),
+            };
+            sign = -sign;
+        }
+        assert_eq!(-state.value() as i32 * sign, margin, "{}", fen);
+    }
+}
@@ -0,0 +1,83 @@
+//! Evaluators value configurations for the player who just played.
+use blobwar::board::Board;
+use blobwar::configuration::Configuration;
+use blobwar::error::BlobwarError;
+use blobwar::strategy::{Evaluator, Frontier, Material, Mobility, Score, Weighted};
+
+/// Values of given FEN by material, mobility, frontier and default weighted evaluators.
+fn values(fen: &str) -> [Score; 4] {
+    let board = Board::from_fen(fen).unwrap();
+    let state = Configuration::from_fen(fen, &board).unwrap();
+    [
+        Material.evaluate(&state),
+        Mobility.evaluate(&state),
+        Frontier.evaluate(&state),
+        Weighted::default().evaluate(&state),
+    ]
+}
+
+#[test]
+fn evaluators_count_what_they_say() {
+    // red just played : 4 stable blobs against 1, nobody can move
+    assert_eq!(values("xx-/xx-/--o o"), [3, 0, 3, 4 * 3 + 2 * 3]);
+    // red just played : 3 duplicates against none, no stable blob
+    assert_eq!(values("x1/2 o"), [1, 3, 0, 4 + 3]);
+}
+
+#[test]
+fn evaluators_are_antisymmetric() {
+    let board = Board::load("standard").unwrap();
+    let mut state = board.initial_configuration();
+    let weighted = Weighted::default();
+    let evaluators: [&dyn Evaluator; 4] = [&Material, &Mobility, &Frontier, &weighted];
+    for _ in 0..10 {
+        for evaluator in evaluators.iter() {
+            assert_eq!(
+                evaluator.evaluate(&state.skip_play()),
+                -evaluator.evaluate(&state)
+            );
+        }
+        let movement = state.movements().next().unwrap();
+        state.apply_movement(&movement);
+    }
+}
+
+#[test]
+fn weights_are_loaded() {
+    let path = std::env::temp_dir().join(format!("blobwar-weights-{}.json", std::process::id()));
+    std::fs::write(&path, r#"{"material": 1, "mobility": 0, "frontier": 3}"#).unwrap();
+    let weights = Weighted::load(&path);
+    std::fs::write(&path, r#"{"material": 1}"#).unwrap();
+    let partial = Weighted::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        weights.unwrap(),
+        Weighted {
+            material: 1,
+            mobility: 0,
+            frontier: 3
+        }
+    );
+    assert!(matches!(partial, Err(BlobwarError::Json(_))));
+    assert!(matches!(Weighted::load(&path), Err(BlobwarError::Io(_))));
+}
+
+#[test]
+fn weights_reaching_mate_values_are_rejected() {
+    let path = std::env::temp_dir().join(format!("blobwar-mate-{}.json", std::process::id()));
+    std::fs::write(
+        &path,
+        r#"{"material": 100000, "mobility": 0, "frontier": 0}"#,
+    )
+    .unwrap();
+    let large = Weighted::load(&path);
+    std::fs::write(
+        &path,
+        r#"{"material": 1, "mobility": -1000, "frontier": 1}"#,
+    )
+    .unwrap();
+    let negative = Weighted::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(large, Err(BlobwarError::InvalidWeights(_))));
+    assert!(matches!(negative, Err(BlobwarError::InvalidWeights(_))));
+}
@@ -0,0 +1,109 @@
+//! Strategy specs are parsed, and those with unreasonable durations or depths rejected.
+use blobwar::clock::MAX_DURATION;
+use blobwar::error::BlobwarError;
+use blobwar::strategy::factory::{parse_duration, STRATEGIES};
+use blobwar::strategy::from_spec;
+
+#[test]
+fn durations_are_parsed() {
+    assert_eq!(parse_duration("500ms").unwrap(), 500);
+    assert_eq!(parse_duration("1.5s").unwrap(), 1500);
+    assert_eq!(parse_duration("86400s").unwrap(), MAX_DURATION);
+}
+
+#[test]
+fn invalid_durations_are_rejected() {
+    for duration in &[
+        "-1s",
+        "1e30s",
+        "NaNs",
+        "infs",
+        "-1ms",
+        "86401s",
+        "86400001ms",
+        "0ms",
+        "0s",
+        "0.0001s",
+        "2",
+    ] {
+        assert!(
+            matches!(
+                parse_duration(duration),
+                Err(BlobwarError::InvalidSpec { .. })
+            ),
+            "{}",
+            duration
+        );
+    }
+    assert!(from_spec("id:alphabeta:1e30s").is_err());
+    assert!(from_spec("mcts:-1s").is_err());
+    assert!(from_spec("id:alphabeta:0ms").is_err());
+    assert!(from_spec("mcts:0s").is_err());
+}
+
+#[test]
+fn endgame_option_is_parsed() {
+    assert!(from_spec("alphabeta:3:endgame").is_ok());
+    assert!(from_spec("alphabetapar:3:endgame=4").is_ok());
+    assert!(from_spec("pvs:3:endgame=8").is_ok());
+    for spec in &[
+        "alphabeta:3:endgame=",
+        "pvs:3:log",
+        "alphabeta::endgame",
+        "minmax:3:endgame",
+    ] {
+        assert!(
+            matches!(from_spec(spec), Err(BlobwarError::InvalidSpec { .. })),
+            "{}",
+            spec
+        );
+    }
+}
+
+#[test]
+fn depths_are_checked() {
+    assert!(from_spec("alphabeta:1").is_ok());
+    assert!(from_spec("minmax:255").is_ok());
+    for spec in &[
+        "alphabeta:0",
+        "minmax:0",
+        "pvs:0",
+        "alphabetapar:0:endgame",
+        "pvs:256",
+    ] {
+        match from_spec(spec) {
+            Err(BlobwarError::InvalidSpec { reason, .. }) => {
+                assert_eq!(reason, "depth should be between 1 and 255", "{}", spec)
+            }
+            _ => panic!("{} accepted", spec),
+        }
+    }
+}
+
+#[test]
+fn mcts_budgets_are_checked() {
+    assert!(from_spec("mcts:1").is_ok());
+    assert!(from_spec("mcts:500ms:greedy").is_ok());
+    for spec in &["mcts:0", "mcts:0:greedy"] {
+        match from_spec(spec) {
+            Err(BlobwarError::InvalidSpec { reason, .. }) => {
+                assert_eq!(reason, "budget must be positive", "{}", spec)
+            }
+            _ => panic!("{} accepted", spec),
+        }
+    }
+}
+
+#[test]
+fn unknown_strategies_list_available_ones() {
+    match from_spec("alphabet:3") {
+        Err(error @ BlobwarError::UnknownStrategy { .. }) => {
+            let message = error.to_string();
+            assert!(message.starts_with("unknown strategy \"alphabet\""));
+            for (spec, _) in STRATEGIES {
+                assert!(message.contains(spec), "{}", spec);
+            }
+        }
+        _ => panic!("alphabet accepted"),
+    }
+}
@@ -0,0 +1,86 @@
+//! Configurations survive a trip through FEN, and malformed FENs are rejected.
+use blobwar::board::Board;
+use blobwar::configuration::Configuration;
+use blobwar::error::BlobwarError;
+use blobwar::strategy::{Greedy, Strategy};
+
+#[test]
+fn configurations_round_trip() {
+    for name in &["standard", "cross", "rings", "irregular"] {
+        let board = Board::load(name).unwrap();
+        let mut state = board.initial_configuration();
+        // both players to move, with blobs around the holes
+        for _ in 0..6 {
+            let fen = state.to_fen();
+            let parsed = Configuration::from_fen(&fen, &board).unwrap();
+            assert!(parsed == state, "{} {}", name, fen);
+            assert_eq!(parsed.current_player, state.current_player, "{}", fen);
+            assert_eq!(parsed.to_fen(), fen);
+            assert_eq!(Board::from_fen(&fen).unwrap().holes, board.holes, "{}", fen);
+            match Greedy().compute_next_move(&state) {
+                Some(movement) => state.apply_movement(&movement),
+                None => state = state.skip_play(),
+            }
+        }
+    }
+}
+
+#[test]
+fn side_to_move_is_kept() {
+    let board = Board::from_fen("x1-/1-o x").unwrap();
+    for fen in &["x1-/1-o x", "x1-/1-o o"] {
+        let state = Configuration::from_fen(fen, &board).unwrap();
+        assert_eq!(state.current_player, fen.ends_with('o'));
+        assert_eq!(state.to_fen(), *fen);
+    }
+    let red = Configuration::from_fen("x1-/1-o x", &board).unwrap();
+    assert!(red.skip_play() == Configuration::from_fen("x1-/1-o o", &board).unwrap());
+    // move counters are accepted and ignored
+    let counted = Configuration::from_fen("x1-/1-o o 3 12", &board).unwrap();
+    assert_eq!(counted.to_fen(), "x1-/1-o o");
+}
+
+#[test]
+fn malformed_fens_are_rejected() {
+    let board = Board::from_fen("x1-/1-o x").unwrap();
+    let parse = |fen: &str| Configuration::from_fen(fen, &board).err();
+    // wrong rank lengths
+    assert!(matches!(
+        parse("x1-/2-o x"),
+        Some(BlobwarError::UnevenRows { .. })
+    ));
+    assert!(matches!(
+        parse("x-/1-o x"),
+        Some(BlobwarError::UnevenRows { .. })
+    ));
+    // bad side to move
+    for fen in &["x1-/1-o", "x1-/1-o b", "x1-/1-o xo"] {
+        assert!(
+            matches!(parse(fen), Some(BlobwarError::InvalidPlayer { .. })),
+            "{}",
+            fen
+        );
+    }
+    assert!(matches!(
+        parse("x1-/1-z x"),
+        Some(BlobwarError::InvalidCell { character: 'z', .. })
+    ));
+    assert!(matches!(
+        parse("x1-/1-o x 1 2 3"),
+        Some(BlobwarError::InvalidCounters(_))
+    ));
+    // cells outside of the board
+    assert!(matches!(
+        parse("x1-1/1-o1 x"),
+        Some(BlobwarError::InvalidDimensions(_))
+    ));
+    assert!(matches!(
+        parse("x1-/1-o/3 x"),
+        Some(BlobwarError::InvalidDimensions(_))
+    ));
+    // blobs on the board's holes
+    assert!(matches!(
+        parse("x1x/1-o x"),
+        Some(BlobwarError::OverlappingCells { .. })
+    ));
+}
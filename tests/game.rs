@@ -0,0 +1,131 @@
+//! Matches end with a winner whatever the players do, and are recorded as played.
+use std::fmt;
+
+use blobwar::board::Board;
+use blobwar::configuration::{Configuration, Movement};
+use blobwar::game::play_match;
+use blobwar::pgn::PgnGame;
+use blobwar::record::GameRecord;
+use blobwar::strategy::{AlphaBeta, Greedy, SearchControl, Strategy};
+
+/// Duplicates to the last cell of the board, whatever the configuration.
+struct Cheater();
+
+impl fmt::Display for Cheater {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Cheater")
+    }
+}
+
+impl Strategy for Cheater {
+    fn compute_next_move(&mut self, configuration: &Configuration) -> Option<Movement> {
+        Some(Movement::Duplicate(configuration.board().size() as u8 - 1))
+    }
+}
+
+#[test]
+fn illegal_movements_forfeit() {
+    let board = Board::default();
+    let mut state = board.initial_configuration();
+    let outcome = play_match(&mut state, &mut Greedy(), &mut Cheater(), None);
+    assert_eq!(outcome.illegal_move, Some(true));
+    assert_eq!(outcome.winner, Some(false));
+    assert_eq!(outcome.plies, 1);
+    let record = outcome.record(&board.initial_configuration(), &Greedy(), &Cheater());
+    let game = PgnGame::from_record(&record).unwrap();
+    assert_eq!(game.get("Result"), Some("1-0"));
+    let parsed = &PgnGame::parse_all(&game.to_string()).unwrap()[0];
+    assert_eq!(parsed.to_record().unwrap().illegal_move, Some(true));
+}
+
+/// Passes whatever the configuration.
+struct Passer();
+
+impl fmt::Display for Passer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Passer")
+    }
+}
+
+impl Strategy for Passer {
+    fn compute_next_move(&mut self, _configuration: &Configuration) -> Option<Movement> {
+        None
+    }
+}
+
+#[test]
+fn passing_with_legal_movements_forfeits() {
+    let board = Board::default();
+    let mut state = board.initial_configuration();
+    let outcome = play_match(&mut state, &mut Passer(), &mut Passer(), None);
+    assert_eq!(outcome.illegal_move, Some(false));
+    assert_eq!(outcome.winner, Some(true));
+    assert_eq!(outcome.plies, 0);
+    // players who cannot move may pass : blue is walled in by holes
+    let board = Board::from_fen("x2/---/---/---/--o x").unwrap();
+    let mut state = Configuration::from_fen("x2/---/---/---/--o x", &board).unwrap();
+    let outcome = play_match(&mut state, &mut Greedy(), &mut Passer(), None);
+    assert_eq!(outcome.illegal_move, None);
+    assert_eq!(outcome.passes, 1);
+}
+
+#[test]
+fn records_start_from_the_given_configuration() {
+    let board = Board::default();
+    let mut start = board.initial_configuration();
+    let opening = Greedy().compute_next_move(&start).unwrap();
+    start.apply_movement(&opening);
+    let mut state = start;
+    let outcome = play_match(&mut state, &mut Greedy(), &mut Greedy(), None);
+    let record = outcome.record(&start, &Greedy(), &Greedy());
+    assert!(record.first_player);
+    let rebuilt = record.rebuild_board().unwrap();
+    let configurations = record.configurations(&rebuilt).unwrap();
+    assert_eq!(configurations[0].to_fen(), start.to_fen());
+    assert_eq!(configurations.last().unwrap().to_fen(), state.to_fen());
+    // PGN keeps both through its FEN tag
+    let game = PgnGame::from_record(&record).unwrap();
+    let parsed = &PgnGame::parse_all(&game.to_string()).unwrap()[0];
+    let reloaded = parsed.to_record().unwrap();
+    assert!(reloaded.first_player);
+    assert_eq!(reloaded.starting_blobs, record.starting_blobs);
+    assert_eq!(reloaded.moves.len(), record.moves.len());
+    assert_eq!(reloaded.result, record.result);
+}
+
+#[test]
+fn searched_moves_are_scored() {
+    let board = Board::from_fen("x3/4/4/3o x").unwrap();
+    let start = board.initial_configuration();
+    let mut state = start;
+    let mut red = AlphaBeta::new(2);
+    let outcome = play_match(&mut state, &mut red, &mut Greedy(), None);
+    // searches value the start for red with lower being better : the first move is worth the
+    // opposite
+    let (value, _) = AlphaBeta::new(2)
+        .search(&start, &SearchControl::default())
+        .unwrap();
+    assert_eq!(outcome.moves[0].score, Some(-value));
+    for (ply, recorded) in outcome.moves.iter().enumerate() {
+        // greedy players do not search
+        assert_eq!(recorded.score.is_some(), ply % 2 == 0, "{}", ply);
+    }
+    // PGN dumps annotate them, and keep them when read back
+    let record = outcome.record(&start, &red, &Greedy());
+    let text = PgnGame::from_record(&record).unwrap().to_string();
+    assert!(text.contains(&format!("[%eval {}]", -value)), "{}", text);
+    let reloaded = PgnGame::parse_all(&text).unwrap()[0].to_record().unwrap();
+    let scores = |record: &GameRecord| -> Vec<_> {
+        record.moves.iter().map(|recorded| recorded.score).collect()
+    };
+    assert_eq!(scores(&reloaded), scores(&record));
+}
+
+#[test]
+fn out_of_range_movements_are_illegal() {
+    let board = Board::default();
+    let state = board.initial_configuration();
+    assert!(!state.check_move(&Movement::Jump(200, 2)));
+    assert!(!state.check_move(&Movement::Jump(0, 200)));
+    assert!(!state.check_move(&Movement::Duplicate(200)));
+}
@@ -0,0 +1,119 @@
+//! In-process iterative deepening meets its deadline, and stops early when searching deeper is
+//! useless.
+use std::time::{Duration, Instant};
+
+use blobwar::board::Board;
+use blobwar::configuration::Configuration;
+use blobwar::strategy::{
+    deepening, iterative_deepening, AspirationStats, IterativeDeepening, IterativeStrategy,
+    Outcome, SearchControl, Strategy, MAX_DEPTH,
+};
+
+/// All algorithms iterative deepening can run.
+const STRATEGIES: [IterativeStrategy; 4] = [
+    IterativeStrategy::MinMax,
+    IterativeStrategy::AlphaBeta,
+    IterativeStrategy::AlphaBetaPar,
+    IterativeStrategy::Pvs,
+];
+
+/// Depths of the iterations completed on given configuration under given control.
+fn iterations(
+    strategy: IterativeStrategy,
+    state: &Configuration,
+    control: &SearchControl,
+) -> Vec<u8> {
+    let mut depths = Vec::new();
+    deepening(
+        strategy,
+        state,
+        control,
+        MAX_DEPTH,
+        &mut AspirationStats::default(),
+        |iteration| {
+            depths.push(iteration.depth);
+            true
+        },
+    );
+    depths
+}
+
+#[test]
+fn deadlines_are_met() {
+    let board = Board::default();
+    let state = board.initial_configuration();
+    for &strategy in STRATEGIES.iter() {
+        let start = Instant::now();
+        let movement = iterative_deepening(strategy, &state, start + Duration::from_millis(100));
+        // the search is stopped from within, not waited for
+        assert!(start.elapsed() < Duration::from_secs(2), "{:?}", strategy);
+        assert!(state.check_move(&movement.unwrap()), "{:?}", strategy);
+    }
+}
+
+#[test]
+fn finished_game_trees_return_early() {
+    // red wins on its first move whatever it plays
+    let board = Board::from_fen("x1o/3 x").unwrap();
+    let state = Configuration::from_fen("x1o/3 x", &board).unwrap();
+    for &strategy in STRATEGIES.iter() {
+        let start = Instant::now();
+        let movement = iterative_deepening(strategy, &state, start + Duration::from_secs(60));
+        assert!(start.elapsed() < Duration::from_secs(10), "{:?}", strategy);
+        assert!(state.check_move(&movement.unwrap()));
+        assert_eq!(
+            iterations(strategy, &state, &SearchControl::default()),
+            vec![1]
+        );
+    }
+}
+
+#[test]
+fn proven_wins_return_early() {
+    // red wipes blue out at once, though the game could go on for long
+    let board = Board::from_fen("xo5/7/7/7/7/7/7 x").unwrap();
+    let state = Configuration::from_fen("xo5/7/7/7/7/7/7 x", &board).unwrap();
+    for &strategy in STRATEGIES.iter() {
+        let mut depths = Vec::new();
+        deepening(
+            strategy,
+            &state,
+            &SearchControl::default(),
+            MAX_DEPTH,
+            &mut AspirationStats::default(),
+            |iteration| {
+                assert_eq!(Outcome::of(iteration.value), Outcome::Win(1));
+                depths.push(iteration.depth);
+                true
+            },
+        );
+        assert_eq!(depths, vec![1], "{:?}", strategy);
+    }
+}
+
+#[test]
+fn stopped_searches_complete_no_iteration() {
+    let board = Board::default();
+    let state = board.initial_configuration();
+    let control = SearchControl::default();
+    control.stop();
+    for &strategy in STRATEGIES.iter() {
+        assert!(
+            iterations(strategy, &state, &control).is_empty(),
+            "{:?}",
+            strategy
+        );
+    }
+}
+
+#[test]
+fn strategies_search_in_process() {
+    // no helper binary is needed, even in debug builds
+    let board = Board::default();
+    let state = board.initial_configuration();
+    let mut player = IterativeDeepening::new(IterativeStrategy::AlphaBeta).duration(50);
+    let start = Instant::now();
+    let movement = player.compute_next_move(&state).unwrap();
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert!(state.check_move(&movement));
+}
@@ -0,0 +1,82 @@
+//! Monte Carlo tree search plays legal moves, spends its budget exactly and sees wins.
+use blobwar::board::Board;
+use blobwar::configuration::Configuration;
+use blobwar::strategy::{Budget, Mcts, Rollout, Strategy, DEFAULT_TREES};
+
+#[test]
+fn playouts_budgets_are_spent_exactly() {
+    let board = Board::load("standard").unwrap();
+    let state = board.initial_configuration();
+    let mut mcts = Mcts::new(Budget::Playouts(100)).trees(3);
+    let movement = mcts.compute_next_move(&state).unwrap();
+    assert!(state.check_move(&movement));
+    assert_eq!(mcts.playouts(), 100);
+}
+
+#[test]
+fn budgets_smaller_than_the_trees_are_spent_exactly() {
+    let board = Board::load("standard").unwrap();
+    let state = board.initial_configuration();
+    for budget in 1..=DEFAULT_TREES as u64 {
+        let mut mcts = Mcts::new(Budget::Playouts(budget));
+        let movement = mcts.compute_next_move(&state).unwrap();
+        assert!(state.check_move(&movement));
+        assert_eq!(mcts.playouts(), budget);
+    }
+}
+
+#[test]
+fn seeded_searches_are_reproducible() {
+    let board = Board::load("standard").unwrap();
+    let state = board.initial_configuration();
+    let search = |seed| {
+        Mcts::new(Budget::Playouts(200))
+            .trees(2)
+            .seed(seed)
+            .compute_next_move(&state)
+    };
+    assert_eq!(search(7), search(7));
+}
+
+#[test]
+fn seeded_moves_do_not_depend_on_the_threads() {
+    let board = Board::load("standard").unwrap();
+    let state = board.initial_configuration();
+    let search = |trees: Option<usize>| {
+        let mcts = Mcts::new(Budget::Playouts(200)).seed(11);
+        match trees {
+            Some(trees) => mcts.trees(trees),
+            None => mcts,
+        }
+        .compute_next_move(&state)
+    };
+    let single_thread = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+    for &trees in &[Some(3), None] {
+        assert_eq!(search(trees), single_thread.install(|| search(trees)));
+    }
+    // the default is a fixed number of trees
+    assert_eq!(search(None), search(Some(DEFAULT_TREES)));
+}
+
+#[test]
+fn winning_moves_are_found() {
+    // jumping to c3 or c1 takes both blue blobs
+    let fen = "x3/2oo/4 x";
+    let board = Board::from_fen(fen).unwrap();
+    let state = Configuration::from_fen(fen, &board).unwrap();
+    for &rollout in &[Rollout::Random, Rollout::Greedy] {
+        let movement = Mcts::new(Budget::Playouts(500))
+            .rollout(rollout)
+            .trees(1)
+            .compute_next_move(&state)
+            .unwrap();
+        assert!(
+            state.play(&movement).blobs(true).is_empty(),
+            "{:?}",
+            rollout
+        );
+    }
+}
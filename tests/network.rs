@@ -0,0 +1,73 @@
+//! Misbehaving network clients must not crash the server.
+use blobwar::board::Board;
+use blobwar::game::play_match;
+use blobwar::strategy::{Greedy, NetworkPlayer, Strategy};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+/// Network player connected to a client answering each configuration with given payloads.
+fn player(answers: &'static [&'static str]) -> (NetworkPlayer, thread::JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let client = thread::spawn(move || {
+        let mut stream = TcpStream::connect(address).unwrap();
+        let mut lines = BufReader::new(stream.try_clone().unwrap()).lines();
+        for answer in answers {
+            if lines.next().is_none() {
+                return;
+            }
+            stream.write_all(answer.as_bytes()).unwrap();
+        }
+    });
+    let (connection, _) = listener.accept().unwrap();
+    (NetworkPlayer::new(connection).unwrap(), client)
+}
+
+#[test]
+fn garbage_payload_forfeits() {
+    let board = Board::default();
+    let state = board.initial_configuration();
+    let (mut player, client) = player(&["{\"Duplicate\":9}\n", "garbage\n"]);
+    assert_eq!(
+        player.compute_next_move(&state),
+        Some(blobwar::configuration::Movement::Duplicate(9))
+    );
+    assert_eq!(player.compute_next_move(&state), None);
+    assert!(player.disconnected());
+    assert_eq!(player.compute_next_move(&state), None);
+    client.join().unwrap();
+}
+
+#[test]
+fn closed_connection_forfeits() {
+    let board = Board::default();
+    let state = board.initial_configuration();
+    let (mut player, client) = player(&[]);
+    client.join().unwrap();
+    assert_eq!(player.compute_next_move(&state), None);
+    assert!(player.disconnected());
+}
+
+#[test]
+fn out_of_range_movement_is_illegal() {
+    let board = Board::default();
+    let mut state = board.initial_configuration();
+    let (mut player, client) = player(&["{\"Duplicate\":200}\n"]);
+    let outcome = play_match(&mut state, &mut player, &mut Greedy(), None);
+    assert_eq!(outcome.illegal_move, Some(false));
+    assert_eq!(outcome.winner, Some(true));
+    assert!(!player.disconnected());
+    client.join().unwrap();
+}
+
+#[test]
+fn disconnected_clients_lose() {
+    let board = Board::default();
+    let mut state = board.initial_configuration();
+    let (mut player, client) = player(&[]);
+    client.join().unwrap();
+    let outcome = play_match(&mut state, &mut Greedy(), &mut player, None);
+    assert_eq!(outcome.illegal_move, Some(true));
+    assert_eq!(outcome.winner, Some(false));
+}
@@ -0,0 +1,73 @@
+//! Move ordering sorts movements best first without losing any, nor changing search values.
+use blobwar::board::Board;
+use blobwar::configuration::Movement;
+use blobwar::strategy::{AlphaBeta, MoveOrdering, SearchControl};
+
+mod common;
+use common::greedy_plies;
+
+/// Greedy plies played on the standard board before sorting movements.
+const MIDDLE_GAME_PLIES: usize = 6;
+
+/// Sort key of movements.
+fn key(movement: &Movement) -> (u8, Option<u8>) {
+    match *movement {
+        Movement::Duplicate(destination) => (destination, None),
+        Movement::Jump(source, destination) => (destination, Some(source)),
+    }
+}
+
+#[test]
+fn ordering_keeps_all_movements() {
+    let board = Board::load("standard").unwrap();
+    let state = greedy_plies(&board, MIDDLE_GAME_PLIES);
+    let mut unordered = MoveOrdering::disabled().order(&state, None, 0);
+    let mut ordered = MoveOrdering::default().order(&state, None, 0);
+    assert_eq!(unordered, state.movements().collect::<Vec<_>>());
+    unordered.sort_by_key(key);
+    ordered.sort_by_key(key);
+    assert_eq!(ordered, unordered);
+}
+
+#[test]
+fn hash_moves_killers_and_captures_come_first() {
+    let board = Board::load("standard").unwrap();
+    let state = greedy_plies(&board, MIDDLE_GAME_PLIES);
+    let ordering = MoveOrdering::default();
+    let movements = ordering.order(&state, None, 3);
+    // captures first, duplicates before jumps amongst equal captures
+    let keys: Vec<_> = movements
+        .iter()
+        .map(|movement| {
+            (
+                std::cmp::Reverse(state.captures(movement)),
+                matches!(movement, Movement::Jump(_, _)),
+            )
+        })
+        .collect();
+    assert!(keys.windows(2).all(|pair| pair[0] <= pair[1]));
+    // a killer of ply 3 comes first there, after the hash move
+    let (hash_move, killer) = (movements[5], movements[movements.len() - 1]);
+    ordering.record_cutoff(&killer, 3, 2);
+    let reordered = ordering.order(&state, Some(hash_move), 3);
+    assert_eq!(&reordered[..2], &[hash_move, killer]);
+    // killers are kept per ply and forgotten by aging
+    assert_eq!(ordering.order(&state, None, 4)[0], movements[0]);
+    ordering.age();
+    assert_eq!(ordering.order(&state, None, 3)[0], movements[0]);
+}
+
+#[test]
+fn ordering_prunes_more_without_changing_values() {
+    let board = Board::load("standard").unwrap();
+    let state = greedy_plies(&board, MIDDLE_GAME_PLIES);
+    let control = SearchControl::default();
+    let mut ordered = AlphaBeta::new(4);
+    let mut unordered = AlphaBeta::new(4).ordering(false);
+    let value = ordered.search(&state, &control).map(|(value, _)| value);
+    assert_eq!(
+        unordered.search(&state, &control).map(|(value, _)| value),
+        value
+    );
+    assert!(ordered.nodes() < unordered.nodes());
+}
@@ -0,0 +1,82 @@
+//! Parallel alpha - beta must find the same values and moves as the sequential one.
+use blobwar::board::Board;
+use blobwar::configuration::Configuration;
+use blobwar::strategy::{AlphaBeta, AlphaBetaPar, SearchControl};
+use glob::glob;
+
+mod common;
+use common::greedy_plies;
+
+/// Deepest search compared.
+const MAX_DEPTH: u8 = 4;
+
+/// Deepest search compared when tables are reused between depths.
+const REUSED_MAX_DEPTH: u8 = 6;
+
+/// Greedy moves played before comparing searches a second time.
+const OPENING_PLIES: usize = 4;
+
+/// Compare searches of all depths on given configuration, with fresh tables.
+fn compare(name: &str, state: &Configuration) {
+    for depth in 1..=MAX_DEPTH {
+        let control = SearchControl::default();
+        let sequential = AlphaBeta::new(depth).search(state, &control);
+        let parallel = AlphaBetaPar::new(depth).search(state, &control);
+        assert_eq!(sequential, parallel, "board {} at depth {}", name, depth);
+    }
+}
+
+/// Run given test on a pool of several threads, even on small machines.
+fn with_threads<F: FnOnce() + Send>(test: F) {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(4)
+        .build()
+        .unwrap();
+    pool.install(test)
+}
+
+#[test]
+fn parallel_alpha_beta_matches_sequential_search() {
+    with_threads(|| {
+        for path in glob("boards/*").unwrap() {
+            let name = path
+                .unwrap()
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned();
+            let board = Board::load(&name).unwrap();
+            compare(&name, &board.initial_configuration());
+            compare(&name, &greedy_plies(&board, OPENING_PLIES));
+        }
+    });
+}
+
+#[test]
+fn reused_tables_keep_values_and_moves() {
+    with_threads(|| {
+        for name in &["standard", "cross"] {
+            let board = Board::load(name).unwrap();
+            let state = greedy_plies(&board, OPENING_PLIES);
+            // one searcher for all depths : entries of shallower searches are found again
+            let mut parallel = AlphaBetaPar::new(1);
+            let mut reused_sequential = AlphaBeta::new(1);
+            for depth in 1..=REUSED_MAX_DEPTH {
+                parallel.depth = depth;
+                reused_sequential.depth = depth;
+                let control = SearchControl::default();
+                let sequential = AlphaBeta::new(depth).search(&state, &control);
+                let reused = parallel.search(&state, &control);
+                assert_eq!(sequential, reused, "board {} at depth {}", name, depth);
+                // move orderings learnt by both searchers differ, their choices do not
+                let reused_sequential = reused_sequential.search(&state, &control);
+                assert_eq!(
+                    reused_sequential, reused,
+                    "board {} at depth {}",
+                    name, depth
+                );
+            }
+            assert!(parallel.table_stats().hits > 0, "board {}", name);
+        }
+    });
+}
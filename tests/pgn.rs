@@ -0,0 +1,31 @@
+//! Annotations of PGN games are checked when parsing.
+use blobwar::error::BlobwarError;
+use blobwar::pgn::PgnGame;
+
+/// Game of one annotated move.
+fn game(annotation: &str) -> String {
+    format!(
+        "[FEN \"x6o/8/8/8/8/8/8/o6x x\"]\n\n1. b7 {{{}}} *\n",
+        annotation
+    )
+}
+
+#[test]
+fn thinking_times_are_parsed() {
+    let games = PgnGame::parse_all(&game("[%emt 0.5]")).unwrap();
+    assert_eq!(games.len(), 1);
+}
+
+#[test]
+fn invalid_thinking_times_are_rejected() {
+    for annotation in &["[%emt 1e300]", "[%emt -1]", "[%emt inf]", "[%emt NaN]"] {
+        assert!(
+            matches!(
+                PgnGame::parse_all(&game(annotation)),
+                Err(BlobwarError::InvalidGame(_))
+            ),
+            "{}",
+            annotation
+        );
+    }
+}
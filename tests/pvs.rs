@@ -0,0 +1,78 @@
+//! Principal variation search must find the values of alpha - beta, with a playable line.
+use blobwar::board::Board;
+use blobwar::configuration::Configuration;
+use blobwar::strategy::{AlphaBeta, Mobility, Pvs, SearchControl};
+use glob::glob;
+
+mod common;
+use common::greedy_plies;
+
+/// Deepest search compared.
+const MAX_DEPTH: u8 = 4;
+
+/// Compare searches of all depths on given configuration.
+fn compare(name: &str, state: &Configuration) {
+    for depth in 1..=MAX_DEPTH {
+        let control = SearchControl::default();
+        let alpha_beta = AlphaBeta::new(depth).search(state, &control);
+        let mut pvs = Pvs::new(depth);
+        let result = pvs.search(state, &control);
+        assert_eq!(
+            result.map(|(value, _)| value),
+            alpha_beta.map(|(value, _)| value),
+            "board {} at depth {}",
+            name,
+            depth
+        );
+        // the principal variation starts with the best move and can be played
+        let line = pvs.principal_variation();
+        assert_eq!(
+            line.first().copied(),
+            result.and_then(|(_, movement)| movement)
+        );
+        let mut played = *state;
+        for movement in line {
+            assert!(
+                played.check_move(movement),
+                "board {} at depth {}",
+                name,
+                depth
+            );
+            played.apply_movement(movement);
+        }
+    }
+}
+
+#[test]
+fn pvs_matches_alpha_beta() {
+    for path in glob("boards/*").unwrap() {
+        let name = path
+            .unwrap()
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .into_owned();
+        let board = Board::load(&name).unwrap();
+        compare(&name, &board.initial_configuration());
+        compare(&name, &greedy_plies(&board, 4));
+    }
+}
+
+#[test]
+fn pvs_matches_alpha_beta_with_other_evaluators() {
+    let board = Board::load("standard").unwrap();
+    let state = board.initial_configuration();
+    for depth in 1..=3 {
+        let control = SearchControl::default();
+        let alpha_beta = AlphaBeta::new(depth)
+            .evaluator(Mobility)
+            .search(&state, &control);
+        let pvs = Pvs::new(depth).evaluator(Mobility).search(&state, &control);
+        assert_eq!(
+            pvs.map(|(value, _)| value),
+            alpha_beta.map(|(value, _)| value),
+            "depth {}",
+            depth
+        );
+    }
+}
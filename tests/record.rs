@@ -0,0 +1,108 @@
+//! Corrupt game records must be rejected, not crash.
+use std::time::Duration;
+
+use blobwar::board::Board;
+use blobwar::configuration::Movement;
+use blobwar::error::BlobwarError;
+use blobwar::record::GameRecord;
+use serde_json::{json, Value};
+
+/// Record of an empty game on the default board.
+fn record() -> GameRecord {
+    GameRecord::new(&Board::default(), &"red", &"blue")
+}
+
+#[test]
+fn valid_record_rebuilds_its_board() {
+    let board = record().rebuild_board().unwrap();
+    assert_eq!((board.width, board.height), (8, 8));
+}
+
+#[test]
+fn zero_width_is_rejected() {
+    let record = GameRecord {
+        width: 0,
+        ..record()
+    };
+    assert!(matches!(
+        record.rebuild_board(),
+        Err(BlobwarError::EmptyBoard)
+    ));
+}
+
+#[test]
+fn zero_height_is_rejected() {
+    let record = GameRecord {
+        height: 0,
+        ..record()
+    };
+    assert!(matches!(
+        record.rebuild_board(),
+        Err(BlobwarError::EmptyBoard)
+    ));
+}
+
+#[test]
+fn starting_blob_on_hole_is_rejected() {
+    let mut record = record();
+    record.holes = vec![record.starting_blobs[1][0]];
+    assert!(matches!(
+        record.rebuild_board(),
+        Err(BlobwarError::OverlappingCells { .. })
+    ));
+}
+
+#[test]
+fn starting_blobs_on_each_other_are_rejected() {
+    let mut record = record();
+    record.starting_blobs[1].push(record.starting_blobs[0][0]);
+    assert!(matches!(
+        record.rebuild_board(),
+        Err(BlobwarError::OverlappingCells { .. })
+    ));
+}
+
+/// Record of a few turns on the default board : a duplication, a jump and a pass.
+fn played_record() -> GameRecord {
+    let mut record = record();
+    record.push(Some(Movement::Duplicate(9)), Duration::from_millis(10));
+    record.push(Some(Movement::Jump(63, 45)), Duration::from_millis(20));
+    record.push(None, Duration::from_millis(30));
+    record
+}
+
+#[test]
+fn records_are_saved_in_notation_and_loaded() {
+    let record = played_record();
+    let path = std::env::temp_dir().join(format!("blobwar-record-{}.json", std::process::id()));
+    record.save(&path).unwrap();
+    let saved: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let loaded = GameRecord::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let movements: Vec<&Value> = saved["moves"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|recorded| &recorded["movement"])
+        .collect();
+    assert_eq!(movements, [&json!("b7"), &json!("h1f3"), &json!("0000")]);
+    assert_eq!(loaded, record);
+}
+
+#[test]
+fn records_with_json_movements_still_load() {
+    let record = played_record();
+    let mut saved = serde_json::to_value(&record).unwrap();
+    saved["moves"][0]["movement"] = json!({ "Duplicate": 9 });
+    saved["moves"][1]["movement"] = json!({ "Jump": [63, 45] });
+    saved["moves"][2]["movement"] = Value::Null;
+    let loaded: GameRecord = serde_json::from_value(saved).unwrap();
+    assert_eq!(loaded, record);
+}
+
+#[test]
+fn invalid_notation_is_rejected() {
+    let mut saved = serde_json::to_value(played_record()).unwrap();
+    saved["moves"][0]["movement"] = json!("z9");
+    assert!(serde_json::from_value::<GameRecord>(saved).is_err());
+}
@@ -0,0 +1,112 @@
+//! Searching strategies report what they did to choose their move.
+use blobwar::board::Board;
+use blobwar::configuration::Configuration;
+use blobwar::strategy::{
+    AlphaBeta, AlphaBetaPar, Greedy, IterativeDeepening, IterativeStrategy, MinMax, Pvs,
+    SearchControl, SearchStats, Strategy,
+};
+
+/// Play the move of given strategy and return its statistics, checking they describe it.
+fn stats_of(strategy: &mut dyn Strategy, state: &Configuration) -> SearchStats {
+    let movement = strategy.compute_next_move(state);
+    let stats = strategy.search_stats().unwrap();
+    assert!(stats.counters.nodes > 0, "{}", strategy);
+    assert!(stats.counters.evaluations > 0, "{}", strategy);
+    assert!(
+        stats.counters.evaluations <= stats.counters.nodes,
+        "{}",
+        strategy
+    );
+    // the principal variation starts with the chosen move and can be played
+    assert_eq!(
+        stats.principal_variation.first().copied(),
+        movement,
+        "{}",
+        strategy
+    );
+    let mut played = *state;
+    for movement in &stats.principal_variation {
+        assert!(played.check_move(movement), "{}", strategy);
+        played.apply_movement(movement);
+    }
+    stats
+}
+
+#[test]
+fn fixed_depth_searches_report_their_work() {
+    let board = Board::default();
+    let state = board.initial_configuration();
+    let mut strategies: Vec<Box<dyn Strategy>> = vec![
+        Box::new(MinMax::new(3)),
+        Box::new(AlphaBeta::new(3)),
+        Box::new(AlphaBetaPar::new(3)),
+        Box::new(Pvs::new(3)),
+    ];
+    for strategy in &mut strategies {
+        let stats = stats_of(&mut **strategy, &state);
+        assert_eq!(stats.depth, 3, "{}", strategy);
+        assert_eq!(stats.iterations.len(), 1);
+        assert!(stats.value.is_some(), "{}", strategy);
+    }
+    // pruning searches cut, exhaustive ones do not
+    let minmax = stats_of(&mut MinMax::new(3), &state);
+    assert_eq!(minmax.counters.cutoffs, 0);
+    let alpha_beta = stats_of(&mut AlphaBeta::new(3), &state);
+    assert!(alpha_beta.counters.cutoffs > 0);
+    assert!(alpha_beta.counters.nodes < minmax.counters.nodes);
+    assert_eq!(alpha_beta.principal_variation.len(), 3);
+    // all searches agree on the value of the start
+    let (value, _) = AlphaBeta::new(3)
+        .search(&state, &SearchControl::default())
+        .unwrap();
+    assert_eq!(minmax.value, Some(value));
+    assert_eq!(alpha_beta.value, Some(value));
+}
+
+#[test]
+fn statistics_follow_the_last_move() {
+    let board = Board::default();
+    let mut state = board.initial_configuration();
+    let mut alpha_beta = AlphaBeta::new(2);
+    let first = stats_of(&mut alpha_beta, &state);
+    state = state.play(&first.principal_variation[0]);
+    let second = stats_of(&mut alpha_beta, &state);
+    assert_ne!(first.principal_variation, second.principal_variation);
+    // searching again finds the root in the table : nothing to evaluate
+    alpha_beta.compute_next_move(&state);
+    let third = alpha_beta.search_stats().unwrap();
+    assert!(third.counters.table_hits > 0);
+    assert_eq!(third.counters.evaluations, 0);
+    assert_eq!(third.principal_variation, second.principal_variation);
+}
+
+#[test]
+fn iterative_deepening_reports_each_iteration() {
+    let board = Board::default();
+    let state = board.initial_configuration();
+    let mut player = IterativeDeepening::new(IterativeStrategy::AlphaBeta).duration(200);
+    let stats = stats_of(&mut player, &state);
+    // one iteration per depth
+    assert!(stats.depth >= 1);
+    assert_eq!(stats.iterations.len(), stats.depth as usize);
+    assert!(stats.value.is_some());
+    assert!(stats.time() <= std::time::Duration::from_secs(2));
+    let text = stats.to_string();
+    assert!(
+        text.starts_with(&format!("depth {}, ", stats.depth)),
+        "{}",
+        text
+    );
+    assert_eq!(text.matches("ms").count(), stats.iterations.len());
+    let line = stats.principal_variation(&board);
+    assert_eq!(line.split(' ').count(), stats.principal_variation.len());
+}
+
+#[test]
+fn non_searching_strategies_report_nothing() {
+    let board = Board::default();
+    let state = board.initial_configuration();
+    let mut greedy = Greedy();
+    greedy.compute_next_move(&state);
+    assert!(greedy.search_stats().is_none());
+}
@@ -0,0 +1,93 @@
+//! Board symmetries map positions, movements and configurations consistently.
+use blobwar::board::Board;
+use blobwar::symmetry::Transform;
+
+mod common;
+use common::greedy_plies;
+
+/// Greedy plies played before comparing symmetric configurations.
+const MIDDLE_GAME_PLIES: usize = 5;
+
+#[test]
+fn transforms_are_undone_by_their_inverse() {
+    let board = Board::from_fen("5/5/5/5/5 x").unwrap();
+    for &transform in Transform::ALL.iter() {
+        let mut images: Vec<u8> = (0..25)
+            .map(|position| board.transform_position(position, transform))
+            .collect();
+        for (position, &image) in images.iter().enumerate() {
+            assert_eq!(
+                board.transform_position(image, transform.inverse()),
+                position as u8,
+                "{}",
+                transform
+            );
+        }
+        images.sort_unstable();
+        assert_eq!(images, (0..25).collect::<Vec<u8>>(), "{}", transform);
+    }
+    // quarter turns compose into half turns
+    for position in 0..25 {
+        let twice = board.transform_position(
+            board.transform_position(position, Transform::Rotate90),
+            Transform::Rotate90,
+        );
+        assert_eq!(
+            twice,
+            board.transform_position(position, Transform::Rotate180)
+        );
+    }
+}
+
+#[test]
+fn boards_only_keep_their_own_symmetries() {
+    let rectangle = Board::from_fen("3/3 x").unwrap();
+    assert_eq!(rectangle.symmetries.len(), 4);
+    assert!(rectangle.symmetries.iter().all(|t| !t.swaps_axes()));
+    let holed = Board::from_fen("-2/3/3 x").unwrap();
+    assert_eq!(
+        holed.symmetries,
+        vec![Transform::Identity, Transform::FlipDiagonal]
+    );
+    assert_eq!(Board::default().symmetries.len(), 8);
+}
+
+#[test]
+fn movements_commute_with_symmetries() {
+    for name in &["standard", "cross"] {
+        let board = Board::load(name).unwrap();
+        let state = greedy_plies(&board, MIDDLE_GAME_PLIES);
+        for &transform in &board.symmetries {
+            let image = state.transformed(transform);
+            assert_eq!(image.movements().count(), state.movements().count());
+            for movement in state.movements() {
+                let moved = movement.transform(transform, &board);
+                assert!(image.check_move(&moved), "{} {}", name, transform);
+                assert!(
+                    image.play(&moved) == state.play(&movement).transformed(transform),
+                    "{} {}",
+                    name,
+                    transform
+                );
+            }
+            assert_eq!(image.canonical_hash(), state.canonical_hash());
+        }
+    }
+}
+
+#[test]
+fn symmetric_configurations_share_their_canonical_form() {
+    let board = Board::load("standard").unwrap();
+    let state = greedy_plies(&board, MIDDLE_GAME_PLIES);
+    let (canonical, transform) = state.canonical();
+    assert!(board.symmetries.contains(&transform));
+    assert!(canonical == state.transformed(transform));
+    for &transform in &board.symmetries {
+        let image = state.transformed(transform);
+        assert!(image.canonical().0 == canonical, "{}", transform);
+        // canonical forms are fixed points
+        assert!(canonical.canonical().0 == canonical);
+    }
+    // the player to move is part of the position
+    assert_ne!(state.skip_play().canonical_hash(), state.canonical_hash());
+}
@@ -0,0 +1,80 @@
+//! Searches score finished games as wins or losses, play on through passes and tell game overs
+//! from their horizon.
+use blobwar::board::Board;
+use blobwar::configuration::{Configuration, Movement};
+use blobwar::strategy::{
+    AlphaBeta, AlphaBetaPar, MinMax, Outcome, Pvs, Score, SearchControl, MATE,
+};
+
+/// Value and move found by a search.
+type SearchResult = Option<(Score, Option<Movement>)>;
+
+/// Result of every search algorithm at given depth on given configuration, with whether they
+/// reached their horizon. The endgame solver is disabled : searches score games over by
+/// themselves.
+fn searches(state: &Configuration, depth: u8) -> Vec<(SearchResult, bool)> {
+    let run = |search: &dyn Fn(&SearchControl) -> SearchResult| {
+        let control = SearchControl::default();
+        let result = search(&control);
+        (result, control.horizon_reached())
+    };
+    vec![
+        run(&|control| MinMax::new(depth).search(state, control)),
+        run(&|control| AlphaBeta::new(depth).endgame(0).search(state, control)),
+        run(&|control| AlphaBetaPar::new(depth).endgame(0).search(state, control)),
+        run(&|control| Pvs::new(depth).endgame(0).search(state, control)),
+    ]
+}
+
+#[test]
+fn wiping_out_the_adversary_wins() {
+    // red takes the only blue blob right away, or later
+    let board = Board::from_fen("xo5/7/7/7/7/7/7 x").unwrap();
+    let state = Configuration::from_fen("xo5/7/7/7/7/7/7 x", &board).unwrap();
+    for (result, _) in searches(&state, 3) {
+        let (value, movement) = result.unwrap();
+        // the fastest win is preferred
+        assert_eq!(Outcome::of(value), Outcome::Win(1));
+        assert_eq!(value, -(MATE - 1));
+        let next = state.play(&movement.unwrap());
+        assert!(next.game_over());
+        assert!(next.blobs(true).is_empty());
+    }
+    // blue to move wins just as fast
+    let state = Configuration::from_fen("xo5/7/7/7/7/7/7 o", &board).unwrap();
+    for (result, _) in searches(&state, 2) {
+        let (value, _) = result.unwrap();
+        assert_eq!(Outcome::of(value), Outcome::Win(1));
+    }
+}
+
+#[test]
+fn passes_continue_until_the_game_ends() {
+    // blue is walled in by holes : it passes twice while red fills the board
+    let board = Board::from_fen("x2/---/---/---/--o o").unwrap();
+    let state = Configuration::from_fen("x2/---/---/---/--o o", &board).unwrap();
+    for (result, _) in searches(&state, 4) {
+        let (value, movement) = result.unwrap();
+        assert_eq!(movement, None);
+        assert_eq!(Outcome::of(value), Outcome::Loss(4));
+    }
+    // too shallow to see the end : an evaluation, not a game over
+    for (result, horizon) in searches(&state, 2) {
+        let (value, _) = result.unwrap();
+        assert!(!Outcome::of(value).game_over());
+        assert!(horizon);
+    }
+}
+
+#[test]
+fn finished_games_are_not_searched() {
+    let board = Board::from_fen("xx/oo x").unwrap();
+    let state = Configuration::from_fen("xx/oo x", &board).unwrap();
+    assert!(state.game_over());
+    for (result, horizon) in searches(&state, 3) {
+        let (value, movement) = result.unwrap();
+        assert_eq!(movement, None);
+        assert_eq!(value, 0);
+        assert!(!horizon);
+    }
+}
@@ -0,0 +1,116 @@
+//! Tournament standings count every game for both players, and Elo ratings follow scores.
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::Duration;
+
+use blobwar::board::Board;
+use blobwar::game::play_match;
+use blobwar::strategy::from_spec;
+use blobwar::tournament::{fit_elo, play_games, standings, GameResult, BASE_ELO};
+
+/// Game between given players (red first) ending with given numbers of blobs.
+fn game(players: [usize; 2], red_blobs: u8, blue_blobs: u8) -> GameResult {
+    GameResult {
+        board: "standard".to_owned(),
+        red: players[0].to_string(),
+        blue: players[1].to_string(),
+        winner: match red_blobs.cmp(&blue_blobs) {
+            std::cmp::Ordering::Greater => "red",
+            std::cmp::Ordering::Less => "blue",
+            std::cmp::Ordering::Equal => "draw",
+        },
+        red_blobs,
+        blue_blobs,
+        plies: 10,
+        passes: 0,
+        red_time_ms: 0,
+        blue_time_ms: 0,
+        players,
+        game: None,
+    }
+}
+
+#[test]
+fn scores_follow_the_winner() {
+    let won = game([0, 1], 40, 24);
+    assert_eq!(won.score(0), (1.0, 16));
+    assert_eq!(won.score(1), (0.0, -16));
+    let drawn = game([0, 1], 32, 32);
+    assert_eq!(drawn.score(1), (0.5, 0));
+    // forfeits lose whatever the margin
+    let forfeit = GameResult {
+        winner: "blue",
+        ..game([0, 1], 40, 24)
+    };
+    assert_eq!(forfeit.score(0), (0.0, 16));
+    assert_eq!(forfeit.score(1), (1.0, -16));
+}
+
+#[test]
+fn standings_and_crosstable_count_both_sides() {
+    let specs: Vec<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+    let games = vec![
+        game([0, 1], 40, 20),
+        game([1, 0], 30, 30),
+        game([0, 2], 10, 50),
+        game([2, 1], 45, 15),
+    ];
+    let (standings, crosstable) = standings(&specs, &games);
+    let records: Vec<(usize, usize, usize)> = standings
+        .iter()
+        .map(|s| (s.wins, s.draws, s.losses))
+        .collect();
+    assert_eq!(records, vec![(1, 1, 1), (0, 1, 2), (2, 0, 0)]);
+    assert_eq!(standings[0].strategy, "a");
+    assert_eq!(standings[0].average_margin, (20.0 + 0.0 - 40.0) / 3.0);
+    assert_eq!(standings[2].average_margin, 35.0);
+    assert_eq!(crosstable[0][1], [1, 1, 0]);
+    assert_eq!(crosstable[1][0], [0, 1, 1]);
+    assert_eq!(crosstable[0][2], [0, 0, 1]);
+    assert_eq!(crosstable[2][1], [1, 0, 0]);
+    assert_eq!(crosstable[1][2], [0, 0, 1]);
+    assert_eq!(crosstable[0][0], [0, 0, 0]);
+    // Elo ratings rank players by score
+    assert!(standings[2].elo > standings[0].elo);
+    assert!(standings[0].elo > standings[1].elo);
+}
+
+#[test]
+fn elo_stays_finite_and_symmetric() {
+    // no games : everyone is average
+    assert_eq!(fit_elo(2, &[]), vec![BASE_ELO; 2]);
+    // even results keep players level
+    let even = fit_elo(2, &[game([0, 1], 40, 20), game([1, 0], 40, 20)]);
+    assert!((even[0] - BASE_ELO).abs() < 1e-6);
+    assert!((even[1] - BASE_ELO).abs() < 1e-6);
+    // a player winning everything stays finite, thanks to its virtual draw
+    let games: Vec<GameResult> = (0..10).map(|_| game([0, 1], 40, 20)).collect();
+    let ratings = fit_elo(2, &games);
+    assert!(ratings[0].is_finite() && ratings[0] > BASE_ELO + 100.0);
+    assert!((ratings[0] - BASE_ELO + ratings[1] - BASE_ELO).abs() < 1.0);
+}
+
+#[test]
+fn parallel_searches_are_played_in_a_saturated_pool() {
+    // more games than workers in the global pool : games must not take the workers which
+    // parallel iterative deepening searches rely on
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .build_global()
+        .unwrap();
+    let (sender, receiver) = channel();
+    thread::spawn(move || {
+        let board = Board::load("standard").unwrap();
+        let margins = play_games((0..4).collect(), 4, |_| {
+            let mut red = from_spec("id:alphabetapar:20ms").unwrap();
+            let mut blue = from_spec("id:alphabetapar:20ms").unwrap();
+            let mut state = board.initial_configuration();
+            play_match(&mut state, &mut *red, &mut *blue, None).red_margin()
+        });
+        sender.send(margins).unwrap();
+    });
+    let margins = receiver
+        .recv_timeout(Duration::from_secs(60))
+        .expect("games are stuck");
+    assert_eq!(margins.len(), 4);
+}
@@ -0,0 +1,93 @@
+//! Transposition tables give back the entries stored for a key, and nothing for other keys.
+//! Entries cut searches of their depth or shallower ones.
+use blobwar::configuration::Movement;
+use blobwar::strategy::transposition::{Bound, Entry};
+use blobwar::strategy::{SharedTranspositionTable, TranspositionTable};
+
+/// Keys tried, including the one of an empty shared slot.
+const KEYS: [u64; 3] = [0, 1 << 12, 0xDEAD_BEEF_0123_4567];
+
+/// Entries tried, including the one packing to all zeros without an occupied flag.
+fn entries() -> Vec<Entry> {
+    vec![
+        Entry {
+            depth: 0,
+            value: 0,
+            bound: Bound::Exact,
+            best_move: None,
+        },
+        Entry {
+            depth: 7,
+            value: -3,
+            bound: Bound::Lower,
+            best_move: Some(Movement::Jump(2, 16)),
+        },
+        Entry {
+            depth: 255,
+            value: 1_000_000,
+            bound: Bound::Upper,
+            best_move: Some(Movement::Duplicate(48)),
+        },
+    ]
+}
+
+#[test]
+fn shared_tables_read_back_stored_entries() {
+    for &key in &KEYS {
+        for entry in entries() {
+            let table = SharedTranspositionTable::new(4);
+            assert_eq!(table.probe(key), None, "empty table, key {:x}", key);
+            table.store(key, entry);
+            assert_eq!(table.probe(key), Some(entry), "key {:x}", key);
+            assert_eq!(table.get(key), Some(entry), "key {:x}", key);
+            assert_eq!(table.get(key ^ 1 << 60), None, "other key {:x}", key);
+        }
+    }
+}
+
+#[test]
+fn shared_tables_replace_shallow_entries() {
+    let entries = entries();
+    let (shallow, deep) = (entries[0], entries[1]);
+    let table = SharedTranspositionTable::new(4);
+    // empty slots of key 0 are not taken for deeper entries
+    table.store(0, shallow);
+    assert_eq!(table.get(0), Some(shallow));
+    table.store(0, deep);
+    table.store(0, shallow);
+    assert_eq!(table.get(0), Some(deep));
+    let stats = table.stats();
+    assert_eq!((stats.hits, stats.misses, stats.stores), (0, 0, 2));
+}
+
+#[test]
+fn tables_read_back_stored_entries() {
+    for &key in &KEYS {
+        for entry in entries() {
+            let mut table = TranspositionTable::new(4);
+            assert_eq!(table.probe(key), None, "empty table, key {:x}", key);
+            table.store(key, entry);
+            assert_eq!(table.probe(key), Some(entry), "key {:x}", key);
+            assert_eq!(table.get(key ^ 1 << 60), None, "other key {:x}", key);
+        }
+    }
+}
+
+#[test]
+fn deeper_entries_cut_shallower_searches() {
+    let entry = Entry::new(4, 10, 0, 20, None);
+    assert_eq!(entry.bound, Bound::Exact);
+    for &same_depth_only in &[false, true] {
+        assert_eq!(entry.cutoff(4, 0, 20, same_depth_only), Some(10));
+        assert_eq!(entry.cutoff(5, 0, 20, same_depth_only), None);
+    }
+    assert_eq!(entry.cutoff(2, 0, 20, false), Some(10));
+    assert_eq!(entry.cutoff(2, 0, 20, true), None);
+    // bounds only cut windows they are out of
+    let lower = Entry::new(6, 30, 0, 20, None);
+    assert_eq!(lower.cutoff(3, 0, 25, false), Some(30));
+    assert_eq!(lower.cutoff(3, 0, 40, false), None);
+    let upper = Entry::new(6, -5, 0, 20, None);
+    assert_eq!(upper.cutoff(3, -3, 20, false), Some(-5));
+    assert_eq!(upper.cutoff(3, -10, 20, false), None);
+}
@@ -0,0 +1,162 @@
+//! The UAI engine binary answers tournament managers on its standard input and output.
+use std::io::{BufRead, BufReader, Lines, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use blobwar::board::Board;
+use blobwar::configuration::Configuration;
+
+/// Ataxx starting position, as `startpos`.
+const STARTPOS: &str = "x5o/7/7/7/7/7/o5x x";
+
+/// Running engine.
+struct Engine {
+    child: Child,
+    input: ChildStdin,
+    output: Lines<BufReader<ChildStdout>>,
+}
+
+impl Engine {
+    fn start() -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_uai"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let input = child.stdin.take().unwrap();
+        let output = BufReader::new(child.stdout.take().unwrap()).lines();
+        Engine {
+            child,
+            input,
+            output,
+        }
+    }
+
+    fn send(&mut self, command: &str) {
+        writeln!(self.input, "{}", command).unwrap();
+    }
+
+    /// Lines printed up to the first one starting with given prefix, included.
+    fn until(&mut self, prefix: &str) -> Vec<String> {
+        let mut lines = Vec::new();
+        for line in &mut self.output {
+            let line = line.unwrap();
+            let done = line.starts_with(prefix);
+            lines.push(line);
+            if done {
+                return lines;
+            }
+        }
+        panic!("engine exited before {:?} : {:?}", prefix, lines);
+    }
+
+    fn quit(mut self) {
+        self.send("quit");
+        assert!(self.child.wait().unwrap().success());
+    }
+}
+
+/// Best move of the last line of given search output.
+fn best_move(lines: &[String]) -> String {
+    lines
+        .last()
+        .unwrap()
+        .strip_prefix("bestmove ")
+        .unwrap()
+        .to_owned()
+}
+
+#[test]
+fn engines_identify_themselves() {
+    let mut engine = Engine::start();
+    engine.send("uai");
+    let lines = engine.until("uaiok");
+    assert!(lines[0].starts_with("id name blobwar"));
+    assert!(lines
+        .iter()
+        .any(|line| line.starts_with("option name Algorithm")));
+    engine.send("isready");
+    assert_eq!(engine.until("readyok"), vec!["readyok"]);
+    engine.send("frobnicate");
+    engine.send("isready");
+    let lines = engine.until("readyok");
+    assert_eq!(lines[0], "info string unknown command frobnicate");
+    engine.quit();
+}
+
+#[test]
+fn searches_report_iterations_and_their_best_move() {
+    let board = Board::from_fen(STARTPOS).unwrap();
+    let start = Configuration::from_fen(STARTPOS, &board).unwrap();
+    let mut engine = Engine::start();
+    engine.send("uainewgame");
+    engine.send("position startpos");
+    engine.send("go depth 3");
+    let lines = engine.until("bestmove");
+    let infos: Vec<&String> = lines
+        .iter()
+        .filter(|l| l.starts_with("info depth"))
+        .collect();
+    assert_eq!(infos.len(), 3, "{:?}", lines);
+    for (depth, info) in infos.iter().enumerate() {
+        assert!(info.starts_with(&format!("info depth {} score cp ", depth + 1)));
+        assert!(
+            info.contains(" nodes ") && info.contains(" pv "),
+            "{}",
+            info
+        );
+    }
+    let movement = best_move(&lines);
+    let pv = infos[2].split(" pv ").nth(1).unwrap();
+    assert_eq!(pv.split(' ').next(), Some(movement.as_str()));
+    let parsed = start.parse_move(&movement).unwrap().unwrap();
+    assert!(start.check_move(&parsed));
+
+    // positions follow played moves : blue answers
+    engine.send(&format!("position startpos moves {}", movement));
+    engine.send("go depth 1");
+    let lines = engine.until("bestmove");
+    let after = start.play(&parsed);
+    let answer = after.parse_move(&best_move(&lines)).unwrap().unwrap();
+    assert!(after.check_move(&answer));
+    engine.quit();
+}
+
+#[test]
+fn wins_are_reported_as_mates() {
+    let mut engine = Engine::start();
+    engine.send("setoption name Algorithm value alphabeta");
+    engine.send("position fen xo5/7/7/7/7/7/7 x 0 1");
+    engine.send("go depth 5");
+    let lines = engine.until("bestmove");
+    assert!(
+        lines[0].starts_with("info depth 1 score mate 1 "),
+        "{:?}",
+        lines
+    );
+    // a proven win is not searched deeper
+    assert_eq!(lines.len(), 2, "{:?}", lines);
+    engine.quit();
+}
+
+#[test]
+fn infinite_searches_stop_on_demand() {
+    let mut engine = Engine::start();
+    engine.send("position fen x5o/7/7/7/7/7/o5x o");
+    engine.send("go infinite");
+    engine.send("stop");
+    let lines = engine.until("bestmove");
+    let board = Board::from_fen(STARTPOS).unwrap();
+    let state = Configuration::from_fen("x5o/7/7/7/7/7/o5x o", &board).unwrap();
+    let movement = state.parse_move(&best_move(&lines)).unwrap().unwrap();
+    assert!(state.check_move(&movement));
+    // invalid positions are reported and ignored
+    engine.send("position fen x5o/7/8 x");
+    engine.send("isready");
+    let lines = engine.until("readyok");
+    assert!(
+        lines[0].starts_with("info string invalid position"),
+        "{:?}",
+        lines
+    );
+    engine.quit();
+}
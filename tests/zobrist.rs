@@ -0,0 +1,96 @@
+//! Zobrist hashes are updated by movements : they must match hashes computed from scratch.
+use blobwar::board::Board;
+use blobwar::configuration::{Configuration, Movement};
+use blobwar::strategy::{AlphaBeta, SearchControl};
+use glob::glob;
+
+mod common;
+use common::play_greedy;
+
+/// Plies played on each board.
+const MAX_PLIES: usize = 200;
+
+/// Same configuration, with its hash computed from scratch.
+fn rebuilt<'b>(state: &Configuration<'b>) -> Configuration<'b> {
+    Configuration::from_fen(&state.to_fen(), state.board()).unwrap()
+}
+
+#[test]
+fn incremental_hashes_match_rebuilt_ones() {
+    for path in glob("boards/*").unwrap() {
+        let name = path
+            .unwrap()
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .into_owned();
+        let board = Board::load(&name).unwrap();
+        let mut state = board.initial_configuration();
+        // greedy players might jump forever
+        for _ in 0..MAX_PLIES {
+            if state.game_over() {
+                break;
+            }
+            // every movement, jumps and captures included
+            for movement in state.movements() {
+                let next = state.play(&movement);
+                assert_eq!(
+                    next.zobrist_hash(),
+                    rebuilt(&next).zobrist_hash(),
+                    "{}",
+                    name
+                );
+            }
+            play_greedy(&mut state);
+            assert_eq!(
+                state.zobrist_hash(),
+                rebuilt(&state).zobrist_hash(),
+                "{}",
+                name
+            );
+        }
+    }
+}
+
+#[test]
+fn transpositions_share_their_hash() {
+    let board = Board::default();
+    let start = board.initial_configuration();
+    // both players duplicate next to their top corner, in two orders
+    let (red, blue) = (Movement::Duplicate(9), Movement::Duplicate(14));
+    let (other_red, other_blue) = (Movement::Duplicate(1), Movement::Duplicate(6));
+    let first = start
+        .play(&red)
+        .play(&blue)
+        .play(&other_red)
+        .play(&other_blue);
+    let second = start
+        .play(&other_red)
+        .play(&other_blue)
+        .play(&red)
+        .play(&blue);
+    assert!(first == second);
+    assert_eq!(first.zobrist_hash(), second.zobrist_hash());
+    assert_ne!(first.zobrist_hash(), first.skip_play().zobrist_hash());
+}
+
+#[test]
+fn tables_do_not_change_values() {
+    // a search reusing its table across depths finds the values of fresh searches
+    let board = Board::load("standard").unwrap();
+    let state = board.initial_configuration();
+    let mut reused = AlphaBeta::new(1);
+    for depth in 1..=4 {
+        reused.depth = depth;
+        let control = SearchControl::default();
+        let fresh = AlphaBeta::new(depth).search(&state, &control);
+        let again = reused.search(&state, &control);
+        assert_eq!(
+            fresh.map(|(value, _)| value),
+            again.map(|(value, _)| value),
+            "depth {}",
+            depth
+        );
+    }
+    assert!(reused.table_stats().hits > 0);
+}